@@ -33,6 +33,8 @@ from ._internal import (
     SessionConfig,
     RuntimeConfig,
     ScalarUDF,
+    get_display_options,
+    set_display_options,
 )
 
 from .common import (
@@ -52,6 +54,7 @@ from .expr import (
     SimilarTo,
     ScalarVariable,
     Sort,
+    SortExpr,
     TableScan,
     GetIndexedField,
     Not,
@@ -85,6 +88,7 @@ from .expr import (
     DropTable,
     Repartition,
     Partitioning,
+    WindowFrame,
 )
 
 __version__ = importlib_metadata.version(__name__)
@@ -98,14 +102,18 @@ __all__ = [
     "Expr",
     "AggregateUDF",
     "ScalarUDF",
+    "get_display_options",
+    "set_display_options",
     "column",
     "literal",
+    "record_batch",
     "TableScan",
     "Projection",
     "DFSchema",
     "DFField",
     "Analyze",
     "Sort",
+    "SortExpr",
     "Limit",
     "Filter",
     "Like",
@@ -145,6 +153,7 @@ __all__ = [
     "DropTable",
     "Repartition",
     "Partitioning",
+    "WindowFrame",
 ]
 
 
@@ -154,11 +163,19 @@ class Accumulator(metaclass=ABCMeta):
         pass
 
     @abstractmethod
-    def update(self, values: pa.Array) -> None:
+    def update(self, *values: pa.Array) -> None:
+        """
+        Update the state with a batch of rows, given as one array per
+        argument of the aggregate function
+        """
         pass
 
     @abstractmethod
-    def merge(self, states: pa.Array) -> None:
+    def merge(self, *states: pa.Array) -> None:
+        """
+        Merge the states of other accumulators, given as one array per value
+        returned by `state`
+        """
         pass
 
     @abstractmethod
@@ -167,24 +184,76 @@ class Accumulator(metaclass=ABCMeta):
 
 
 def column(value):
+    """
+    Create a column reference. The name may be qualified by its table, as in
+    ``"t.a"``; double quotes escape names containing dots, as in ``'"a.b"'``
+    """
     return Expr.column(value)
 
 
 col = column
 
 
-def literal(value):
-    if not isinstance(value, pa.Scalar):
-        value = pa.scalar(value)
-    return Expr.literal(value)
+def literal(value, type=None):
+    """
+    Create a literal from a ``pyarrow.Scalar`` or a Python value, whose Arrow
+    type is inferred unless ``type`` is given, as for a typed null
+    """
+    return Expr.literal(value, type)
 
 
 lit = literal
 
 
-def udf(func, input_types, return_type, volatility, name=None):
+def record_batch(columns):
+    """
+    Create a pyarrow RecordBatch from a dict of column names to arrays, or to
+    sequences of values that are converted with ``pyarrow.array``
+    """
+    names = list(columns)
+    arrays = [
+        value if isinstance(value, pa.Array) else pa.array(value)
+        for value in columns.values()
+    ]
+    for name, array in zip(names[1:], arrays[1:]):
+        if len(array) != len(arrays[0]):
+            raise ValueError(
+                f"Column {name} has {len(array)} rows but column {names[0]} "
+                f"has {len(arrays[0])}"
+            )
+    return pa.RecordBatch.from_arrays(arrays, names=names)
+
+
+def udf(
+    func,
+    input_types,
+    volatility,
+    return_type=None,
+    name=None,
+    scalar=False,
+    cache=False,
+    cache_size=10_000,
+):
     """
     Create a new User Defined Function
+
+    By default `func` is called once per batch with a pyarrow array for each
+    argument and must return an array. With `scalar=True` it is instead called
+    once per row with Python values, such as `int` or `str` and `None` for
+    nulls, and returns one value. This is convenient but much slower, since
+    every value is converted to and from a Python object.
+
+    If `return_type` is omitted it is inferred when the UDF is created, by
+    calling `func` once with a sample row of arguments, so `func` must not
+    have side effects that matter for that extra call. An error is raised if
+    `func` later returns a different type.
+
+    With `cache=True` the result for each distinct row of arguments is
+    remembered, and `func` is only called for rows it has not seen before,
+    which helps when it is expensive and sees the same inputs repeatedly. The
+    cache is created afresh for each query, so results are never reused
+    across queries. At most `cache_size` results are kept; the cache is
+    emptied when it is full.
     """
     if not callable(func):
         raise TypeError("`func` argument must be callable")
@@ -196,12 +265,18 @@ def udf(func, input_types, return_type, volatility, name=None):
         input_types=input_types,
         return_type=return_type,
         volatility=volatility,
+        scalar=scalar,
+        cache_size=cache_size if cache else None,
     )
 
 
 def udaf(accum, input_type, return_type, state_type, volatility, name=None):
     """
     Create a new User Defined Aggregate Function
+
+    `input_type` is the type of the single argument, or a list with the type
+    of each argument when the function takes several columns, e.g.
+    `[pa.float64(), pa.float64()]` for a weighted mean of values and weights.
     """
     if not issubclass(accum, Accumulator):
         raise TypeError(
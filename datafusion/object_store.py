@@ -18,6 +18,9 @@
 
 from ._internal import object_store
 
+# the Google Cloud Storage store under the name object_store itself uses
+GoogleCloudStorage = object_store.GoogleCloud
+
 
 def __getattr__(name):
     return getattr(object_store, name)
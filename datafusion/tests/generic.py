@@ -16,6 +16,7 @@
 # under the License.
 
 import datetime
+import json
 
 import numpy as np
 import pyarrow as pa
@@ -85,3 +86,31 @@ def write_parquet(path, data):
     table = pa.Table.from_arrays([data], names=["a"])
     pq.write_table(table, path)
     return str(path)
+
+
+def write_delta_commit(root, version, actions):
+    """Writes one commit to the transaction log of a Delta table at root"""
+    log = root / "_delta_log"
+    log.mkdir(parents=True, exist_ok=True)
+    with open(log / f"{version:020}.json", "w") as f:
+        for action in actions:
+            f.write(json.dumps(action) + "\n")
+
+
+def delta_metadata(schema):
+    """The metaData action of an unpartitioned Delta table with the given
+    {name: type} columns"""
+    fields = [
+        {"name": name, "type": ty, "nullable": True, "metadata": {}}
+        for name, ty in schema.items()
+    ]
+    return {
+        "metaData": {
+            "id": "00000000-0000-0000-0000-000000000000",
+            "format": {"provider": "parquet", "options": {}},
+            "schemaString": json.dumps({"type": "struct", "fields": fields}),
+            "partitionColumns": [],
+            "configuration": {},
+            "createdTime": 0,
+        }
+    }
@@ -27,6 +27,7 @@ from datafusion import (
     SessionConfig,
     RuntimeConfig,
     DataFrame,
+    record_batch,
 )
 import pytest
 
@@ -59,6 +60,27 @@ def test_create_context_with_all_valid_args():
         ctx.catalog("datafusion")
 
 
+def test_create_context_with_disabled_optimizer_rules():
+    ctx = SessionContext(disabled_optimizer_rules=["push_down_filter"])
+    batch = pa.RecordBatch.from_arrays(
+        [pa.array([1, 2, 3]), pa.array([4, 5, 6])],
+        names=["a", "b"],
+    )
+    df = ctx.create_dataframe([[batch]])
+    df = df.select(
+        column("a"), (column("a") + column("b")).alias("s")
+    ).filter(column("a") > literal(1))
+
+    # without push_down_filter the filter stays above the projection
+    plan = df.optimized_logical_plan().display_indent()
+    assert plan.index("Filter") < plan.index("Projection")
+
+
+def test_create_context_with_unknown_optimizer_rule():
+    with pytest.raises(Exception, match="Unknown optimizer rule"):
+        SessionContext(disabled_optimizer_rules=["no_such_rule"])
+
+
 def test_register_record_batches(ctx):
     # create a RecordBatch and register it as memtable
     batch = pa.RecordBatch.from_arrays(
@@ -76,6 +98,18 @@ def test_register_record_batches(ctx):
     assert result[0].column(1) == pa.array([-3, -3, -3])
 
 
+def test_record_batch(ctx):
+    batch = record_batch({"a": pa.array([1, 2, 3]), "b": ["x", "y", None]})
+
+    assert batch.schema == pa.schema([("a", pa.int64()), ("b", pa.string())])
+    ctx.register_record_batches("t", [[batch]])
+    result = ctx.sql("SELECT a FROM t WHERE b IS NOT NULL").collect()
+    assert result[0].column(0) == pa.array([1, 2])
+
+    with pytest.raises(ValueError, match="Column b has 2 rows but column a"):
+        record_batch({"a": [1, 2, 3], "b": [1, 2]})
+
+
 def test_create_dataframe_registers_unique_table_name(ctx):
     # create a RecordBatch and register it as memtable
     batch = pa.RecordBatch.from_arrays(
@@ -304,6 +338,28 @@ def test_table_exist(ctx):
     assert ctx.table_exist("t") is True
 
 
+def test_set_and_get_config(ctx):
+    key = "datafusion.execution.parquet.pushdown_filters"
+    assert ctx.get_config(key) == "false"
+
+    ctx.set_config(key, True)
+    assert ctx.get_config(key) == "true"
+
+    ctx.set_config(key, "false")
+    assert ctx.get_config(key) == "false"
+
+    ctx.set_config("datafusion.execution.batch_size", 1024)
+    assert ctx.get_config("datafusion.execution.batch_size") == "1024"
+
+
+def test_unknown_config(ctx):
+    with pytest.raises(KeyError, match="did you mean"):
+        ctx.get_config("execution.parquet.pushdown_filters")
+
+    with pytest.raises(KeyError, match="Unknown configuration option"):
+        ctx.set_config("datafusion.execution.no_such_option", True)
+
+
 def test_read_json(ctx):
     path = os.path.dirname(os.path.abspath(__file__))
 
@@ -0,0 +1,987 @@
+# Licensed to the Apache Software Foundation (ASF) under one
+# or more contributor license agreements.  See the NOTICE file
+# distributed with this work for additional information
+# regarding copyright ownership.  The ASF licenses this file
+# to you under the Apache License, Version 2.0 (the
+# "License"); you may not use this file except in compliance
+# with the License.  You may obtain a copy of the License at
+#
+#   http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing,
+# software distributed under the License is distributed on an
+# "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+# KIND, either express or implied.  See the License for the
+# specific language governing permissions and limitations
+# under the License.
+
+import copy
+import random
+
+import pyarrow as pa
+import pytest
+
+from datafusion.common import DataType, DataTypeMap, PythonType, SqlType
+
+ALL_SQL_TYPES = [
+    getattr(SqlType, name)
+    for name in dir(SqlType)
+    if isinstance(getattr(SqlType, name), SqlType)
+]
+
+# arrow types reachable from the SQL types that have a mapping
+ARROW_TYPES = [
+    DataTypeMap.sql(sql_type).arrow_type
+    for sql_type in [
+        SqlType.BIGINT,
+        SqlType.BINARY,
+        SqlType.BOOLEAN,
+        SqlType.DATE,
+        SqlType.DECIMAL,
+        SqlType.DOUBLE,
+        SqlType.NULL,
+        SqlType.SMALLINT,
+        SqlType.VARBINARY,
+        SqlType.VARCHAR,
+    ]
+]
+
+
+def test_default_value():
+    assert DataTypeMap.sql(SqlType.BIGINT).default_value() == pa.scalar(0)
+    assert DataTypeMap.sql(SqlType.VARCHAR).default_value() == pa.scalar("")
+    assert DataTypeMap.sql(SqlType.NULL).default_value().as_py() is None
+
+
+def test_null_value():
+    null = DataTypeMap.sql(SqlType.BIGINT).null_value()
+    assert null.type == pa.int64()
+    assert null.as_py() is None
+
+    null = DataTypeMap.sql(SqlType.VARCHAR).null_value()
+    assert null.type == pa.string()
+    assert null.as_py() is None
+
+
+def test_data_type_comparison():
+    int64 = DataTypeMap.sql(SqlType.BIGINT).arrow_type
+    utf8 = DataTypeMap.sql(SqlType.VARCHAR).arrow_type
+
+    assert int64 == DataTypeMap.sql(SqlType.BIGINT).arrow_type
+    assert int64 != utf8
+    assert hash(int64) == hash(DataTypeMap.sql(SqlType.BIGINT).arrow_type)
+    assert len({int64, utf8, DataTypeMap.sql(SqlType.BIGINT).arrow_type}) == 2
+
+
+def test_data_type_ordering_is_consistent():
+    hypothesis = pytest.importorskip("hypothesis")
+    st = hypothesis.strategies
+
+    @hypothesis.given(st.lists(st.sampled_from(ARROW_TYPES)))
+    def check(types):
+        ordered = sorted(types)
+        assert sorted(ordered) == ordered
+        assert sorted(reversed(types)) == ordered
+        for a, b in zip(ordered, ordered[1:]):
+            assert a <= b
+            assert not a > b
+            assert (a == b) == (hash(a) == hash(b) and not a < b)
+
+    check()
+
+
+def test_sql_type_str_round_trip():
+    for sql_type in ALL_SQL_TYPES:
+        assert SqlType.from_str(str(sql_type)) == sql_type
+        assert SqlType.from_str(str(sql_type).lower()) == sql_type
+
+
+def test_sql_type_str():
+    assert str(SqlType.INTEGER) == "INTEGER"
+    assert str(SqlType.INTERVAL_DAY) == "INTERVAL DAY"
+    assert str(SqlType.INTERVAL_DAY_HOUR) == "INTERVAL DAY TO HOUR"
+    assert SqlType.from_str("integer") == SqlType.INTEGER
+    assert SqlType.from_str("interval  day") == SqlType.INTERVAL_DAY
+
+    with pytest.raises(ValueError, match="Unknown SQL type"):
+        SqlType.from_str("NOT A TYPE")
+
+
+def test_union():
+    union = pa.union(
+        [pa.field("i", pa.int32()), pa.field("s", pa.string())], mode="dense"
+    )
+    type_map = DataTypeMap.arrow(DataType(union))
+
+    assert type_map.python_type == PythonType.Object
+    assert type_map.sql_type == SqlType.OTHER
+    assert not type_map.is_sparse_union()
+
+    children = type_map.union_children()
+    assert [(type_id, name) for type_id, name, _ in children] == [
+        (0, "i"),
+        (1, "s"),
+    ]
+    assert children[0][2].sql_type == SqlType.INTEGER
+    assert children[1][2].sql_type == SqlType.VARCHAR
+
+
+def test_sql_type_ordering():
+    ordered = sorted(ALL_SQL_TYPES)
+    for seed in range(10):
+        shuffled = list(ALL_SQL_TYPES)
+        random.Random(seed).shuffle(shuffled)
+        assert sorted(shuffled) == ordered
+
+    assert SqlType.BIGINT < SqlType.VARCHAR
+    assert SqlType.VARCHAR >= SqlType.VARCHAR
+
+    priority = {SqlType.TINYINT: 0, SqlType.SMALLINT: 1, SqlType.INTEGER: 2}
+    assert priority[SqlType.SMALLINT] == 1
+
+
+@pytest.mark.skipif(
+    not hasattr(pa, "run_end_encoded"),
+    reason="requires run-end encoded types in pyarrow",
+)
+def test_run_end_encoded():
+    ree = pa.run_end_encoded(pa.int32(), pa.string())
+    type_map = DataTypeMap.arrow(DataType(ree))
+
+    assert type_map.python_type == PythonType.Str
+    assert type_map.sql_type == SqlType.VARCHAR
+    assert type_map.run_ends_type() == DataType(pa.int32())
+
+
+def test_python_type_all_types():
+    all_types = PythonType.all_types()
+
+    assert len(all_types) == len(set(all_types)) == 10
+    assert PythonType.Str in all_types
+    assert sorted(reversed(all_types)) == all_types
+
+
+def test_data_type_map_all_types():
+    sql_types = DataTypeMap.all_sql_types()
+    assert len(sql_types) == len(set(sql_types)) == 48
+    assert SqlType.VARCHAR in sql_types
+    assert sorted(reversed(sql_types)) == sql_types
+    # every variant has its own SQL spelling
+    assert len({str(t) for t in sql_types}) == len(sql_types)
+
+    assert DataTypeMap.all_python_types() == PythonType.all_types()
+
+
+def test_python_type_comparison():
+    assert PythonType.Bool < PythonType.Str
+    assert PythonType.Int <= PythonType.Int
+    assert PythonType.Int != PythonType.Float
+    assert hash(PythonType.Int) == hash(PythonType.Int)
+    assert {PythonType.Int: "int"}[PythonType.Int] == "int"
+
+
+def test_validate():
+    DataTypeMap.sql(SqlType.BIGINT).validate()
+    DataTypeMap.arrow(DataType(pa.int8())).validate()
+
+    type_map = DataTypeMap.sql(SqlType.BIGINT)
+    type_map.python_type = PythonType.Bytes
+    type_map.sql_type = SqlType.VARCHAR
+    with pytest.raises(ValueError, match="python_type .* sql_type"):
+        type_map.validate()
+
+
+def test_context_manager_validates_on_exit():
+    with DataTypeMap.sql(SqlType.BIGINT) as type_map:
+        # temporarily inconsistent inside the block
+        type_map.sql_type = SqlType.VARCHAR
+        type_map.sql_type = SqlType.BIGINT
+    assert type_map.sql_type == SqlType.BIGINT
+
+    with pytest.raises(ValueError, match="sql_type"):
+        with DataTypeMap.sql(SqlType.BIGINT) as type_map:
+            type_map.sql_type = SqlType.VARCHAR
+
+    # errors raised in the block are not replaced by validation errors
+    with pytest.raises(KeyError):
+        with DataTypeMap.sql(SqlType.BIGINT) as type_map:
+            type_map.sql_type = SqlType.VARCHAR
+            raise KeyError("boom")
+
+
+def test_from_arrow_field():
+    field = pa.field("x", pa.int32(), nullable=False, metadata={"k": "v"})
+    type_map = DataTypeMap.from_arrow_field(field)
+
+    assert type_map.arrow_type == DataType(pa.int32())
+    assert type_map.python_type == PythonType.Int
+    assert type_map.sql_type == SqlType.INTEGER
+    assert type_map.nullable is False
+    assert type_map.metadata == {"k": "v"}
+
+    assert type_map.to_arrow_field("x").equals(field, check_metadata=True)
+
+    assert DataTypeMap.sql(SqlType.BIGINT).nullable is True
+
+
+def test_sql_large_types():
+    assert DataTypeMap.sql(SqlType.VARCHAR).arrow_type == DataType(pa.utf8())
+    assert DataTypeMap.sql(SqlType.BINARY).arrow_type == DataType(pa.binary())
+
+    varchar = DataTypeMap.sql(SqlType.VARCHAR, large=True)
+    assert varchar.arrow_type == DataType(pa.large_utf8())
+    assert varchar.python_type == PythonType.Str
+    varchar.validate()
+
+    binary = DataTypeMap.sql(SqlType.BINARY, large=True)
+    assert binary.arrow_type == DataType(pa.large_binary())
+    binary.validate()
+
+    # types without a large variant are unaffected
+    bigint = DataTypeMap.sql(SqlType.BIGINT, large=True)
+    assert bigint.arrow_type == DataType(pa.int64())
+
+
+def test_sql_decimal():
+    type_map = DataTypeMap.sql_decimal(10, 2)
+    assert type_map.arrow_type == DataType(pa.decimal128(10, 2))
+    assert type_map.sql_type == SqlType.DECIMAL
+    type_map.validate()
+
+    assert DataTypeMap.sql_decimal(38, 0).arrow_type == DataType(
+        pa.decimal128(38, 0)
+    )
+    assert DataTypeMap.sql_decimal(76, 10).arrow_type == DataType(
+        pa.decimal256(76, 10)
+    )
+
+    for precision, scale in [(0, 0), (77, 0), (5, 6)]:
+        with pytest.raises(ValueError):
+            DataTypeMap.sql_decimal(precision, scale)
+
+
+def test_sql_char_and_varchar():
+    char = DataTypeMap.sql_char(10)
+    assert char.arrow_type == DataType(pa.utf8())
+    assert char.python_type == PythonType.Str
+    assert char.sql_type == SqlType.CHAR
+    assert char.metadata == {"length": "10"}
+    char.validate()
+    assert DataTypeMap.sql(SqlType.CHAR).arrow_type == DataType(pa.utf8())
+
+    varchar = DataTypeMap.sql_varchar(255)
+    assert varchar.sql_type == SqlType.VARCHAR
+    assert varchar.metadata == {"max_length": "255"}
+    assert DataTypeMap.sql_varchar().metadata == {}
+
+    field = char.to_arrow_field("c")
+    assert field.type == pa.utf8()
+    assert field.metadata == {b"length": b"10"}
+
+
+@pytest.mark.parametrize(
+    ("precision", "unit"),
+    [(0, "s"), (3, "ms"), (4, "us"), (6, "us"), (7, "ns"), (9, "ns")],
+)
+def test_sql_timestamp(precision, unit):
+    type_map = DataTypeMap.sql_timestamp(precision)
+    assert type_map.arrow_type == DataType(pa.timestamp(unit))
+    assert type_map.python_type == PythonType.Datetime
+    assert type_map.sql_type == SqlType.TIMESTAMP
+    type_map.validate()
+
+
+def test_sql_timestamp_with_time_zone():
+    type_map = DataTypeMap.sql_timestamp(3, "UTC")
+    assert type_map.arrow_type == DataType(pa.timestamp("ms", tz="UTC"))
+    assert type_map.sql_type == SqlType.TIMESTAMP_WITH_LOCAL_TIME_ZONE
+    type_map.validate()
+
+    assert DataTypeMap.sql(SqlType.TIMESTAMP).arrow_type == DataType(
+        pa.timestamp("us")
+    )
+    with pytest.raises(ValueError, match="between 0 and 9"):
+        DataTypeMap.sql_timestamp(10)
+
+
+def test_from_schema():
+    schema = pa.schema(
+        [
+            pa.field("id", pa.int64(), nullable=False),
+            pa.field("name", pa.string(), metadata={"k": "v"}),
+            pa.field("at", pa.timestamp("us")),
+        ]
+    )
+    maps = DataTypeMap.from_schema(schema)
+
+    assert [name for name, _ in maps] == ["id", "name", "at"]
+    assert [type_map.sql_type for _, type_map in maps] == [
+        SqlType.BIGINT,
+        SqlType.VARCHAR,
+        SqlType.TIMESTAMP,
+    ]
+    assert maps[0][1].nullable is False
+    assert maps[1][1].metadata == {"k": "v"}
+
+    schema = schema.append(pa.field("tags", pa.list_(pa.string())))
+    with pytest.raises(Exception, match="Cannot map field tags"):
+        DataTypeMap.from_schema(schema)
+
+
+@pytest.mark.skipif(
+    not hasattr(pa.DataType, "_import_from_c_capsule"),
+    reason="requires the Arrow PyCapsule interface in pyarrow",
+)
+def test_arrow_c_schema():
+    for arrow_type in [
+        pa.int64(),
+        pa.timestamp("ms", tz="UTC"),
+        pa.list_(pa.string()),
+    ]:
+        data_type = DataType(arrow_type)
+        # importing moves the schema out of the capsule, so each use needs its own
+        capsule = data_type.__arrow_c_schema__()
+        assert pa.DataType._import_from_c_capsule(capsule) == arrow_type
+
+        capsule = data_type.__arrow_c_schema__()
+        assert DataType.from_arrow_c_schema(capsule) == data_type
+        assert DataType.from_arrow_c_schema(arrow_type) == data_type
+        assert DataType.from_arrow_c_schema(data_type) == data_type
+
+    with pytest.raises(TypeError, match="__arrow_c_schema__"):
+        DataType.from_arrow_c_schema("int64")
+
+
+def test_from_pyarrow_dtype():
+    if not hasattr(pa.int64(), "__arrow_c_schema__"):
+        with pytest.raises(TypeError, match="Arrow PyCapsule interface"):
+            DataTypeMap.from_pyarrow_dtype(pa.int64())
+        return
+
+    type_map = DataTypeMap.from_pyarrow_dtype(pa.int64())
+    assert type_map.arrow_type == DataType(pa.int64())
+    assert type_map.python_type == PythonType.Int
+    assert type_map.sql_type == SqlType.BIGINT
+
+    type_map = DataTypeMap.from_pyarrow_dtype(pa.string())
+    assert type_map.sql_type == SqlType.VARCHAR
+
+    with pytest.raises(TypeError, match="Arrow PyCapsule interface"):
+        DataTypeMap.from_pyarrow_dtype("int64")
+
+
+def test_format():
+    type_map = DataTypeMap.sql(SqlType.BIGINT)
+
+    assert f"{type_map:arrow}" == "Int64"
+    assert f"{type_map:sql}" == "BIGINT"
+    assert f"{type_map:python}" == "Int"
+    assert f"{type_map}" == repr(type_map)
+    assert repr(type_map) == (
+        "DataTypeMap(arrow_type=Int64, python_type=Int, sql_type=BIGINT)"
+    )
+    assert format(DataTypeMap.sql_char(3), "sql") == "CHAR"
+
+    with pytest.raises(ValueError, match="Invalid format specifier 'json'"):
+        f"{type_map:json}"
+
+
+def test_union_operator():
+    int32 = DataTypeMap.arrow(DataType(pa.int32()))
+    int64 = DataTypeMap.sql(SqlType.BIGINT)
+    double = DataTypeMap.sql(SqlType.DOUBLE)
+
+    assert (int32 | int64).arrow_type == DataType(pa.int64())
+    assert (int64 | int32).arrow_type == DataType(pa.int64())
+    assert (int32 | double).arrow_type == DataType(pa.float64())
+    assert int32.upcast(int64).sql_type == SqlType.BIGINT
+
+    utf8 = DataTypeMap.sql(SqlType.VARCHAR)
+    large_utf8 = DataTypeMap.sql(SqlType.VARCHAR, large=True)
+    assert (utf8 | large_utf8).arrow_type == DataType(pa.large_utf8())
+    assert (utf8 | utf8).arrow_type == DataType(pa.utf8())
+
+    with pytest.raises(TypeError, match="Cannot upcast"):
+        int64 | utf8
+    with pytest.raises(TypeError):
+        int64 | 1
+
+
+@pytest.mark.parametrize(
+    "other",
+    [
+        DataTypeMap.sql(SqlType.BIGINT),
+        DataType(pa.int64()),
+        "int64",
+        "Int64",
+        SqlType.BIGINT,
+    ],
+)
+def test_upcast_coerces_argument(other):
+    int32 = DataTypeMap.arrow(DataType(pa.int32()))
+
+    assert int32.upcast(other).arrow_type == DataType(pa.int64())
+
+
+def test_upcast_invalid_argument():
+    int32 = DataTypeMap.arrow(DataType(pa.int32()))
+
+    with pytest.raises(ValueError, match="Unknown or non-primitive"):
+        int32.upcast("int65")
+    with pytest.raises(TypeError, match="Expected a DataTypeMap"):
+        int32.upcast(1)
+
+
+def test_data_type_from_str():
+    assert DataType.from_str("string") == DataType(pa.utf8())
+    assert DataType.from_str("Float64") == DataType(pa.float64())
+    assert DataType.from_str("large_binary") == DataType(pa.large_binary())
+
+
+@pytest.mark.parametrize(
+    ("name", "arrow_type"),
+    [
+        ("decimal(10, 2)", pa.decimal128(10, 2)),
+        ("decimal(5)", pa.decimal128(5, 0)),
+        ("decimal(50,4)", pa.decimal256(50, 4)),
+        ("decimal128(10, 2)", pa.decimal128(10, 2)),
+        ("decimal256(10, 2)", pa.decimal256(10, 2)),
+        ("timestamp[ms]", pa.timestamp("ms")),
+        (
+            "timestamp[us, tz=America/New_York]",
+            pa.timestamp("us", "America/New_York"),
+        ),
+        ("time32[s]", pa.time32("s")),
+        ("time64[ns]", pa.time64("ns")),
+        ("duration[ms]", pa.duration("ms")),
+        ("date32[day]", pa.date32()),
+    ],
+)
+def test_data_type_from_str_parameterized(name, arrow_type):
+    assert DataType.from_str(name) == DataType(arrow_type)
+    # pyarrow prints the types the way they parse
+    assert DataType.from_str(str(arrow_type)) == DataType(arrow_type)
+
+
+@pytest.mark.parametrize(
+    "name",
+    ["decimal(0, 1)", "decimal128(40, 2)", "time32[us]", "timestamp[ms, UTC]"],
+)
+def test_data_type_from_str_invalid_parameters(name):
+    with pytest.raises(ValueError, match="Unknown or non-primitive"):
+        DataType.from_str(name)
+
+
+@pytest.mark.parametrize(
+    ("arrow_type", "size"),
+    [
+        (pa.bool_(), 1),
+        (pa.int8(), 1),
+        (pa.int16(), 2),
+        (pa.int32(), 4),
+        (pa.int64(), 8),
+        (pa.float32(), 4),
+        (pa.float64(), 8),
+        (pa.decimal128(10, 2), 16),
+        (pa.decimal256(40, 2), 32),
+        (pa.date32(), 4),
+        (pa.date64(), 8),
+        (pa.timestamp("ns", tz="UTC"), 8),
+        (pa.utf8(), None),
+        (pa.binary(), None),
+    ],
+)
+def test_approx_memory_per_row(arrow_type, size):
+    type_map = DataTypeMap.arrow(DataType(arrow_type))
+    assert type_map.approx_memory_per_row() == size
+
+
+@pytest.mark.parametrize(
+    ("arrow_type", "min_value", "max_value"),
+    [
+        (pa.int8(), -(2**7), 2**7 - 1),
+        (pa.int16(), -(2**15), 2**15 - 1),
+        (pa.int32(), -(2**31), 2**31 - 1),
+        (pa.int64(), -(2**63), 2**63 - 1),
+        (pa.uint8(), 0, 2**8 - 1),
+        (pa.uint16(), 0, 2**16 - 1),
+        (pa.uint32(), 0, 2**32 - 1),
+        (pa.uint64(), 0, 2**64 - 1),
+        (pa.float32(), -3.4028234663852886e38, 3.4028234663852886e38),
+        (pa.float64(), -1.7976931348623157e308, 1.7976931348623157e308),
+    ],
+)
+def test_min_max_value(arrow_type, min_value, max_value):
+    type_map = DataTypeMap.arrow(DataType(arrow_type))
+
+    assert type_map.min_value().type == arrow_type
+    assert type_map.min_value().as_py() == min_value
+    assert type_map.max_value().as_py() == max_value
+    assert type_map.min_value().as_py() < type_map.max_value().as_py()
+
+    # the upcast type can hold every value of both types
+    wider = type_map | DataTypeMap.arrow(DataType(pa.float64()))
+    assert wider.min_value().as_py() <= type_map.min_value().as_py()
+    assert wider.max_value().as_py() >= type_map.max_value().as_py()
+
+
+def test_min_max_value_non_numeric():
+    decimal = DataTypeMap.arrow(DataType(pa.decimal128(5, 2)))
+    assert str(decimal.max_value().as_py()) == "999.99"
+    assert str(decimal.min_value().as_py()) == "-999.99"
+
+    utf8 = DataTypeMap.sql(SqlType.VARCHAR)
+    assert utf8.max_value() is None
+    assert utf8.min_value() is None
+
+
+@pytest.mark.parametrize("arrow_type", [pa.float16(), pa.decimal256(40, 2)])
+def test_min_max_value_without_scalar_type(arrow_type):
+    # numeric, but DataFusion has no scalar values of these types yet
+    type_map = DataTypeMap.arrow(DataType(arrow_type))
+    assert type_map.max_value() is None
+    assert type_map.min_value() is None
+
+
+NUMERIC_TYPES = [
+    pa.int8(),
+    pa.int16(),
+    pa.int32(),
+    pa.int64(),
+    pa.uint8(),
+    pa.uint16(),
+    pa.uint32(),
+    pa.uint64(),
+    pa.float32(),
+    pa.float64(),
+]
+STRING_TYPES = [pa.utf8(), pa.large_utf8()]
+
+
+@pytest.mark.parametrize("source", NUMERIC_TYPES + STRING_TYPES)
+@pytest.mark.parametrize("target", NUMERIC_TYPES + STRING_TYPES)
+def test_can_cast_to_numeric_and_string(source, target):
+    # numbers cast to each other and to strings, and strings parse to numbers
+    type_map = DataTypeMap.arrow(DataType(source))
+    assert type_map.can_cast_to(DataTypeMap.arrow(DataType(target)))
+    assert type_map.can_cast_to(DataType(target))
+
+
+@pytest.mark.parametrize(
+    ("source", "target", "expected"),
+    [
+        (pa.bool_(), pa.bool_(), True),
+        (pa.bool_(), pa.int32(), True),
+        (pa.int32(), pa.bool_(), True),
+        (pa.bool_(), pa.utf8(), True),
+        (pa.utf8(), pa.bool_(), True),
+        (pa.date32(), pa.date64(), True),
+        (pa.date32(), pa.utf8(), True),
+        (pa.utf8(), pa.date32(), True),
+        (pa.date32(), pa.timestamp("us"), True),
+        (pa.timestamp("us"), pa.int64(), True),
+        (pa.utf8(), pa.binary(), True),
+        (pa.binary(), pa.int64(), False),
+        (pa.date32(), pa.bool_(), False),
+        (pa.bool_(), pa.date32(), False),
+        (pa.null(), pa.int64(), True),
+    ],
+)
+def test_can_cast_to(source, target, expected):
+    type_map = DataTypeMap.arrow(DataType(source))
+    assert type_map.can_cast_to(DataType(target)) is expected
+
+
+@pytest.mark.parametrize(
+    ("arrow_type", "exact", "approximate"),
+    [
+        (pa.int8(), True, False),
+        (pa.int16(), True, False),
+        (pa.int32(), True, False),
+        (pa.int64(), True, False),
+        (pa.uint8(), True, False),
+        (pa.uint16(), True, False),
+        (pa.uint32(), True, False),
+        (pa.uint64(), True, False),
+        (pa.decimal128(10, 2), True, False),
+        (pa.decimal256(40, 2), True, False),
+        (pa.float16(), False, True),
+        (pa.float32(), False, True),
+        (pa.float64(), False, True),
+        (pa.bool_(), False, False),
+        (pa.utf8(), False, False),
+        (pa.date32(), False, False),
+    ],
+)
+def test_is_exact_numeric(arrow_type, exact, approximate):
+    type_map = DataTypeMap.arrow(DataType(arrow_type))
+    assert type_map.is_exact_numeric() is exact
+    assert type_map.is_approximate_numeric() is approximate
+
+
+@pytest.mark.parametrize(
+    ("arrow_type", "category"),
+    [
+        (pa.int8(), "numeric"),
+        (pa.uint64(), "numeric"),
+        (pa.float32(), "numeric"),
+        (pa.decimal128(10, 2), "numeric"),
+        (pa.utf8(), "string"),
+        (pa.large_utf8(), "string"),
+        (pa.date32(), "temporal"),
+        (pa.time64("us"), "temporal"),
+        (pa.timestamp("ms", tz="UTC"), "temporal"),
+        (pa.duration("s"), "temporal"),
+        (pa.binary(), "binary"),
+        (pa.binary(16), "binary"),
+        (pa.bool_(), "boolean"),
+        (pa.list_(pa.int64()), "collection"),
+        (pa.struct([("a", pa.int64())]), "collection"),
+        (pa.map_(pa.utf8(), pa.int64()), "collection"),
+        (pa.null(), "other"),
+    ],
+)
+def test_sql_type_category(arrow_type, category):
+    type_map = DataTypeMap(
+        DataType(arrow_type), PythonType.Object, SqlType.OTHER
+    )
+    assert type_map.sql_type_category() == category
+
+
+def test_describe():
+    int64 = DataTypeMap.arrow(DataType(pa.int64()))
+    assert int64.describe() == {
+        "arrow_type": "Int64",
+        "python_type": "Int",
+        "sql_type": "BIGINT",
+        "nullable": True,
+        "is_numeric": True,
+        "is_temporal": False,
+        "is_nested": False,
+        "byte_width": 8,
+        "metadata": {},
+    }
+
+    decimal = DataTypeMap.arrow(DataType(pa.decimal128(10, 2))).describe()
+    assert (decimal["precision"], decimal["scale"]) == (10, 2)
+
+    timestamp = DataTypeMap.arrow(DataType(pa.timestamp("ms", tz="UTC")))
+    description = timestamp.describe()
+    assert description["is_temporal"]
+    assert (description["unit"], description["timezone"]) == ("ms", "UTC")
+
+    fixed_list = DataTypeMap(
+        DataType(pa.list_(pa.int32(), 3)), PythonType.List, SqlType.ARRAY
+    ).describe()
+    assert fixed_list["is_nested"]
+    assert fixed_list["list_size"] == 3
+    assert fixed_list["byte_width"] is None
+
+
+def test_data_type_deepcopy():
+    data_type = DataType(pa.list_(pa.struct([("a", pa.int64())])))
+    copied = copy.deepcopy(data_type)
+    assert copied == data_type
+    assert copied is not data_type
+
+    nested = {"types": [data_type, DataType(pa.utf8())]}
+    assert copy.deepcopy(nested) == nested
+
+
+@pytest.mark.parametrize(
+    ("arrow_type", "name"),
+    [
+        (pa.null(), "null"),
+        (pa.bool_(), "bool"),
+        (pa.int8(), "int"),
+        (pa.uint64(), "int"),
+        (pa.float16(), "floatingpoint"),
+        (pa.float64(), "floatingpoint"),
+        (pa.decimal128(10, 2), "decimal"),
+        (pa.decimal256(40, 2), "decimal"),
+        (pa.utf8(), "utf8"),
+        (pa.large_utf8(), "largeutf8"),
+        (pa.binary(), "binary"),
+        (pa.large_binary(), "largebinary"),
+        (pa.binary(4), "fixedsizebinary"),
+        (pa.date32(), "date"),
+        (pa.date64(), "date"),
+        (pa.time32("ms"), "time"),
+        (pa.time64("ns"), "time"),
+        (pa.timestamp("us", tz="UTC"), "timestamp"),
+        (pa.duration("s"), "duration"),
+        (pa.month_day_nano_interval(), "interval"),
+        (pa.list_(pa.int64()), "list"),
+        (pa.large_list(pa.int64()), "largelist"),
+        (pa.list_(pa.int64(), 2), "fixedsizelist"),
+        (pa.struct([("a", pa.int64())]), "struct"),
+        (pa.map_(pa.utf8(), pa.int64()), "map"),
+        (pa.dictionary(pa.int32(), pa.utf8()), "utf8"),
+        (pa.run_end_encoded(pa.int32(), pa.utf8()), "runendencoded"),
+    ],
+)
+def test_to_feather_dtype_string(arrow_type, name):
+    type_map = DataTypeMap(
+        DataType(arrow_type), PythonType.Object, SqlType.OTHER
+    )
+    assert type_map.to_feather_dtype_string() == name
+
+
+@pytest.mark.parametrize(
+    ("arrow_type", "spark_type"),
+    [
+        (pa.null(), "void"),
+        (pa.bool_(), "boolean"),
+        (pa.int8(), "tinyint"),
+        (pa.int16(), "smallint"),
+        (pa.int32(), "int"),
+        (pa.int64(), "bigint"),
+        (pa.uint8(), "smallint"),
+        (pa.uint16(), "int"),
+        (pa.uint32(), "bigint"),
+        (pa.uint64(), "decimal(20,0)"),
+        (pa.float16(), "float"),
+        (pa.float32(), "float"),
+        (pa.float64(), "double"),
+        (pa.decimal128(10, 2), "decimal(10,2)"),
+        (pa.utf8(), "string"),
+        (pa.large_utf8(), "string"),
+        (pa.binary(), "binary"),
+        (pa.large_binary(), "binary"),
+        (pa.binary(4), "binary"),
+        (pa.date32(), "date"),
+        (pa.date64(), "date"),
+        (pa.timestamp("us", tz="UTC"), "timestamp"),
+        (pa.timestamp("ns"), "timestamp_ntz"),
+        (pa.duration("us"), "interval day to second"),
+        (pa.month_interval(), "interval year to month"),
+        (pa.list_(pa.utf8()), "array<string>"),
+        (
+            pa.struct([("a", pa.int64()), ("b c", pa.list_(pa.float64()))]),
+            "struct<a:bigint,`b c`:array<double>>",
+        ),
+        (pa.map_(pa.utf8(), pa.int32()), "map<string,int>"),
+        (pa.dictionary(pa.int32(), pa.utf8()), "string"),
+    ],
+)
+def test_to_spark_sql_type(arrow_type, spark_type):
+    type_map = DataTypeMap(
+        DataType(arrow_type), PythonType.Object, SqlType.OTHER
+    )
+    assert type_map.to_spark_sql_type() == spark_type
+
+
+@pytest.mark.parametrize(
+    "arrow_type",
+    [pa.time64("us"), pa.decimal256(50, 2), pa.month_day_nano_interval()],
+)
+def test_to_spark_sql_type_unsupported(arrow_type):
+    type_map = DataTypeMap(
+        DataType(arrow_type), PythonType.Object, SqlType.OTHER
+    )
+    with pytest.raises(Exception, match="Spark SQL has no type"):
+        type_map.to_spark_sql_type()
+
+
+@pytest.mark.parametrize(
+    ("arrow_type", "bigquery_type"),
+    [
+        (pa.bool_(), "BOOL"),
+        (pa.int8(), "INT64"),
+        (pa.int32(), "INT64"),
+        (pa.int64(), "INT64"),
+        (pa.uint32(), "INT64"),
+        (pa.uint64(), "NUMERIC"),
+        (pa.float32(), "FLOAT64"),
+        (pa.float64(), "FLOAT64"),
+        (pa.decimal128(38, 9), "NUMERIC"),
+        (pa.decimal128(10, 2), "NUMERIC"),
+        (pa.decimal128(38, 20), "BIGNUMERIC"),
+        (pa.decimal256(76, 38), "BIGNUMERIC"),
+        (pa.utf8(), "STRING"),
+        (pa.large_utf8(), "STRING"),
+        (pa.binary(), "BYTES"),
+        (pa.binary(16), "BYTES"),
+        (pa.date32(), "DATE"),
+        (pa.time64("us"), "TIME"),
+        (pa.timestamp("us", tz="UTC"), "TIMESTAMP"),
+        (pa.timestamp("us"), "DATETIME"),
+        (pa.month_day_nano_interval(), "INTERVAL"),
+        (pa.list_(pa.int64()), "ARRAY<INT64>"),
+        (
+            pa.struct([("a", pa.utf8()), ("b c", pa.list_(pa.float64()))]),
+            "STRUCT<a STRING, `b c` ARRAY<FLOAT64>>",
+        ),
+        (pa.dictionary(pa.int32(), pa.utf8()), "STRING"),
+    ],
+)
+def test_to_bigquery_type(arrow_type, bigquery_type):
+    type_map = DataTypeMap(
+        DataType(arrow_type), PythonType.Object, SqlType.OTHER
+    )
+    assert type_map.to_bigquery_type() == bigquery_type
+
+
+@pytest.mark.parametrize(
+    "arrow_type",
+    [
+        pa.null(),
+        pa.duration("s"),
+        pa.decimal256(76, 0),
+        pa.list_(pa.list_(pa.int64())),
+        pa.map_(pa.utf8(), pa.int64()),
+    ],
+)
+def test_to_bigquery_type_unsupported(arrow_type):
+    type_map = DataTypeMap(
+        DataType(arrow_type), PythonType.Object, SqlType.OTHER
+    )
+    with pytest.raises(Exception, match="BigQuery has no type"):
+        type_map.to_bigquery_type()
+
+
+def test_sql_type_precedence():
+    ordered = [
+        SqlType.TINYINT,
+        SqlType.SMALLINT,
+        SqlType.INTEGER,
+        SqlType.BIGINT,
+        SqlType.DECIMAL,
+        SqlType.FLOAT,
+        SqlType.DOUBLE,
+    ]
+    ranks = [DataTypeMap.sql(t).sql_type_precedence() for t in ordered]
+    assert ranks == sorted(set(ranks))
+
+
+def test_sql_type_precedence_consistent_with_upcast():
+    types = [
+        DataTypeMap.arrow(DataType(t))
+        for t in [
+            pa.null(),
+            pa.int8(),
+            pa.int16(),
+            pa.int32(),
+            pa.int64(),
+            pa.uint8(),
+            pa.uint16(),
+            pa.uint32(),
+            pa.uint64(),
+            pa.float16(),
+            pa.float32(),
+            pa.float64(),
+            pa.decimal128(10, 2),
+            pa.utf8(),
+            pa.large_utf8(),
+            pa.binary(),
+            pa.large_binary(),
+            pa.bool_(),
+            pa.date32(),
+        ]
+    ]
+    for a in types:
+        for b in types:
+            try:
+                common = a.upcast(b)
+            except TypeError:
+                continue
+            if common.arrow_type == b.arrow_type:
+                assert a.sql_type_precedence() <= b.sql_type_precedence()
+
+
+def test_list_depth():
+    nested = pa.list_(pa.large_list(pa.list_(pa.int32(), 2)))
+    assert DataType(nested).list_depth() == 3
+    assert DataType(pa.list_(pa.int32())).list_depth() == 1
+    assert DataType(pa.int32()).list_depth() == 0
+    # a struct stops the count, even when it has list fields
+    inner = pa.struct([("a", pa.list_(pa.int32()))])
+    assert DataType(pa.list_(inner)).list_depth() == 1
+
+
+def test_struct_depth():
+    inner = pa.struct([("c", pa.int32())])
+    outer = pa.struct([("a", pa.int32()), ("b", inner)])
+    assert DataType(outer).struct_depth() == 2
+    assert DataType(pa.struct([("b", outer)])).struct_depth() == 3
+    assert DataType(pa.struct([])).struct_depth() == 1
+    assert DataType(pa.list_(outer)).struct_depth() == 0
+
+
+@pytest.mark.parametrize(
+    "arrow_type",
+    [
+        pa.null(),
+        pa.bool_(),
+        pa.int8(),
+        pa.int64(),
+        pa.uint16(),
+        pa.float16(),
+        pa.float32(),
+        pa.float64(),
+        pa.utf8(),
+        pa.large_utf8(),
+        pa.binary(),
+        pa.large_binary(),
+        pa.binary(4),
+        pa.date32(),
+        pa.date64(),
+        pa.time32("s"),
+        pa.time64("ns"),
+        pa.timestamp("us"),
+        pa.timestamp("ms", tz="UTC"),
+        pa.duration("ms"),
+        pa.month_day_nano_interval(),
+        pa.decimal128(10, 2),
+        pa.decimal256(40, 5),
+        pa.list_(pa.utf8()),
+        pa.list_(pa.field("x", pa.int32(), nullable=False)),
+        pa.large_list(pa.list_(pa.int8())),
+        pa.list_(pa.int32(), 2),
+        pa.struct([("a", pa.int32()), pa.field("b", pa.utf8(), False)]),
+        pa.map_(pa.utf8(), pa.int32()),
+        pa.dictionary(pa.int32(), pa.utf8()),
+        pa.sparse_union([pa.field("a", pa.int32()), pa.field("b", pa.utf8())]),
+        pa.run_end_encoded(pa.int32(), pa.utf8()),
+    ],
+)
+def test_arrow_schema_string(arrow_type):
+    type_map = DataTypeMap(
+        DataType(arrow_type), PythonType.Object, SqlType.OTHER
+    )
+    assert type_map.arrow_schema_string() == str(arrow_type)
+
+
+def test_arrow_schema_string_examples():
+    int64 = DataTypeMap.arrow(DataType(pa.int64()))
+    assert int64.arrow_schema_string() == "int64"
+    utf8_list = DataTypeMap(
+        DataType(pa.list_(pa.utf8())), PythonType.List, SqlType.ARRAY
+    )
+    assert utf8_list.arrow_schema_string() == "list<item: string>"
+
+
+def test_sql_decimal_precision_and_scale():
+    type_map = DataTypeMap.sql(SqlType.DECIMAL, precision=10, scale=2)
+    assert type_map.arrow_type == DataType(pa.decimal128(10, 2))
+    assert type_map.sql_type == SqlType.DECIMAL
+    wide = DataTypeMap.sql(SqlType.DECIMAL, precision=50)
+    assert wide.arrow_type == DataType(pa.decimal256(50, 0))
+
+    # the arrow and SQL mappings agree for decimals
+    for arrow_type in [pa.decimal128(10, 2), pa.decimal256(60, 4)]:
+        from_arrow = DataTypeMap.arrow(DataType(arrow_type))
+        from_sql = DataTypeMap.sql(
+            SqlType.DECIMAL,
+            precision=arrow_type.precision,
+            scale=arrow_type.scale,
+        )
+        assert from_sql.arrow_type == from_arrow.arrow_type
+        assert from_sql.python_type == from_arrow.python_type
+
+    with pytest.raises(ValueError, match="scale 5 must not be larger"):
+        DataTypeMap.sql(SqlType.DECIMAL, precision=4, scale=5)
+    with pytest.raises(ValueError, match="requires a precision"):
+        DataTypeMap.sql(SqlType.DECIMAL, scale=2)
+    with pytest.raises(ValueError, match="Only DECIMAL"):
+        DataTypeMap.sql(SqlType.BIGINT, precision=10)
@@ -15,11 +15,28 @@
 # specific language governing permissions and limitations
 # under the License.
 
+import datetime
+import gzip
+import math
+import time
+
 import pyarrow as pa
+import pyarrow.dataset as ds
+import pyarrow.parquet as pq
 import pytest
 
 from datafusion import functions as f
-from datafusion import DataFrame, SessionContext, column, literal, udf
+from datafusion import (
+    DataFrame,
+    SessionConfig,
+    SessionContext,
+    WindowFrame,
+    column,
+    get_display_options,
+    literal,
+    set_display_options,
+    udf,
+)
 
 
 @pytest.fixture
@@ -83,6 +100,95 @@ def test_select_columns(df):
     assert result.column(1) == pa.array([1, 2, 3])
 
 
+def test_select_names_and_expressions(df):
+    df = df.select("c", column("a") + literal(1), "b")
+
+    assert df.schema().names == ["c", "a + Int64(1)", "b"]
+    assert df.to_pydict()["a + Int64(1)"] == [2, 3, 4]
+
+
+def test_getitem(df):
+    result = df.select(df["a"] * df["b"]).collect()[0]
+    assert result.column(0) == pa.array([4, 10, 18])
+
+    assert df[["c", "a"]].schema().names == ["c", "a"]
+    assert df["b", "a"].schema().names == ["b", "a"]
+
+    with pytest.raises(KeyError, match="No field named missing"):
+        df["missing"]
+    with pytest.raises(KeyError, match="No field named missing"):
+        df[["a", "missing"]]
+    with pytest.raises(KeyError, match="No field named missing"):
+        df.select("a", "missing")
+
+
+def test_getitem_qualified(ctx):
+    batch = pa.RecordBatch.from_arrays(
+        [pa.array([1, 2, 3]), pa.array([4, 5, 6])],
+        names=["a", "b"],
+    )
+    ctx.register_record_batches("t", [[batch]])
+    df = ctx.table("t")
+
+    assert df.select(df["t.b"]).to_pydict() == {"b": [4, 5, 6]}
+    assert df[["t.a", "b"]].to_pydict() == {"a": [1, 2, 3], "b": [4, 5, 6]}
+    with pytest.raises(KeyError):
+        df["u.a"]
+
+
+def test_filter_sql(ctx):
+    df = ctx.from_pydict({"x": [5, 11, 12, 20], "y": ["a", None, "c", "d"]})
+
+    result = df.filter_sql("x > 10 AND y IS NOT NULL AND upper(y) <> 'D'")
+    assert result.to_pydict() == {"x": [12], "y": ["c"]}
+    result = df.filter_sql("x BETWEEN 6 AND 15 OR y IN ('a')")
+    assert result.to_pydict()["x"] == [5, 11, 12]
+
+    with pytest.raises(Exception, match="Invalid filter predicate 'x >'"):
+        df.filter_sql("x >")
+    with pytest.raises(Exception, match="after the end of the expression"):
+        df.filter_sql("x > 1 y")
+    with pytest.raises(Exception, match="z"):
+        df.filter_sql("z > 1")
+
+
+def test_transform(df):
+    def add_sum(df, name="sum"):
+        return df.with_column(name, column("a") + column("b"))
+
+    def keep(df, *names):
+        return df.select(*names)
+
+    result = df.transform(add_sum).transform(keep, "a", "sum")
+    assert result.to_pydict() == {"a": [1, 2, 3], "sum": [5, 7, 9]}
+
+    result = df.transform(add_sum, name="total")
+    assert result.schema().names == ["a", "b", "c", "total"]
+
+    steps = [add_sum, lambda df: df.filter(column("sum") > literal(5))]
+    assert df.transform(steps).to_pydict()["sum"] == [7, 9]
+    assert df.transform([]).to_pydict() == df.to_pydict()
+
+
+def test_transform_errors(df):
+    def fails(df):
+        raise ValueError("boom")
+
+    with pytest.raises(RuntimeError, match="step .*fails failed") as e:
+        df.transform(fails)
+    assert isinstance(e.value.__cause__, ValueError)
+    assert e.value.__cause__.__traceback__ is not None
+
+    def not_a_dataframe(df):
+        return df.to_pydict()
+
+    with pytest.raises(TypeError, match="returned dict, expected a DataFrame"):
+        df.transform(not_a_dataframe)
+
+    with pytest.raises(TypeError, match="not callable"):
+        df.transform([not_a_dataframe, 1])
+
+
 def test_filter(df):
     df = df.filter(column("a") > literal(2)).select(
         column("a") + column("b"),
@@ -105,6 +211,77 @@ def test_sort(df):
     assert table.to_pydict() == expected
 
 
+def test_sort_multiple_keys_with_nulls(ctx):
+    df = ctx.from_pydict(
+        {
+            "a": [1, None, 1, 2, None],
+            "b": [3, 1, None, 2, 2],
+        }
+    )
+
+    result = df.sort(
+        column("a").sort(ascending=False, nulls_first=True),
+        column("b").sort(nulls_first=False),
+    ).to_pydict()
+    assert result == {
+        "a": [None, None, 2, 1, 1],
+        "b": [1, 2, 2, 3, None],
+    }
+
+    # nulls sort last when ascending and first when descending by default
+    result = df.sort("a", "b", ascending=[True, False]).to_pydict()
+    assert result == {
+        "a": [1, 1, 2, None, None],
+        "b": [None, 3, 2, 2, 1],
+    }
+
+    result = df.sort(
+        "a", "b", ascending=[True, True], nulls_first=[True, True]
+    ).to_pydict()
+    assert result == {
+        "a": [None, None, 1, 1, 2],
+        "b": [1, 2, None, 3, 2],
+    }
+
+
+def test_sort_by_expression_not_in_projection(df):
+    df = df.select(column("c")).sort(
+        (column("a") + column("b")).sort(ascending=False)
+    )
+    assert df.to_pydict() == {"c": [8, 5, 8]}
+
+    df = df.sort(column("c"), ascending=[False])
+    assert df.to_pydict() == {"c": [8, 8, 5]}
+
+
+def test_sort_invalid_arguments(df):
+    with pytest.raises(Exception, match="Expected 2 values for ascending"):
+        df.sort("a", "b", ascending=[True])
+
+    with pytest.raises(Exception, match="Cannot set the sort order"):
+        df.sort(column("a").sort(), ascending=[True])
+
+
+def test_sort_expr_builders(df):
+    result = df.sort(column("c").desc(), column("a").asc()).to_pydict()
+    assert result["a"] == [1, 3, 2]
+
+    running = f.sum(column("a")).over(order_by=[column("a").desc()])
+    rank = f.window("rank", [], order_by=[column("b").desc()])
+    result = df.select(
+        column("a"), running.alias("running"), rank.alias("rank")
+    ).sort("a")
+    assert result.to_pydict() == {
+        "a": [1, 2, 3],
+        "running": [6, 5, 3],
+        "rank": [3, 2, 1],
+    }
+
+    ordered = f.array_agg(column("a"), order_by=[column("a").desc()])
+    result = df.aggregate([], [ordered.alias("l")]).to_pydict()
+    assert result == {"l": [[3, 2, 1]]}
+
+
 def test_limit(df):
     df = df.limit(1)
 
@@ -115,6 +292,59 @@ def test_limit(df):
     assert len(result.column(1)) == 1
 
 
+def test_limit_with_offset(df):
+    df = df.sort(column("a").sort())
+
+    assert df.limit(2, offset=1).to_pydict()["a"] == [2, 3]
+    assert df.limit(10, offset=100).to_pydict()["a"] == []
+
+
+def test_sample(ctx):
+    df = ctx.from_pydict({"a": list(range(10000))})
+
+    sample = df.sample(0.1, seed=42)
+    rows = sample.to_pydict()["a"]
+    assert 800 < len(rows) < 1200
+    assert df.sample(0.1, seed=42).to_pydict()["a"] == rows
+    assert df.sample(0.1, seed=7).to_pydict()["a"] != rows
+    # the same rows are chosen however the data is partitioned
+    repartitioned = df.repartition(4).sample(0.1, seed=42)
+    assert sorted(repartitioned.to_pydict()["a"]) == sorted(rows)
+
+    assert 800 < df.sample(0.1).count() < 1200
+    assert df.sample(0.0).count() == 0
+    assert df.sample(1.0).count() == 10000
+
+    for fraction in [-0.1, 1.5, float("nan")]:
+        with pytest.raises(ValueError, match="between 0 and 1"):
+            df.sample(fraction)
+    with pytest.raises(NotImplementedError):
+        df.sample(0.5, with_replacement=True)
+
+
+def test_sample_n(ctx):
+    df = ctx.from_pydict({"a": list(range(1000))})
+
+    rows = df.sample_n(10, seed=1).to_pydict()["a"]
+    assert len(set(rows)) == 10
+    assert df.sample_n(10, seed=1).to_pydict()["a"] == rows
+    assert df.sample_n(10).count() == 10
+    assert df.sample_n(2000).count() == 1000
+
+
+def test_head(df):
+    assert df.head(2).count() == 2
+    assert df.head().count() == 3
+
+
+def test_tail(df):
+    sorted_df = df.sort(column("a").sort(ascending=False))
+    assert sorted_df.tail(2).to_pydict()["a"] == [2, 1]
+    assert sorted_df.tail(10).to_pydict()["a"] == [3, 2, 1]
+
+    assert df.tail(2).to_pydict()["a"] == [2, 3]
+
+
 def test_with_column(df):
     df = df.with_column("c", column("a") + column("b"))
 
@@ -142,13 +372,298 @@ def test_with_column_renamed(df):
     assert result.schema.field(2).name == "sum"
 
 
+def test_with_column_replaces_existing(df):
+    df = df.with_column("a", column("a") * literal(10))
+    assert df.to_pydict() == {
+        "a": [10, 20, 30],
+        "b": [4, 5, 6],
+        "c": [8, 5, 8],
+    }
+
+
+def test_with_columns(df):
+    df = df.with_columns(
+        a=column("a") + column("b"), d=column("a") * literal(2)
+    )
+    # both expressions see the original "a"
+    assert df.to_pydict() == {
+        "a": [5, 7, 9],
+        "b": [4, 5, 6],
+        "c": [8, 5, 8],
+        "d": [2, 4, 6],
+    }
+
+    with pytest.raises(Exception, match="Column e is assigned"):
+        df.with_columns(e=column("a"), f=column("e") + literal(1))
+
+
+def test_with_columns_exprs(df):
+    df = df.with_columns(
+        [
+            (column("a") + column("b")).alias("sum"),
+            (column("c") * literal(10)).alias("c"),
+            literal("x").alias("tag"),
+        ]
+    )
+    # replaced columns keep their place, new ones are appended in order
+    assert df.schema().names == ["a", "b", "c", "sum", "tag"]
+    assert df.to_pydict() == {
+        "a": [1, 2, 3],
+        "b": [4, 5, 6],
+        "c": [80, 50, 80],
+        "sum": [5, 7, 9],
+        "tag": ["x", "x", "x"],
+    }
+
+    with pytest.raises(Exception, match="must be aliased"):
+        df.with_columns([column("a") + literal(1)])
+    with pytest.raises(Exception, match="more than once"):
+        df.with_columns(column("a").alias("e"), e=column("b"))
+
+
+def test_drop(df):
+    assert df.drop("a", "c").to_pydict() == {"b": [4, 5, 6]}
+
+    with pytest.raises(KeyError, match="missing"):
+        df.drop("a", "missing")
+    result = df.drop("a", "missing", ignore_missing=True)
+    assert result.schema().names == ["b", "c"]
+
+
+def test_fill_null(ctx):
+    df = ctx.from_pydict(
+        {
+            "i": [1, None, 3],
+            "f": [1.5, None, float("nan")],
+            "s": ["a", None, "c"],
+        }
+    )
+
+    result = df.fill_null(0).to_pydict()
+    assert result["i"] == [1, 0, 3]
+    assert result["f"][:2] == [1.5, 0.0]
+    # NaN is a value, not a null
+    assert math.isnan(result["f"][2])
+    assert result["s"] == ["a", "0", "c"]
+
+    result = df.fill_null(7, subset=["i"]).to_pydict()
+    assert result["i"] == [1, 7, 3]
+    assert result["f"][1] is None
+
+    # "x" cannot be cast to the numeric columns, which are left unchanged
+    result = df.fill_null("x").to_pydict()
+    assert result["i"] == [1, None, 3]
+    assert result["s"] == ["a", "x", "c"]
+    with pytest.raises(TypeError, match="Cannot fill nulls of column i"):
+        df.fill_null("x", strict=True)
+
+
+def test_fill_nan(ctx):
+    df = ctx.from_pydict(
+        {"i": [1, None, 3], "f": [1.5, None, float("nan")]}
+    )
+
+    result = df.fill_nan(0.0).to_pydict()
+    # nulls are not NaN
+    assert result == {"i": [1, None, 3], "f": [1.5, None, 0.0]}
+
+    with pytest.raises(TypeError, match="only float columns"):
+        df.fill_nan(0.0, subset=["i"])
+
+
+def test_drop_null(ctx):
+    df = ctx.from_pydict(
+        {
+            "a": [1, None, None, 4],
+            "b": [float("nan"), 2.0, None, None],
+        }
+    )
+
+    assert df.drop_null().to_pydict()["a"] == [1]
+    assert df.drop_null(how="all").to_pydict()["a"] == [1, None, 4]
+    assert df.drop_null(subset=["b"]).to_pydict()["a"] == [1, None]
+
+    with pytest.raises(ValueError, match="expected 'any' or 'all'"):
+        df.drop_null(how="some")
+
+
+def test_value_counts(ctx):
+    df = ctx.from_pydict(
+        {
+            "a": ["x", "y", None, "x", "z", "x", None],
+            "l": [[1], [2], [3], [4], [5], [6], [7]],
+        }
+    )
+
+    assert df.value_counts("a").to_pydict() == {
+        "a": ["x", None, "y", "z"],
+        "count": [3, 2, 1, 1],
+    }
+
+    result = df.value_counts("a", normalize=True).to_pydict()
+    assert result["a"] == ["x", None, "y", "z"]
+    assert result["proportion"] == pytest.approx([3 / 7, 2 / 7, 1 / 7, 1 / 7])
+
+    result = df.value_counts("a", sort=False).to_pydict()
+    assert sorted(zip(result["count"], result["a"]), key=str) == [
+        (1, "y"),
+        (1, "z"),
+        (2, None),
+        (3, "x"),
+    ]
+
+    with pytest.raises(TypeError, match="column l of nested type"):
+        df.value_counts("l")
+
+    with pytest.raises(KeyError):
+        df.value_counts("missing")
+
+
+def test_null_count(ctx):
+    df = ctx.from_pydict(
+        {
+            "a": [1, None, 3],
+            "b": [None, None, "z"],
+            "l": [[1], None, []],
+        }
+    )
+
+    assert df.null_count().to_pydict() == {"a": [1], "b": [2], "l": [1]}
+
+
+def test_unnest_column(ctx):
+    df = ctx.from_pydict({"a": [1, 2, 3, 4], "l": [[1, 2], None, [], [3]]})
+
+    result = df.unnest_column("l").sort(column("a").sort()).to_pydict()
+    assert result == {"a": [1, 1, 2, 4], "l": [1, 2, None, 3]}
+
+    result = df.unnest_column("l", preserve_nulls=False)
+    result = result.sort(column("a").sort()).to_pydict()
+    assert result == {"a": [1, 1, 4], "l": [1, 2, 3]}
+
+    with pytest.raises(TypeError, match="column a of type Int64"):
+        df.unnest_column("a")
+
+
+def test_unnest_columns_zipped(ctx):
+    df = ctx.from_pydict(
+        {
+            "l": [[1, 2], [3], None],
+            "a": [1, 2, 3],
+            "m": [["x", "y"], None, None],
+        }
+    )
+
+    result = df.unnest_columns("l", "m").sort(column("a").sort())
+    assert result.schema().names == ["l", "a", "m"]
+    assert result.to_pydict() == {
+        "l": [1, 2, 3, None],
+        "a": [1, 1, 2, 3],
+        "m": ["x", "y", None, None],
+    }
+
+    result = df.unnest_columns("l", "m", preserve_nulls=False)
+    assert result.sort(column("a").sort()).to_pydict()["a"] == [1, 1, 2]
+
+    df = ctx.from_pydict({"l": [[1, 2]], "m": [["x"]]})
+    with pytest.raises(Exception, match="have lengths 2 and 1"):
+        df.unnest_columns("l", "m").collect()
+
+
+def test_unpivot(ctx):
+    df = ctx.from_pydict(
+        {
+            "k": ["x", "y"],
+            "m1": [1, 2],
+            "m2": [3, 4],
+            "m3": [5.5, None],
+        }
+    )
+
+    result = df.unpivot(["k"], ["m1", "m2", "m3"])
+    assert result.schema().names == ["k", "variable", "value"]
+    assert result.schema().field("value").type == pa.float64()
+    assert result.count() == 6
+
+    result = df.unpivot(["k"], var_name="metric", value_name="v")
+    result = result.sort(column("k").sort(), column("metric").sort())
+    assert result.to_pydict() == {
+        "k": ["x", "x", "x", "y", "y", "y"],
+        "metric": ["m1", "m2", "m3", "m1", "m2", "m3"],
+        "v": [1.0, 3.0, 5.5, 2.0, 4.0, None],
+    }
+
+    df = ctx.from_pydict({"k": [1], "a": [[1]], "b": [True]})
+    with pytest.raises(TypeError, match="Cannot unpivot columns a"):
+        df.unpivot(["k"])
+
+
+def test_unpivot_options(ctx):
+    df = ctx.from_pydict({"k": ["x", "y"], "m1": [1, None], "m2": [2.5, 3.5]})
+
+    result = df.unpivot(
+        id_vars=["k"],
+        value_vars=["m1", "m2"],
+        variable_name="metric",
+        value_name="value",
+        include_nulls=False,
+    )
+    result = result.sort(column("k").sort(), column("metric").sort())
+    assert result.schema().field("value").type == pa.float64()
+    assert result.to_pydict() == {
+        "k": ["x", "x", "y"],
+        "metric": ["m1", "m2", "m2"],
+        "value": [1.0, 2.5, 3.5],
+    }
+
+    melted = df.melt(["k"]).sort(column("k").sort(), column("variable").sort())
+    assert melted.to_pydict()["value"] == [1.0, 2.5, None, 3.5]
+    assert df.melt(value_vars=["m1", "m2"]).count() == 4
+
+    with pytest.raises(ValueError, match="not both"):
+        df.unpivot(["k"], var_name="a", variable_name="b")
+
+
+def test_pivot(ctx):
+    df = ctx.from_pydict(
+        {
+            "k": ["x", "x", "x", "y", "y"],
+            "category": ["a", "b", "a", "b", None],
+            "v": [1, 2, 3, 4, 5],
+        }
+    )
+
+    result = df.pivot(["k"], "category", "v").sort(column("k").sort())
+    assert result.to_pydict() == {
+        "k": ["x", "y"],
+        "a": [4, None],
+        "b": [2, 4],
+        "null": [None, 5],
+    }
+
+    result = df.pivot(["k"], "category", "v", aggfunc="count")
+    assert result.sort(column("k").sort()).to_pydict()["a"] == [2, 0]
+
+    with pytest.raises(ValueError, match="more than max_columns=2"):
+        df.pivot(["k"], "category", "v", max_columns=2)
+    with pytest.raises(ValueError, match="Unsupported aggfunc 'median'"):
+        df.pivot(["k"], "category", "v", aggfunc="median")
+
+
+def test_unnest_struct_column(ctx):
+    df = ctx.from_pydict({"s": [{"x": 1}, {"x": 2}]})
+
+    with pytest.raises(TypeError, match="Cannot unnest struct column s"):
+        df.unnest_column("s")
+
+
 def test_udf(df):
     # is_null is a pa function over arrays
     is_null = udf(
         lambda x: x.is_null(),
         [pa.int64()],
-        pa.bool_(),
-        volatility="immutable",
+        "immutable",
+        return_type=pa.bool_(),
     )
 
     df = df.select(is_null(column("a")))
@@ -157,6 +672,65 @@ def test_udf(df):
     assert result == pa.array([False, False, False])
 
 
+def test_collect_timeout(ctx):
+    def slow(x):
+        time.sleep(0.2)
+        return x
+
+    slow_udf = udf(
+        slow, [pa.int64()], "volatile", return_type=pa.int64()
+    )
+    batches = [
+        pa.RecordBatch.from_arrays([pa.array([i])], names=["a"])
+        for i in range(20)
+    ]
+    df = ctx.create_dataframe([batches]).select(slow_udf(column("a")))
+
+    start = time.monotonic()
+    with pytest.raises(TimeoutError):
+        df.collect(timeout=0.5)
+    # the query is cancelled rather than run to completion
+    assert time.monotonic() - start < 2
+
+    stream = df.execute_stream(timeout=0.5)
+    with pytest.raises(TimeoutError):
+        while stream.next() is not None:
+            pass
+    # the partial results are discarded rather than ending the iteration early
+    with pytest.raises(TimeoutError):
+        stream.next()
+    with pytest.raises(TimeoutError):
+        next(stream)
+
+    with pytest.raises(ValueError, match="positive"):
+        df.collect(timeout=-1)
+
+    fast = ctx.create_dataframe([batches]).select(column("a"))
+    assert sum(b.num_rows for b in fast.collect(timeout=10)) == 20
+
+
+def test_cancel_stream(ctx):
+    batches = [
+        pa.RecordBatch.from_arrays([pa.array([i])], names=["a"])
+        for i in range(5)
+    ]
+    df = ctx.create_dataframe([batches])
+
+    stream = df.execute_stream()
+    assert stream.next().to_pyarrow().num_rows == 1
+    stream.cancel()
+
+    with pytest.raises(Exception, match="cancelled"):
+        stream.next()
+    with pytest.raises(Exception, match="cancelled"):
+        for _ in stream:
+            pass
+
+    # streams can be iterated until they are exhausted
+    stream = df.execute_stream()
+    assert sum(b.to_pyarrow().num_rows for b in stream) == 5
+
+
 def test_join():
     ctx = SessionContext()
 
@@ -181,6 +755,176 @@ def test_join():
     assert table.to_pydict() == expected
 
 
+def test_df_schema_qualifiers(ctx):
+    left = pa.RecordBatch.from_arrays(
+        [pa.array([1, 2]), pa.array([3, 4])], names=["a", "b"]
+    )
+    right = pa.RecordBatch.from_arrays(
+        [pa.array([1, 2]), pa.array([5, 6])], names=["a", "c"]
+    )
+    ctx.register_record_batches("l", [[left]])
+    ctx.register_record_batches("r", [[right]])
+    df = ctx.sql("SELECT * FROM l JOIN r ON l.a = r.a")
+
+    schema = df.df_schema()
+    assert len(schema) == 4
+    assert [(f.qualifier(), f.name()) for f in schema.fields()] == [
+        ("l", "a"),
+        ("l", "b"),
+        ("r", "a"),
+        ("r", "c"),
+    ]
+    assert schema.field("r.a").qualified_name() == "r.a"
+    assert schema.field("c").qualifier() == "r"
+    assert schema.field("c").field() == pa.field("c", pa.int64())
+    with pytest.raises(KeyError):
+        schema.field("a")
+    with pytest.raises(KeyError):
+        schema.field("missing")
+
+
+def test_schema_field_metadata(ctx, tmp_path):
+    field = pa.field("a", pa.int64(), metadata={"unit": "m"})
+    table = pa.Table.from_arrays([pa.array([1, 2])], schema=pa.schema([field]))
+    pq.write_table(table, tmp_path / "data.parquet")
+
+    df = ctx.read_parquet(str(tmp_path / "data.parquet"))
+    assert df.schema().field("a").metadata == {b"unit": b"m"}
+    assert df.df_schema().field("a").metadata() == {"unit": "m"}
+
+
+@pytest.fixture
+def join_dfs(ctx):
+    left = ctx.from_pydict(
+        {"id": [1, 2, 3], "value": ["a", "b", "c"]}, name="left_t"
+    )
+    right = ctx.from_pydict(
+        {"key": [2, 3, 4], "value": ["x", "y", "z"]}, name="right_t"
+    )
+    return left, right
+
+
+@pytest.mark.parametrize(
+    "how,expected_ids",
+    [
+        ("inner", [2, 3]),
+        ("left", [1, 2, 3]),
+        ("right", [2, 3, None]),
+        ("full", [1, 2, 3, None]),
+        ("semi", [2, 3]),
+        ("anti", [1]),
+    ],
+)
+def test_join_types(join_dfs, how, expected_ids):
+    left, right = join_dfs
+    df = left.join(right, left_on=["id"], right_on=["key"], how=how)
+
+    result = df.to_pydict()
+    assert sorted(result["id"], key=lambda v: (v is None, v)) == expected_ids
+    if how in ("semi", "anti"):
+        assert set(result) == {"id", "value"}
+    else:
+        assert set(result) == {"id", "key", "value_l", "value_r"}
+
+
+def test_join_suffixes(join_dfs):
+    left, right = join_dfs
+    df = left.join(
+        right, left_on=["id"], right_on=["key"], suffixes=("_left", "_right")
+    ).sort(column("id").sort())
+
+    assert df.to_pydict() == {
+        "id": [2, 3],
+        "value_left": ["b", "c"],
+        "key": [2, 3],
+        "value_right": ["x", "y"],
+    }
+
+
+def test_join_left_and_right_suffix(ctx):
+    left = ctx.from_pydict(
+        {"id": [1, 2, 3], "value": ["a", "b", "c"]}, name="left_t"
+    )
+    right = ctx.from_pydict(
+        {"id": [2, 3, 4], "value": ["x", "y", "z"]}, name="right_t"
+    )
+
+    df = left.join(
+        right,
+        left_on=["id"],
+        right_on=["id"],
+        left_suffix="_left",
+        right_suffix="_right",
+    ).sort("id")
+    assert df.schema().names == ["id", "value_left", "value_right"]
+    assert df.to_pydict() == {
+        "id": [2, 3],
+        "value_left": ["b", "c"],
+        "value_right": ["x", "y"],
+    }
+
+    # the single key column keeps the values of the rows only on one side
+    df = left.join(right, join_keys=(["id"], ["id"]), how="full").sort("id")
+    assert df.to_pydict() == {
+        "id": [1, 2, 3, 4],
+        "value_l": ["a", "b", "c", None],
+        "value_r": [None, "x", "y", "z"],
+    }
+
+    with pytest.raises(Exception, match="either suffixes or left_suffix"):
+        left.join(
+            right,
+            join_keys=(["id"], ["id"]),
+            suffixes=("_a", "_b"),
+            left_suffix="_left",
+        )
+
+
+def test_join_on(ctx):
+    left = ctx.from_pydict({"a": [1, 2, 3], "v": [10, 20, 30]}, name="l")
+    right = ctx.from_pydict({"a": [2, 3], "w": [200, 300]}, name="r")
+
+    # "a" exists on both sides, the table name disambiguates it
+    df = left.join_on(right, column("l.a") < column("r.a"), how="left")
+    df = df.sort(column("l.a").sort(), column("r.a").sort())
+
+    plan = df.execution_plan().display_indent()
+    assert "NestedLoopJoinExec" in plan
+    assert "HashJoinExec" not in plan
+
+    table = pa.Table.from_batches(df.collect())
+    assert table.column(0).to_pylist() == [1, 1, 2, 3]
+    assert table.column(2).to_pylist() == [2, 3, 3, None]
+
+    df = left.join_on(
+        right,
+        column("l.a") == column("r.a"),
+        column("v") * literal(10) <= column("w"),
+    )
+    assert sorted(df.to_pydict()["v"]) == [20, 30]
+
+    with pytest.raises(Exception, match="at least one join condition"):
+        left.join_on(right)
+
+
+def test_cross_join(ctx):
+    left = ctx.from_pydict({"a": [1, 2, 3]}, name="l")
+    right = ctx.from_pydict({"b": ["x", "y"]}, name="r")
+
+    df = left.cross_join(right).sort(column("a").sort(), column("b").sort())
+    assert df.to_pydict() == {
+        "a": [1, 1, 2, 2, 3, 3],
+        "b": ["x", "y", "x", "y", "x", "y"],
+    }
+
+
+def test_join_incompatible_key_types(ctx, join_dfs):
+    left, _ = join_dfs
+    right = ctx.from_pydict({"flag": [True, False]})
+    with pytest.raises(Exception, match="Join keys id .* and flag"):
+        left.join(right, left_on=["id"], right_on=["flag"])
+
+
 def test_distinct():
     ctx = SessionContext()
 
@@ -205,6 +949,101 @@ def test_distinct():
     assert df_a.collect() == df_b.collect()
 
 
+def test_distinct_with_nulls(ctx):
+    df = ctx.from_pydict(
+        {"a": [1, None, 1, None, 2], "b": ["x", None, "x", None, "y"]}
+    )
+    result = df.distinct().sort("a").to_pydict()
+    assert result == {"a": [1, 2, None], "b": ["x", "y", None]}
+
+
+def test_distinct_on(ctx):
+    df = ctx.from_pydict(
+        {
+            "k": ["a", "a", "b", "b", None, None],
+            "ts": [1, 2, 1, 3, 5, 4],
+            "v": [10, 20, 30, 40, 50, 60],
+        }
+    )
+    # latest value per key, nulls forming a group of their own
+    result = (
+        df.distinct_on(
+            [column("k")],
+            [column("k"), column("v")],
+            [column("k").sort(), column("ts").sort(ascending=False)],
+        )
+        .sort("k")
+        .to_pydict()
+    )
+    assert result == {"k": ["a", "b", None], "v": [20, 40, 50]}
+
+    with pytest.raises(Exception, match="at least one expression"):
+        df.distinct_on([], [column("k")])
+
+
+def test_union_with_nulls(ctx):
+    df_a = ctx.from_pydict({"a": [1, 2, None], "b": ["x", "y", None]})
+    df_b = ctx.from_pydict({"a": [2, None], "b": ["y", None]})
+
+    result = df_a.union(df_b).sort("a").to_pydict()
+    assert result == {
+        "a": [1, 2, 2, None, None],
+        "b": ["x", "y", "y", None, None],
+    }
+
+    expected = {"a": [1, 2, None], "b": ["x", "y", None]}
+    assert df_a.union(df_b, distinct=True).sort("a").to_pydict() == expected
+    assert df_a.union_distinct(df_b).sort("a").to_pydict() == expected
+
+
+def test_union_by_name(ctx):
+    df_a = ctx.from_pydict({"a": [1], "b": ["x"]})
+    df_b = ctx.from_pydict({"b": ["y"], "a": [2]})
+
+    result = df_a.union(df_b, by_name=True).sort("a")
+    assert result.to_pydict() == {"a": [1, 2], "b": ["x", "y"]}
+
+    df_c = ctx.from_pydict({"b": ["z"], "c": [3]})
+    with pytest.raises(Exception, match="column a is missing"):
+        df_a.union(df_c, by_name=True)
+
+
+def test_union_by_name_fill_missing(ctx):
+    df_a = ctx.from_pydict({"a": [1], "b": ["x"], "c": [1.5]})
+    df_b = ctx.from_pydict({"d": [True], "b": ["y"], "a": [2]})
+
+    result = df_a.union_by_name(df_b).sort("a")
+    assert result.to_pydict() == {
+        "a": [1, 2],
+        "b": ["x", "y"],
+        "c": [1.5, None],
+        "d": [None, True],
+    }
+    assert result.schema().field("c").type == pa.float64()
+    assert result.schema().field("d").type == pa.bool_()
+
+    duplicated = df_a.union_by_name(df_a.select("c", "b", "a"), distinct=True)
+    assert duplicated.count() == 1
+
+    with pytest.raises(Exception, match="column d is missing"):
+        df_a.union_by_name(df_b, fill_missing=False)
+
+    df_c = ctx.from_pydict({"b": [[1, 2]], "a": [3]})
+    with pytest.raises(Exception, match="incompatible schemas"):
+        df_a.union_by_name(df_c)
+
+
+def test_set_operation_schema_mismatch(ctx):
+    df_a = ctx.from_pydict({"a": [1], "b": ["x"]})
+    df_b = ctx.from_pydict({"a": [1]})
+
+    for op in (df_a.union, df_a.intersect, df_a.except_all):
+        with pytest.raises(
+            Exception, match=r"\[a: Int64, b: Utf8\] and \[a: Int64\]"
+        ):
+            op(df_b)
+
+
 def test_window_functions(df):
     df = df.select(
         column("a"),
@@ -291,6 +1130,136 @@ def test_window_functions(df):
     assert table.sort_by("a").to_pydict() == expected
 
 
+@pytest.fixture
+def window_df(ctx):
+    table = pa.table(
+        {
+            "ts": pa.array(
+                [datetime.datetime(2021, 1, d) for d in (1, 2, 4, 5, 6)],
+                pa.timestamp("ns"),
+            ),
+            "g": ["a", "b", "a", "b", "a"],
+            "x": [1, 2, 3, 4, 5],
+        }
+    )
+    return ctx.from_arrow_table(table)
+
+
+def test_window_running_sum(window_df):
+    running = f.sum(column("x")).over(
+        order_by=[column("ts")], frame=WindowFrame("rows", None, 0)
+    )
+    last_two = f.window(
+        "sum",
+        [column("x")],
+        order_by=[column("ts").sort()],
+        window_frame=WindowFrame("rows", 1, 0),
+    )
+    df = window_df.select(
+        column("x"), running.alias("running"), last_two.alias("last_two")
+    ).sort(column("x").sort())
+
+    assert df.to_pydict() == {
+        "x": [1, 2, 3, 4, 5],
+        "running": [1, 3, 6, 10, 15],
+        "last_two": [1, 3, 5, 7, 9],
+    }
+
+
+def test_window_lag_over_partitions(window_df):
+    lag = f.window(
+        "lag",
+        [column("x")],
+        partition_by=[column("g")],
+        order_by=[column("ts")],
+    )
+    df = window_df.select(column("x"), lag.alias("previous"))
+    df = df.sort(column("x").sort())
+
+    assert df.to_pydict()["previous"] == [None, None, 1, 2, 3]
+
+
+def test_window_range_frame_over_timestamps(window_df):
+    one_day = pa.scalar(
+        pa.MonthDayNano([0, 1, 0]), type=pa.month_day_nano_interval()
+    )
+    frame = WindowFrame("range", one_day, 0)
+    assert frame.units() == "range"
+
+    window_sum = f.sum(column("x")).over(order_by=[column("ts")], frame=frame)
+    df = window_df.select(column("x"), window_sum.alias("s"))
+    df = df.sort(column("x").sort())
+
+    # the 4th has no row on the day before
+    assert df.to_pydict()["s"] == [1, 3, 3, 7, 9]
+
+    with pytest.raises(Exception, match="Unknown window frame units"):
+        WindowFrame("minutes", None, 0)
+
+
+def test_aggregate_with_expression_keys(ctx):
+    table = pa.table(
+        {
+            "ts": pa.array(
+                [
+                    datetime.datetime(2021, 1, 1, 1),
+                    datetime.datetime(2021, 1, 1, 13),
+                    datetime.datetime(2021, 1, 2, 8),
+                ],
+                pa.timestamp("ns"),
+            ),
+            "g": ["a", "b", "a"],
+            "x": [1, 2, 3],
+        }
+    )
+    df = ctx.from_arrow_table(table)
+
+    day = f.date_trunc(literal("day"), column("ts")).alias("day")
+    result = (
+        df.aggregate([day, "g"], [f.sum(column("x")).alias("total")])
+        .sort(column("day").sort(), column("g").sort())
+        .to_pydict()
+    )
+    assert result["total"] == [1, 2, 3]
+    assert result["g"] == ["a", "b", "a"]
+
+    # filtering on the aggregate alias works like HAVING
+    result = (
+        df.aggregate(["g"], [f.sum(column("x")).alias("total")])
+        .filter(column("total") > literal(2))
+        .to_pydict()
+    )
+    assert result == {"g": ["a"], "total": [4]}
+
+    # no group keys gives a single row
+    result = df.aggregate([], [f.count(column("x")).alias("n")]).to_pydict()
+    assert result == {"n": [3]}
+
+    with pytest.raises(Exception, match="Duplicate output column name g"):
+        df.aggregate(["g"], [f.max(column("x")).alias("g")])
+
+
+def test_aggregate_over(ctx):
+    batch = pa.RecordBatch.from_arrays(
+        [pa.array([1, 2, 3, 4]), pa.array(["a", "b", "a", "b"])],
+        names=["x", "g"],
+    )
+    ctx.register_record_batches("t", [[batch]])
+
+    df = ctx.table("t").select(
+        column("x"),
+        f.sum(column("x")).over(partition_by=[column("g")]).alias("s"),
+    )
+    expected = ctx.sql("SELECT x, SUM(x) OVER (PARTITION BY g) AS s FROM t")
+    result = pa.Table.from_batches(df.collect()).sort_by("x")
+    expected = pa.Table.from_batches(expected.collect()).sort_by("x")
+    assert result.to_pydict() == expected.to_pydict()
+    assert result.to_pydict()["s"] == [4, 6, 4, 6]
+
+    with pytest.raises(Exception, match="requires an aggregate"):
+        (column("x") + literal(1)).over(partition_by=[column("g")])
+
+
 def test_get_dataframe(tmp_path):
     ctx = SessionContext()
 
@@ -324,12 +1293,123 @@ def test_struct_select(struct_df):
     assert result.column(1) == pa.array([-3, -3, -3])
 
 
-def test_explain(df):
+def test_explain(df, capsys):
     df = df.select(
         column("a") + column("b"),
         column("a") - column("b"),
     )
     df.explain()
+    out = capsys.readouterr().out
+    assert "logical_plan" in out
+    assert "physical_plan" in out
+    assert out.strip() == df.explain_string().strip()
+
+    text = df.explain_string(analyze=True)
+    assert "ProjectionExec" in text
+    assert "output_rows=3" in text
+
+
+def test_explain_verbose(df):
+    df = df.filter(column("a") > literal(1)).select(column("a") + column("b"))
+
+    stages = df.explain(format="stages")
+    assert [plan_type for plan_type, _ in stages] == [
+        "logical_plan",
+        "physical_plan",
+    ]
+
+    stages = df.explain(verbose=True, format="stages")
+    plan_types = [plan_type for plan_type, _ in stages]
+    assert plan_types[0] == "initial_logical_plan"
+    assert "physical_plan" in plan_types
+    assert len(set(plan_types)) > 2
+    assert "initial_logical_plan" in df.explain_string(verbose=True)
+
+    stages = df.explain(analyze=True, format="stages")
+    assert len(stages) == 1
+    assert "output_rows=" in stages[0][1]
+
+
+def test_explain_dict(df):
+    df = df.select(column("a") + column("b"))
+
+    def names(plan):
+        yield plan["name"]
+        for child in plan["children"]:
+            yield from names(child)
+
+    plan = df.explain(format="dict")
+    assert plan["name"] == "ProjectionExec"
+    assert plan["description"].startswith("ProjectionExec: expr=")
+    assert plan["metrics"] == {}
+    assert "MemoryExec" in set(names(plan))
+
+    plan = df.explain(analyze=True, format="dict")
+    assert plan["metrics"]["output_rows"] == 3
+    assert "elapsed_compute" in plan["metrics"]
+
+    with pytest.raises(ValueError, match="Unknown explain format"):
+        df.explain(format="yaml")
+
+
+def test_show_reports_truncation(df, capsys):
+    df.show(2)
+    out = capsys.readouterr().out
+
+    assert "| a | b | c |" in out
+    assert "showing 2 of 3 rows" in out
+
+    df.show()
+    assert "showing" not in capsys.readouterr().out
+
+
+def test_show_str(ctx):
+    df = ctx.from_pydict({"a": list(range(10)), "b": ["x"] * 10})
+    lines = df.show_str(5).splitlines()
+
+    assert "| a | b |" in lines
+    rows = [line for line in lines if line.startswith("| ") and "x" in line]
+    assert len(rows) == 5
+    assert lines[-1] == "showing 5 of 10 rows"
+
+
+def test_show_pushes_a_limit_into_the_plan(ctx):
+    df = ctx.from_pydict({"a": list(range(100))})
+    plan = df._show_plan(5).display_indent()
+
+    assert "GlobalLimitExec: skip=0, fetch=6" in plan
+
+
+def test_show_without_exact_row_count(ctx):
+    df = ctx.from_pydict({"a": list(range(10))}).filter(column("a") > literal(2))
+    lines = df.show_str(5).splitlines()
+
+    assert lines[-1] == "showing the first 5 rows, more not shown"
+
+
+def test_show_abbreviates_long_values(ctx, capsys):
+    df = ctx.from_pydict({"s": ["x" * 100]})
+    df.show()
+    out = capsys.readouterr().out
+
+    assert "x" * 29 + "..." in out
+    assert "x" * 100 not in out
+
+
+def test_repr_html(df):
+    old_options = get_display_options()
+    try:
+        set_display_options(max_rows=2, max_columns=2)
+        html = df._repr_html_()
+
+        assert "<th>a</th><th>b</th><th>...</th>" in html
+        assert "<th>c</th>" not in html
+        # two rows plus one row of truncation markers
+        assert html.count("<tr>") == 4
+
+        assert "..." in repr(df)
+    finally:
+        set_display_options(**old_options)
 
 
 def test_logical_plan(aggregate_df):
@@ -363,6 +1443,23 @@ def test_optimized_logical_plan(aggregate_df):
     assert expected == plan.display_indent()
 
 
+def test_optimized_logical_plan_pushes_down_filter(df):
+    df = df.select(
+        column("a"), (column("a") + column("b")).alias("s")
+    ).filter(column("a") > literal(1))
+
+    def operators(plan):
+        return [line.strip().split(":")[0] for line in plan.splitlines()]
+
+    # the unoptimized plan keeps the filter above the projection
+    raw = operators(df.logical_plan().display_indent())
+    assert raw.index("Filter") < raw.index("Projection")
+
+    # the optimizer pushes the filter below the projection
+    optimized = operators(df.optimized_logical_plan().display_indent())
+    assert optimized.index("Projection") < optimized.index("Filter")
+
+
 def test_execution_plan(aggregate_df):
     plan = aggregate_df.execution_plan()
 
@@ -398,12 +1495,106 @@ def test_execution_plan(aggregate_df):
     assert batch is None
 
 
+def test_plan_to_dot(ctx):
+    left = ctx.from_pydict({"k": [1, 1, 2], "v": [10, 20, 30]}, name="l")
+    right = ctx.from_pydict({"k": [1, 2], "name": ["x", "y"]}, name="r")
+    df = left.join_on(right, column("l.k") == column("r.k")).filter(
+        column("v") > literal(15)
+    )
+
+    dot = df.plan_to_dot()
+    assert dot.startswith("digraph plan {")
+    assert dot.endswith("}")
+    assert '[label="Filter: l.v > Int64(15)"]' in dot
+    assert '[label="Inner Join: ' in dot
+    assert "l.k = r.k" in dot
+    assert "node0 -> node1;" in dot
+    # every node but the root has an incoming edge
+    assert dot.count("[label=") == dot.count(" -> ") + 1
+
+    dot = df.plan_to_dot(physical=True)
+    assert "HashJoinExec: mode=" in dot or "NestedLoopJoinExec" in dot
+    assert "FilterExec: v@" in dot
+
+
+def test_plan_accessors_join_aggregate(ctx):
+    calls = []
+
+    def tracked(values):
+        calls.append(len(values))
+        return values
+
+    tracked = udf(
+        tracked, [pa.int64()], "volatile", return_type=pa.int64()
+    )
+    left = ctx.from_pydict({"k": [1, 1, 2], "v": [10, 20, 30]}, name="l")
+    right = ctx.from_pydict({"k": [1, 2], "name": ["x", "y"]}, name="r")
+    df = (
+        left.join_on(right, column("l.k") == column("r.k"))
+        .aggregate([column("name")], [f.sum(tracked(column("v"))).alias("s")])
+        .sort(column("name").sort())
+    )
+
+    logical = df.logical_plan()
+    assert logical.display().startswith("Sort:")
+    indent = logical.display_indent()
+    assert "Aggregate:" in indent
+    assert "Inner Join:" in indent
+    assert "s:Int64" in logical.display_indent_schema()
+    assert [plan.display().split(":")[0] for plan in logical.inputs()] == [
+        "Aggregate"
+    ]
+
+    optimized = df.optimized_logical_plan()
+    assert "Inner Join: l.k = r.k" in optimized.display_indent()
+
+    physical = df.execution_plan()
+    assert "HashJoinExec" in physical.display_indent()
+    assert "AggregateExec" in physical.display_indent()
+    # building the plans does not run the query
+    assert calls == []
+
+    expected = {"name": ["x", "y"], "s": [30, 30]}
+    assert df.to_pydict() == expected
+    calls.clear()
+
+    # the logical plan round-trips through a new DataFrame
+    df = ctx.create_dataframe_from_logical_plan(logical)
+    assert df.to_pydict() == expected
+
+    # and the execution plan can be run partition by partition
+    batches = []
+    for partition in range(physical.partition_count):
+        batches.extend(ctx.execute(physical, partition))
+    assert pa.Table.from_batches(
+        [batch.to_pyarrow() for batch in batches]
+    ).to_pydict() == expected
+
+
 def test_repartition(df):
-    df.repartition(2)
+    df = df.repartition(2)
+
+    plan = df.execution_plan().display_indent()
+    assert "RepartitionExec: partitioning=RoundRobinBatch(2)" in plan
+    assert len(df.collect_partitioned()) == 2
 
 
 def test_repartition_by_hash(df):
-    df.repartition_by_hash(column("a"), num=2)
+    df = df.repartition_by_hash(column("a"), num=3)
+
+    plan = df.execution_plan().display_indent()
+    assert "RepartitionExec: partitioning=Hash(" in plan
+    partitions = df.collect_partitioned()
+    assert len(partitions) == 3
+    rows = sum(batch.num_rows for batches in partitions for batch in batches)
+    assert rows == 3
+
+
+def test_repartition_by_hash_nested_type(ctx):
+    df = ctx.from_pydict({"l": [[1], [2, 3]]})
+
+    with pytest.raises(Exception, match="cannot be hashed"):
+        df.repartition_by_hash(column("l"), num=2)
 
 
 def test_intersect():
@@ -526,13 +1717,240 @@ def test_union_distinct(ctx):
     assert df_c.collect() == df_a_u_b.collect()
 
 
+def test_statistics(tmp_path):
+    path = tmp_path / "stats.parquet"
+    table = pa.table({"a": [3, None, 7, 5], "b": ["x", "z", None, "y"]})
+    pq.write_table(table, path)
+
+    config = SessionConfig().set(
+        "datafusion.execution.collect_statistics", "true"
+    )
+    ctx = SessionContext(config)
+    statistics = ctx.read_parquet(str(path)).statistics()
+
+    assert statistics["num_rows"] == 4
+    assert statistics["is_exact"]
+    assert statistics["columns"]["a"]["min"] == 3
+    assert statistics["columns"]["a"]["max"] == 7
+    assert statistics["columns"]["a"]["null_count"] == 1
+    assert statistics["columns"]["b"]["null_count"] == 1
+
+    # in-memory data has no statistics to report
+    df = SessionContext().from_pydict({"a": [1, 2]})
+    statistics = df.filter(column("a") > literal(1)).statistics()
+    assert not statistics["is_exact"]
+    columns = statistics["columns"]
+    assert columns["a"]["min"] is None
+    assert columns["a"]["distinct_count"] is None
+
+
 def test_cache(df):
     assert df.cache().collect() == df.collect()
 
 
+def test_cache_replaces_scan(ctx, tmp_path):
+    path = tmp_path / "cache.parquet"
+    pq.write_table(pa.table({"a": [1, 2, 3], "b": ["x", "y", "z"]}), path)
+    df = ctx.read_parquet(str(path)).filter(column("a") > literal(1))
+    assert df.cache_stats() is None
+
+    cached = df.cache()
+    stats = cached.cache_stats()
+    assert stats["num_rows"] == 2
+    assert stats["size_bytes"] > 0
+    assert cached.schema() == df.schema()
+
+    plan = cached.select(column("b")).execution_plan().display_indent()
+    assert "MemoryExec" in plan
+    assert "ParquetExec" not in plan
+
+    # later changes to the source are not seen by the cached frame
+    path.unlink()
+    assert cached.sort(column("a").sort()).to_pydict() == {
+        "a": [2, 3],
+        "b": ["y", "z"],
+    }
+
+
 def test_count(df):
     # Get number of rows
     assert df.count() == 3
+    assert df.limit(2).count() == 2
+    assert df.select(column("c")).count(distinct=True) == 2
+    assert df.filter(column("a") > literal(5)).count() == 0
+
+
+def test_count_empty(ctx):
+    batch = pa.RecordBatch.from_arrays(
+        [pa.array([], type=pa.int64())], names=["a"]
+    )
+    assert ctx.create_dataframe([[batch]]).count() == 0
+    assert ctx.create_dataframe([[batch]]).count(distinct=True) == 0
+
+
+def test_is_empty(df):
+    assert not df.is_empty()
+    assert df.filter(column("a") > literal(5)).is_empty()
+    assert df.limit(0).is_empty()
+
+
+def test_write_parquet(ctx, df, tmp_path):
+    path = tmp_path / "a.parquet"
+    df.write_parquet(
+        str(path),
+        compression="zstd",
+        compression_level=3,
+        row_group_size=2,
+        single_file=True,
+    )
+
+    metadata = pq.ParquetFile(path).metadata
+    assert metadata.num_row_groups == 2
+    assert metadata.row_group(0).column(0).compression == "ZSTD"
+    assert pq.read_table(path).to_pydict() == df.to_pydict()
+    result = ctx.read_parquet(str(path)).sort(column("a").sort())
+    assert result.to_pydict() == df.to_pydict()
+
+
+def test_write_parquet_partitions(ctx, df, tmp_path):
+    path = tmp_path / "out"
+    df.repartition(2).write_parquet(str(path), compression="snappy")
+
+    files = sorted(p.name for p in path.iterdir())
+    assert files == ["part-0.parquet", "part-1.parquet"]
+    assert ds.dataset(path).to_table().num_rows == 3
+    # round-robin repartitioning may leave a partition, and its file, empty
+    for name in files:
+        metadata = pq.ParquetFile(path / name).metadata
+        for i in range(metadata.num_row_groups):
+            assert metadata.row_group(i).column(0).compression == "SNAPPY"
+
+
+def test_write_parquet_invalid_options(df, tmp_path):
+    path = str(tmp_path / "a.parquet")
+    with pytest.raises(ValueError, match="expected one of uncompressed"):
+        df.write_parquet(path, compression="lzo")
+    with pytest.raises(ValueError, match="does not take a compression level"):
+        df.write_parquet(path, compression="snappy", compression_level=1)
+    with pytest.raises(ValueError, match="statistics_enabled"):
+        df.write_parquet(path, statistics_enabled="all")
+
+
+def test_write_csv(ctx, tmp_path):
+    df = ctx.from_pydict(
+        {
+            "a": [1, 2, None],
+            "s": ["x", 'with "quotes"', "with,comma"],
+            "d": [datetime.date(2021, 1, 2), None, None],
+        }
+    )
+    path = tmp_path / "a.csv"
+    df.write_csv(
+        str(path), null_value="NA", date_format="%d/%m/%Y", single_file=True
+    )
+
+    lines = path.read_text().splitlines()
+    assert lines == [
+        "a,s,d",
+        "1,x,02/01/2021",
+        '2,"with ""quotes""",NA',
+        'NA,"with,comma",NA',
+    ]
+
+    df = df.select_columns("a", "s")
+    df.write_csv(str(path), single_file=True)
+    result = ctx.read_csv(str(path)).to_pydict()
+    assert result == df.to_pydict()
+
+    with pytest.raises(ValueError, match="Delimiter must be a single"):
+        df.write_csv(str(path), delimiter="::")
+
+
+def test_write_csv_partitions(ctx, df, tmp_path):
+    path = tmp_path / "out"
+    df.repartition(2).write_csv(str(path), delimiter="|")
+
+    assert sorted(p.name for p in path.iterdir()) == [
+        "part-0.csv",
+        "part-1.csv",
+    ]
+    ctx.register_csv("t", str(path), delimiter="|")
+    result = ctx.sql("SELECT * FROM t ORDER BY a").to_pydict()
+    assert result == df.to_pydict()
+
+
+def test_write_json(ctx, df, tmp_path):
+    path = tmp_path / "a.json"
+    df.write_json(str(path), single_file=True)
+
+    result = ctx.read_json(str(path)).sort(column("a").sort())
+    assert result.to_pydict() == df.to_pydict()
+
+    path = tmp_path / "a.json.gz"
+    df.write_json(str(path), compression="gzip", single_file=True)
+    with gzip.open(path, "rt") as f:
+        assert f.readline() == '{"a":1,"b":4,"c":8}\n'
+
+    with pytest.raises(ValueError, match="Unsupported compression 'lzo'"):
+        df.write_json(str(path), compression="lzo")
+
+
+def test_write_partitioned(ctx, tmp_path):
+    df = ctx.from_pydict(
+        {
+            "year": [2023, 2023, 2024],
+            "month": ["07", "08", "07"],
+            "v": [1, 2, 3],
+        }
+    )
+    path = tmp_path / "out"
+    df.write_parquet(str(path), partition_by=["year", "month"])
+
+    assert sorted(
+        str(p.relative_to(path)) for p in path.rglob("*.parquet")
+    ) == [
+        "year=2023/month=07/part-0.parquet",
+        "year=2023/month=08/part-0.parquet",
+        "year=2024/month=07/part-0.parquet",
+    ]
+    file = path / "year=2023/month=07/part-0.parquet"
+    assert pq.read_table(file).column_names == ["v"]
+
+    ctx.register_parquet(
+        "t",
+        str(path),
+        table_partition_cols=[("year", pa.int64()), ("month", "string")],
+    )
+    result = ctx.sql("SELECT year, month, v FROM t ORDER BY v")
+    assert result.to_pydict() == df.to_pydict()
+
+    for write, extension in [
+        (df.write_csv, "csv"),
+        (df.write_json, "json"),
+    ]:
+        out = tmp_path / extension
+        write(str(out), partition_by=["month"])
+        assert sorted(p.name for p in out.iterdir()) == [
+            "month=07",
+            "month=08",
+        ]
+        assert (out / "month=08" / f"part-0.{extension}").exists()
+
+
+def test_write_partitioned_escapes_values(ctx, tmp_path):
+    df = ctx.from_pydict({"k": ["a/b", None, ""], "v": [1, 2, 3]})
+    path = tmp_path / "out"
+    df.write_parquet(str(path), partition_by=["k"])
+
+    assert sorted(p.name for p in path.iterdir()) == [
+        "k=__HIVE_DEFAULT_PARTITION__",
+        "k=a%2Fb",
+    ]
+    rows = pq.read_table(path / "k=__HIVE_DEFAULT_PARTITION__")
+    assert sorted(rows.column("v").to_pylist()) == [2, 3]
+
+    with pytest.raises(Exception, match="single file"):
+        df.write_parquet(str(path), partition_by=["k"], single_file=True)
 
 
 def test_to_pandas(df):
@@ -595,6 +2013,40 @@ def test_empty_to_arrow_table(df):
     assert set(pyarrow_table.column_names) == {"a", "b", "c"}
 
 
+@pytest.mark.skipif(
+    not hasattr(pa.RecordBatchReader, "from_stream"),
+    reason="requires the Arrow PyCapsule interface",
+)
+def test_arrow_c_stream(df):
+    reader = pa.RecordBatchReader.from_stream(df)
+    assert reader.read_all() == df.to_arrow_table()
+
+    schema = pa.schema([("b", pa.float64()), ("a", pa.int32())])
+    reader = pa.RecordBatchReader.from_stream(df, schema=schema)
+    table = reader.read_all()
+    assert table.schema == schema
+    assert table.to_pydict() == {"b": [4.0, 5.0, 6.0], "a": [1, 2, 3]}
+
+
+def test_arrow_c_stream_rejects_other_capsules(df):
+    stream_capsule = df.__arrow_c_stream__()
+    with pytest.raises(TypeError, match="arrow_schema"):
+        df.__arrow_c_stream__(stream_capsule)
+
+
+@pytest.mark.skipif(
+    not hasattr(pa.RecordBatchReader, "from_stream"),
+    reason="requires the Arrow PyCapsule interface",
+)
+def test_write_dataset_from_arrow_c_stream(df, tmp_path):
+    ds.write_dataset(
+        pa.RecordBatchReader.from_stream(df), tmp_path, format="parquet"
+    )
+
+    table = ds.dataset(tmp_path, format="parquet").to_table()
+    assert table.sort_by("a").to_pydict() == df.to_pydict()
+
+
 def test_to_pylist(df):
     # Convert datafusion dataframe to Python list
     pylist = df.to_pylist()
@@ -613,6 +2065,42 @@ def test_to_pydict(df):
     assert pydict == {"a": [1, 2, 3], "b": [4, 5, 6], "c": [8, 5, 8]}
 
 
+def test_to_pydict_nulls_and_nested(ctx):
+    batch = pa.RecordBatch.from_arrays(
+        [
+            pa.array([1, None]),
+            pa.array([{"x": 1, "y": "a"}, None]),
+            pa.array([[1, 2], None]),
+            pa.array([datetime.date(2021, 1, 1), None]),
+        ],
+        names=["a", "s", "l", "d"],
+    )
+    df = ctx.create_dataframe([[batch]])
+
+    assert df.to_pydict() == {
+        "a": [1, None],
+        "s": [{"x": 1, "y": "a"}, None],
+        "l": [[1, 2], None],
+        "d": [datetime.date(2021, 1, 1), None],
+    }
+    assert df.to_pylist() == [
+        {
+            "a": 1,
+            "s": {"x": 1, "y": "a"},
+            "l": [1, 2],
+            "d": datetime.date(2021, 1, 1),
+        },
+        {"a": None, "s": None, "l": None, "d": None},
+    ]
+
+
+def test_python_value_matches_to_pydict(ctx):
+    value = datetime.date(2021, 1, 1)
+    df = ctx.from_pydict({"d": [value]})
+
+    assert literal(value).python_value() == df.to_pydict()["d"][0]
+
+
 def test_describe(df):
     # Calculate statistics
     df = df.describe()
@@ -621,7 +2109,7 @@ def test_describe(df):
     result = df.to_pydict()
 
     assert result == {
-        "describe": [
+        "statistic": [
             "count",
             "null_count",
             "mean",
@@ -629,8 +2117,42 @@ def test_describe(df):
             "min",
             "max",
             "median",
+            "distinct",
+        ],
+        "a": [3.0, 0.0, 2.0, 1.0, 1.0, 3.0, 2.0, None],
+        "b": [3.0, 0.0, 5.0, 1.0, 4.0, 6.0, 5.0, None],
+        "c": [3.0, 0.0, 7.0, 1.7320508075688772, 5.0, 8.0, 8.0, None],
+    }
+
+
+def test_describe_mixed_types():
+    ctx = SessionContext()
+    batch = pa.RecordBatch.from_arrays(
+        [
+            pa.array([1.5, None, 3.5]),
+            pa.array(["x", "y", None]),
+            pa.array([[1], [2], []]),
         ],
-        "a": [3.0, 3.0, 2.0, 1.0, 1.0, 3.0, 2.0],
-        "b": [3.0, 3.0, 5.0, 1.0, 4.0, 6.0, 5.0],
-        "c": [3.0, 3.0, 7.0, 1.7320508075688772, 5.0, 8.0, 8.0],
+        names=["f", "s", "l"],
+    )
+    df = ctx.create_dataframe([[batch]])
+
+    result = df.describe().to_pydict()
+    assert result["statistic"][:2] == ["count", "null_count"]
+    # the median is approximate
+    f = result["f"][:6] + result["f"][7:]
+    assert f == [2.0, 1.0, 2.5, 1.4142135623730951, 1.5, 3.5, None]
+    assert result["s"] == ["2", "1", None, None, "x", "y", None, "2"]
+    # unsupported statistics are null instead of failing
+    assert result["l"] == ["3", "0", None, None, None, None, None, None]
+
+    result = df.describe(statistics=["max", "count"]).to_pydict()
+    assert result == {
+        "statistic": ["max", "count"],
+        "f": [3.5, 2.0],
+        "s": ["y", "2"],
+        "l": [None, "3"],
     }
+
+    with pytest.raises(ValueError, match="Unknown statistic 'mode'"):
+        df.describe(statistics=["mode"])
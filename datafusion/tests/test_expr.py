@@ -15,7 +15,13 @@
 # specific language governing permissions and limitations
 # under the License.
 
-from datafusion import SessionContext
+from datetime import datetime, timezone
+from decimal import Decimal
+
+import pyarrow as pa
+
+from datafusion import SessionContext, column, literal
+from datafusion.common import DataType
 from datafusion.expr import Column, Literal, BinaryExpr, AggregateFunction
 from datafusion.expr import (
     Projection,
@@ -23,6 +29,7 @@ from datafusion.expr import (
     Aggregate,
     Limit,
     Sort,
+    SortExpr,
     TableScan,
 )
 import pytest
@@ -108,3 +115,150 @@ def test_sort(test_ctx):
 
     plan = plan.to_variant()
     assert isinstance(plan, Sort)
+
+    sort_expr = plan.sort_exprs()[0].to_variant()
+    assert isinstance(sort_expr, SortExpr)
+    assert sort_expr.ascending()
+    assert not sort_expr.nulls_first()
+
+
+def test_sort_expr():
+    sort_expr = column("a").sort(ascending=False)
+    assert isinstance(sort_expr, SortExpr)
+    assert sort_expr.expr().canonical_name() == "a"
+    assert not sort_expr.ascending()
+    assert repr(sort_expr) == "a DESC NULLS FIRST"
+
+    # nulls sort last in ascending order and first in descending order
+    assert repr(column("a").sort()) == "a ASC NULLS LAST"
+    assert repr(column("a").asc()) == "a ASC NULLS LAST"
+    assert repr(column("a").desc()) == "a DESC NULLS FIRST"
+    assert repr(column("a").desc(nulls_first=False)) == "a DESC NULLS LAST"
+    assert repr(column("a").asc(nulls_first=True)) == "a ASC NULLS FIRST"
+
+
+def test_sort_expr_is_not_an_expr():
+    with pytest.raises(TypeError, match="is not an Expr"):
+        column("a").desc().alias("b")
+
+    with pytest.raises(AttributeError):
+        column("a").desc().no_such_method()
+
+    ctx = SessionContext()
+    df = ctx.from_pydict({"a": [1, 2]})
+    with pytest.raises(TypeError, match="SortExpr"):
+        df.select(column("a").desc())
+
+
+def test_nested_field_access():
+    ctx = SessionContext()
+    addr = pa.array(
+        [
+            {"city": "Oslo", "geo": {"lat": 59.9}},
+            {"city": "Rome", "geo": {"lat": 41.9}},
+        ]
+    )
+    batch = pa.RecordBatch.from_arrays(
+        [addr, pa.array([[1, 2], [3]])], names=["addr", "l"]
+    )
+    df = ctx.create_dataframe([[batch]])
+
+    result = df.select(
+        column("addr")["city"].alias("city"),
+        column("addr").field("geo")["lat"].alias("lat"),
+        column("l")[0].alias("first"),
+        column("l")[1].alias("second"),
+    )
+    assert result.to_pydict() == {
+        "city": ["Oslo", "Rome"],
+        "lat": [59.9, 41.9],
+        "first": [1, 3],
+        "second": [2, None],
+    }
+
+    with pytest.raises(Exception, match="missing"):
+        df.select(column("addr")["missing"])
+    with pytest.raises(ValueError, match="negative"):
+        column("l")[-1]
+    with pytest.raises(TypeError):
+        column("l")[1.5]
+
+
+def test_operators_plan_like_sql():
+    ctx = SessionContext()
+    batch = pa.RecordBatch.from_arrays(
+        [
+            pa.array([1, 2, 3]),
+            pa.array([0.5, 1.5, 2.5]),
+            pa.array(["x", "y", "z"]),
+        ],
+        names=["a", "b", "c"],
+    )
+    ctx.register_record_batches("t", [[batch]])
+    a, b, c = column("a"), column("b"), column("c")
+
+    predicate = ((a + 1 > 2 * b) & ~(c == "x")) | (10 % a != -a)
+    # select the columns as SELECT * does, for the plans to match
+    df = ctx.table("t").filter(predicate).select_columns("a", "b", "c")
+    sql = ctx.sql(
+        "SELECT * FROM t "
+        "WHERE (a + 1 > 2 * b AND NOT c = 'x') OR 10 % a <> -a"
+    )
+    assert (
+        df.optimized_logical_plan().display_indent()
+        == sql.optimized_logical_plan().display_indent()
+    )
+    assert df.to_pydict() == sql.to_pydict()
+
+
+def test_operators_lift_python_values():
+    a = column("a")
+    assert str(1 + a) == str(literal(1) + a)
+    assert str(1 - a) == str(literal(1) - a)
+    assert str(2 * a) == str(literal(2) * a)
+    assert str(1 / a) == str(literal(1) / a)
+    assert str(a <= 1.5) == str(a <= literal(1.5))
+    assert str(True & (a > 1)) == str(literal(True) & (a > 1))
+    assert str(False | (a > 1)) == str(literal(False) | (a > 1))
+
+
+def test_expr_hashable():
+    a = column("a")
+    assert hash(a + 1) == hash(column("a") + 1)
+    assert len({a + 1, column("a") + 1, a - 1}) == 2
+
+
+def test_cast_and_try_cast():
+    ctx = SessionContext()
+    df = ctx.from_pydict({"a": [1, 2], "s": ["2021-01-02T03:04:05Z", "x"]})
+
+    result = df.select(
+        column("a").cast("decimal(10, 2)").alias("decimal"),
+        column("a").cast(pa.float32()).alias("float"),
+        column("a").cast(DataType(pa.utf8())).alias("string"),
+        column("s").try_cast("timestamp[us, tz=UTC]").alias("timestamp"),
+    )
+    schema = result.schema()
+    assert schema.field("decimal").type == pa.decimal128(10, 2)
+    assert schema.field("float").type == pa.float32()
+    assert schema.field("timestamp").type == pa.timestamp("us", tz="UTC")
+    assert result.to_pydict() == {
+        "decimal": [Decimal("1.00"), Decimal("2.00")],
+        "float": [1.0, 2.0],
+        "string": ["1", "2"],
+        "timestamp": [
+            datetime(2021, 1, 2, 3, 4, 5, tzinfo=timezone.utc),
+            None,
+        ],
+    }
+
+    # a value that does not convert fails cast, but is null with try_cast
+    result = df.select(column("s").try_cast("int64").alias("s"))
+    assert result.to_pydict() == {"s": [None, None]}
+    with pytest.raises(Exception, match="Cannot cast"):
+        df.select(column("s").cast("int64")).collect()
+
+    with pytest.raises(ValueError, match="Unknown or non-primitive"):
+        column("a").cast("decimal(ten, 2)")
+    with pytest.raises(TypeError, match="Expected a DataType"):
+        column("a").cast(1)
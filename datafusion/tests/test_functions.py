@@ -18,7 +18,8 @@
 import numpy as np
 import pyarrow as pa
 import pytest
-from datetime import datetime
+from datetime import date, datetime, time, timedelta, timezone
+from decimal import Decimal
 
 from datafusion import SessionContext, column
 from datafusion import functions as f
@@ -67,6 +68,65 @@ def test_literal(df):
     assert result.column(5) == pa.array([b"hello world"] * 3)
 
 
+@pytest.mark.parametrize(
+    ("value", "arrow_type"),
+    [
+        (1, pa.int64()),
+        (2**63, pa.uint64()),
+        (2**64, pa.decimal128(20, 0)),
+        (1.5, pa.float64()),
+        (True, pa.bool_()),
+        ("a", pa.string()),
+        (b"a", pa.binary()),
+        (datetime(2020, 1, 2, 3), pa.timestamp("us")),
+        (date(2020, 1, 2), pa.date32()),
+        (time(3, 4), pa.time64("us")),
+        (timedelta(days=1, seconds=2), pa.month_day_nano_interval()),
+        (Decimal("1.250"), pa.decimal128(4, 3)),
+        (None, pa.null()),
+        ([1, 2], pa.list_(pa.int64())),
+    ],
+)
+def test_literal_types(df, value, arrow_type):
+    result = df.select(literal(value).alias("v"))
+    assert result.schema().field("v").type == arrow_type
+
+
+def test_literal_typed(df):
+    aware = datetime(2020, 1, 2, 3, tzinfo=timezone.utc)
+    result = df.select(
+        literal(aware).alias("aware"),
+        literal(None, type=pa.int32()).alias("null"),
+        literal(pa.scalar(1, pa.int8())).alias("scalar"),
+        literal(1, type=pa.float32()).alias("cast"),
+    )
+    schema = result.schema()
+    assert schema.field("aware").type.tz is not None
+    assert schema.field("null").type == pa.int32()
+    assert schema.field("scalar").type == pa.int8()
+    assert schema.field("cast").type == pa.float32()
+    assert result.collect()[0].column(0)[0].as_py() == aware
+
+
+def test_column_qualified():
+    ctx = SessionContext()
+    batch = pa.RecordBatch.from_arrays(
+        [pa.array([1, 2]), pa.array([3, 4])], names=["a", "b.c"]
+    )
+    ctx.register_record_batches("t", [[batch]])
+    df = ctx.table("t")
+
+    assert df.select(column("t.a")).to_pydict() == {"a": [1, 2]}
+    assert df.select(column('"b.c"')).to_pydict() == {"b.c": [3, 4]}
+    assert df.select(column('t."b.c"')).to_pydict() == {"b.c": [3, 4]}
+    assert df.select(f.col("t.a")).to_pydict() == {"a": [1, 2]}
+
+    with pytest.raises(ValueError, match="Unterminated quote"):
+        column('"b.c')
+    with pytest.raises(Exception):
+        df.select(column("u.a"))
+
+
 def test_lit_arith(df):
     """
     Test literals with arithmetic operations
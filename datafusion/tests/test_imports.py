@@ -37,6 +37,7 @@ from datafusion.expr import (
     Literal,
     BinaryExpr,
     AggregateFunction,
+    SortExpr,
     Projection,
     TableScan,
     Filter,
@@ -86,6 +87,7 @@ from datafusion.expr import (
     DropTable,
     Repartition,
     Partitioning,
+    WindowFrame,
 )
 
 
@@ -107,7 +109,14 @@ def test_class_module_is_datafusion():
         assert klass.__module__ == "datafusion"
 
     # expressions
-    for klass in [Expr, Column, Literal, BinaryExpr, AggregateFunction]:
+    for klass in [
+        Expr,
+        Column,
+        Literal,
+        BinaryExpr,
+        AggregateFunction,
+        SortExpr,
+    ]:
         assert klass.__module__ == "datafusion.expr"
 
     # operators
@@ -161,6 +170,7 @@ def test_class_module_is_datafusion():
         DropTable,
         Repartition,
         Partitioning,
+        WindowFrame,
     ]:
         assert klass.__module__ == "datafusion.expr"
 
@@ -15,12 +15,16 @@
 # specific language governing permissions and limitations
 # under the License.
 
+import datetime
+import pathlib
+
 import numpy as np
 import pyarrow as pa
+import pyarrow.compute as pc
 import pyarrow.dataset as ds
 import pytest
 
-from datafusion import udf
+from datafusion import SessionConfig, SessionContext, col, lit, udf
 
 from . import generic as helpers
 
@@ -30,6 +34,26 @@ def test_no_table(ctx):
         ctx.sql("SELECT a FROM b").collect()
 
 
+def test_sql_params(ctx):
+    ctx.from_pydict({"x": [1, 5, 10], "s": ["a", "b", "c"]}, name="t")
+
+    df = ctx.sql("SELECT x FROM t WHERE x > $1 ORDER BY x", params=[4])
+    assert df.to_pydict() == {"x": [5, 10]}
+
+    query = "SELECT s FROM t WHERE x > $1 AND s <> $2 ORDER BY s"
+    df = ctx.sql(query, params={"$1": 0, 2: "b"})
+    assert df.to_pydict() == {"s": ["a", "c"]}
+
+    with pytest.raises(TypeError, match=r"parameter \$1 of type Int64"):
+        ctx.sql("SELECT x FROM t WHERE x > $1", params=["many"])
+    with pytest.raises(ValueError, match="1 parameters but 2 values"):
+        ctx.sql("SELECT x FROM t WHERE x > $1", params=[1, 2])
+    with pytest.raises(ValueError, match="named placeholders"):
+        ctx.sql("SELECT x FROM t WHERE x > $1", params={"min": 1})
+    with pytest.raises(ValueError, match=r"parameter \$1 given twice"):
+        ctx.sql("SELECT x FROM t WHERE x > $1", params={1: 5, "$1": 6})
+
+
 def test_register_csv(ctx, tmp_path):
     path = tmp_path / "test.csv"
 
@@ -78,6 +102,31 @@ def test_register_csv(ctx, tmp_path):
         ctx.register_csv("csv4", path, delimiter="wrong")
 
 
+def test_register_csv_with_inferred_schema(ctx, tmp_path):
+    dir_root = tmp_path / "csv_dir"
+    dir_root.mkdir(exist_ok=False)
+
+    for i in range(3):
+        table = pa.Table.from_arrays(
+            [[1, 2], ["a", "b"]],
+            names=["int", "str"],
+        )
+        pa.csv.write_csv(table, dir_root / f"part-{i}.csv")
+
+    schema = ctx.register_csv_with_inferred_schema("csv", str(dir_root))
+    assert schema == pa.schema([("int", pa.int64()), ("str", pa.string())])
+
+    result = ctx.sql("SELECT COUNT(int) AS cnt FROM csv").collect()
+    result = pa.Table.from_batches(result)
+    assert result.to_pydict() == {"cnt": [6]}
+
+    table = pa.Table.from_arrays([[1, 2]], names=["other"])
+    pa.csv.write_csv(table, dir_root / "part-3.csv")
+
+    with pytest.raises(Exception, match="part-3.csv"):
+        ctx.register_csv_with_inferred_schema("csv_bad", str(dir_root))
+
+
 def test_register_parquet(ctx, tmp_path):
     path = helpers.write_parquet(tmp_path / "a.parquet", helpers.data())
     ctx.register_parquet("t", path)
@@ -88,6 +137,52 @@ def test_register_parquet(ctx, tmp_path):
     assert result.to_pydict() == {"cnt": [100]}
 
 
+def test_register_parquet_file_sort_order(tmp_path):
+    path = tmp_path / "a.parquet"
+    table = pa.Table.from_arrays([list(range(10))], names=["a"])
+    pa.parquet.write_table(table, path)
+
+    def operators(plan):
+        return [plan["name"]] + [
+            name for child in plan["children"] for name in operators(child)
+        ]
+
+    for file_sort_order, sorts in [([col("a").asc()], 0), ([], 1)]:
+        ctx = SessionContext(SessionConfig().with_target_partitions(1))
+        ctx.register_parquet("t", str(path), file_sort_order=file_sort_order)
+        df = ctx.sql("SELECT a FROM t ORDER BY a")
+        assert df.to_pydict() == {"a": list(range(10))}
+        plan = df.explain(format="dict")
+        assert operators(plan).count("SortExec") == sorts
+
+    with pytest.raises(NotImplementedError, match="file_sort_order"):
+        ctx.register_parquet(
+            "u",
+            str(path),
+            schema=table.schema,
+            lazy=True,
+            file_sort_order=["a"],
+        )
+
+
+def test_register_parquet_pruning(tmp_path):
+    path = tmp_path / "a.parquet"
+    table = pa.Table.from_arrays([list(range(100))], names=["a"])
+    pa.parquet.write_table(table, path, row_group_size=10)
+
+    def row_groups_pruned(plan):
+        pruned = plan["metrics"].get("row_groups_pruned", 0)
+        return pruned + sum(row_groups_pruned(c) for c in plan["children"])
+
+    for pruning, expected in [(True, 9), (False, 0)]:
+        ctx = SessionContext()
+        ctx.register_parquet("t", str(path), parquet_pruning=pruning)
+        df = ctx.sql("SELECT a FROM t WHERE a = 42")
+        assert df.to_pydict() == {"a": [42]}
+        plan = df.explain(analyze=True, format="dict")
+        assert row_groups_pruned(plan) == expected
+
+
 def test_register_parquet_partitioned(ctx, tmp_path):
     dir_root = tmp_path / "dataset_parquet_partitioned"
     dir_root.mkdir(exist_ok=False)
@@ -123,6 +218,211 @@ def test_register_parquet_partitioned(ctx, tmp_path):
     assert dict(zip(rd["grp"], rd["cnt"])) == {"a": 3, "b": 1}
 
 
+def test_register_parquet_typed_partition(ctx, tmp_path):
+    dir_root = tmp_path / "dataset_parquet_typed_partition"
+    for year in (2023, 2024):
+        (dir_root / f"year={year}").mkdir(parents=True)
+        table = pa.Table.from_arrays([[year - 2000, 1]], names=["int"])
+        pa.parquet.write_table(table, dir_root / f"year={year}/file.parquet")
+
+    ctx.register_parquet(
+        "datapp", str(dir_root), table_partition_cols=[("year", pa.int32())]
+    )
+    result = ctx.sql(
+        "SELECT year, SUM(int) AS total FROM datapp "
+        "WHERE year > 2023 GROUP BY year"
+    ).collect()
+    result = pa.Table.from_batches(result)
+
+    assert result.schema.field("year").type == pa.int32()
+    assert result.to_pydict() == {"year": [2024], "total": [25]}
+
+    df = ctx.read_parquet(
+        str(dir_root), table_partition_cols=[("year", pa.int32())]
+    )
+    assert df.count() == 4
+
+    (dir_root / "year=unknown").mkdir()
+    with pytest.raises(Exception, match="year=unknown"):
+        ctx.read_parquet(
+            str(dir_root), table_partition_cols=[("year", pa.int32())]
+        )
+
+
+def test_read_parquet_lazy(ctx, tmp_path):
+    dir_root = tmp_path / "lazy"
+    dir_root.mkdir()
+    schema = pa.schema([("a", pa.int64())])
+
+    # the directory is empty until after the frame and the table are created,
+    # so the files are only listed once they are scanned
+    df = ctx.read_parquet(str(dir_root), schema=schema, lazy=True)
+    ctx.register_parquet("t", str(dir_root), schema=schema, lazy=True)
+    assert df.schema() == schema
+
+    table = pa.Table.from_arrays([[1, 2, 3]], names=["a"])
+    pa.parquet.write_table(table, dir_root / "1.parquet")
+    assert df.to_pydict() == {"a": [1, 2, 3]}
+    assert ctx.sql("SELECT SUM(a) AS s FROM t").to_pydict() == {"s": [6]}
+
+    # the listing is reused by later scans, so a new file is not seen
+    pa.parquet.write_table(table, dir_root / "2.parquet")
+    assert df.count() == 3
+    assert ctx.sql("SELECT SUM(a) AS s FROM t").to_pydict() == {"s": [6]}
+    assert ctx.read_parquet(str(dir_root)).count() == 6
+
+    with pytest.raises(ValueError, match="needs a schema"):
+        ctx.read_parquet(str(dir_root), lazy=True)
+
+
+def test_read_parquet_lazy_partitioned(ctx, tmp_path):
+    dir_root = tmp_path / "lazy_partitioned"
+    for year in (2023, 2024):
+        (dir_root / f"year={year}").mkdir(parents=True)
+        table = pa.Table.from_arrays([[year - 2000, 1]], names=["int"])
+        pa.parquet.write_table(table, dir_root / f"year={year}/file.parquet")
+    pa.parquet.write_table(table, dir_root / "outside.parquet")
+
+    df = ctx.read_parquet(
+        str(dir_root),
+        table_partition_cols=[("year", pa.int32())],
+        schema=pa.schema([("int", pa.int64())]),
+        lazy=True,
+    )
+    df = df.filter(col("year") > lit(2023)).filter(col("int") > lit(1))
+    assert df.to_pydict() == {"int": [24], "year": [2024]}
+
+
+requires_delta = pytest.mark.skipif(
+    not hasattr(SessionContext, "register_delta"),
+    reason="built without the delta feature",
+)
+
+
+@pytest.fixture
+def delta_table(tmp_path):
+    """A Delta table with 2 rows at version 0, 3 at version 1 and 2 at version
+    2, committed 1, 2 and 3 seconds after the epoch"""
+    root = tmp_path / "delta_table"
+    root.mkdir()
+    files = {
+        "part-0.parquet": [1, 2],
+        "part-1.parquet": [3],
+        "part 2.parquet": [4],
+    }
+    for name, values in files.items():
+        table = pa.table({"a": pa.array(values, type=pa.int64())})
+        pa.parquet.write_table(table, root / name)
+
+    def add(path):
+        return {"add": {"path": path, "size": 0, "dataChange": True}}
+
+    def commit_info(version):
+        return {"commitInfo": {"timestamp": (version + 1) * 1000}}
+
+    helpers.write_delta_commit(
+        root,
+        0,
+        [
+            commit_info(0),
+            {"protocol": {"minReaderVersion": 1, "minWriterVersion": 2}},
+            helpers.delta_metadata({"a": "long"}),
+            add("part-0.parquet"),
+        ],
+    )
+    helpers.write_delta_commit(
+        root, 1, [commit_info(1), add("part-1.parquet")]
+    )
+    # paths in the log are percent-encoded
+    helpers.write_delta_commit(
+        root,
+        2,
+        [
+            commit_info(2),
+            {"remove": {"path": "part-0.parquet", "dataChange": True}},
+            add("part%202.parquet"),
+        ],
+    )
+    return str(root)
+
+
+@requires_delta
+def test_register_delta(ctx, delta_table):
+    ctx.register_delta("t", delta_table)
+    result = ctx.sql("SELECT a FROM t ORDER BY a").collect()
+    result = pa.Table.from_batches(result)
+    assert result.to_pydict() == {"a": [3, 4]}
+
+
+@requires_delta
+def test_register_delta_time_travel(ctx, delta_table):
+    ctx.register_delta("v0", delta_table, version=0)
+    ctx.register_delta("v1", delta_table, version=1)
+    assert ctx.table("v0").count() == 2
+    assert ctx.table("v1").count() == 3
+
+    ctx.register_delta("t", delta_table, as_of_timestamp=2.5)
+    assert ctx.table("t").count() == 3
+    as_of = datetime.datetime.fromtimestamp(1.5, tz=datetime.timezone.utc)
+    ctx.register_delta("u", delta_table, as_of_timestamp=as_of)
+    assert ctx.table("u").count() == 2
+
+    with pytest.raises(
+        Exception, match="no version 3, the latest version is 2"
+    ):
+        ctx.register_delta("missing", delta_table, version=3)
+    with pytest.raises(
+        Exception, match="at or before the requested timestamp"
+    ):
+        ctx.register_delta("early", delta_table, as_of_timestamp=0.5)
+    with pytest.raises(ValueError, match="Only one of"):
+        ctx.register_delta("both", delta_table, version=0, as_of_timestamp=1)
+
+
+
+@requires_delta
+@pytest.mark.parametrize(
+    "action,match",
+    [
+        (
+            {"protocol": {"minReaderVersion": 2, "minWriterVersion": 5}},
+            "requires reader version 2",
+        ),
+        (
+            {
+                "protocol": {
+                    "minReaderVersion": 1,
+                    "minWriterVersion": 7,
+                    "readerFeatures": ["timestampNtz"],
+                }
+            },
+            "reader features",
+        ),
+        (
+            {
+                "add": {
+                    "path": "part-1.parquet",
+                    "size": 0,
+                    "dataChange": True,
+                    "deletionVector": {"storageType": "u"},
+                }
+            },
+            "deletion vectors",
+        ),
+    ],
+)
+def test_register_delta_unsupported(ctx, delta_table, action, match):
+    helpers.write_delta_commit(pathlib.Path(delta_table), 3, [action])
+    with pytest.raises(Exception, match=match):
+        ctx.register_delta("t", delta_table)
+
+
+@requires_delta
+def test_register_delta_without_early_commits(ctx, delta_table):
+    (pathlib.Path(delta_table) / "_delta_log" / f"{0:020}.json").unlink()
+    with pytest.raises(Exception, match="log cleanup after a checkpoint"):
+        ctx.register_delta("t", delta_table)
+
 def test_register_dataset(ctx, tmp_path):
     path = helpers.write_parquet(tmp_path / "a.parquet", helpers.data())
     dataset = ds.dataset(path, format="parquet")
@@ -244,7 +544,11 @@ def test_udf(
     ctx.register_parquet("t", path)
 
     func = udf(
-        fn, input_types, output_type, name="func", volatility="immutable"
+        fn,
+        input_types,
+        "immutable",
+        return_type=output_type,
+        name="func",
     )
     ctx.register_udf(func)
 
@@ -254,6 +558,119 @@ def test_udf(
     assert result == pa.array(expected_values)
 
 
+def test_scalar_udf(ctx):
+    ctx.register_record_batches(
+        "t", [[pa.record_batch([pa.array(["a", "bcd", None, ""])], ["s"])]]
+    )
+
+    def str_len(value):
+        return None if value is None else len(value)
+
+    func = udf(
+        str_len,
+        [pa.utf8()],
+        "immutable",
+        return_type=pa.int32(),
+        name="str_len",
+        scalar=True,
+    )
+    ctx.register_udf(func)
+
+    batches = ctx.sql("SELECT str_len(s) AS n FROM t").collect()
+    assert batches[0].column(0) == pa.array([1, 3, None, 0], pa.int32())
+
+
+def test_udf_cache(ctx):
+    batch = pa.record_batch([pa.array([1, 2, 1, None, 2, 1])], ["x"])
+    ctx.register_record_batches("t", [[batch]])
+    calls = []
+
+    def expensive(value):
+        calls.append(value)
+        return None if value is None else value * 10
+
+    func = udf(
+        expensive,
+        [pa.int64()],
+        "immutable",
+        return_type=pa.int64(),
+        name="expensive",
+        scalar=True,
+        cache=True,
+    )
+    ctx.register_udf(func)
+
+    query = "SELECT expensive(x) AS y FROM t"
+    expected = [10, 20, 10, None, 20, 10]
+    assert ctx.sql(query).to_pydict() == {"y": expected}
+    # each distinct input once, though the query sees 6 rows
+    assert sorted(calls, key=str) == [1, 2, None]
+
+    # every query starts with an empty cache
+    assert ctx.sql(query).to_pydict() == {"y": expected}
+    assert len(calls) == 6
+
+    # batch functions are only passed the rows they have not seen, and the
+    # cache is emptied when it would exceed cache_size
+    lengths = []
+
+    def batch_expensive(values):
+        lengths.append(len(values))
+        return pc.multiply(values, 10)
+
+    func = udf(
+        batch_expensive,
+        [pa.int64()],
+        "immutable",
+        return_type=pa.int64(),
+        name="batch_expensive",
+        cache=True,
+        cache_size=2,
+    )
+    ctx.register_udf(func)
+    ctx.register_record_batches("t2", [[batch, batch]])
+    query = "SELECT batch_expensive(x) AS y FROM t2"
+    assert ctx.sql(query).to_pydict() == {"y": expected * 2}
+    assert lengths == [3, 1]
+
+    # since nothing is kept between queries, any volatility can be cached
+    func = udf(
+        expensive,
+        [pa.int64()],
+        "volatile",
+        return_type=pa.int64(),
+        name="volatile_expensive",
+        scalar=True,
+        cache=True,
+    )
+    ctx.register_udf(func)
+    del calls[:]
+    query = "SELECT volatile_expensive(x) AS y FROM t"
+    assert ctx.sql(query).to_pydict() == {"y": expected}
+    assert sorted(calls, key=str) == [1, 2, None]
+
+
+def test_udf_inferred_return_type(ctx):
+    ctx.register_record_batches(
+        "t", [[pa.record_batch([pa.array(["a", "bcd", None])], ["s"])]]
+    )
+
+    func = udf(pc.utf8_length, [pa.utf8()], "immutable", name="utf8_len")
+    ctx.register_udf(func)
+    batches = ctx.sql("SELECT utf8_len(s) AS n FROM t").collect()
+    assert batches[0].column(0) == pa.array([1, 3, None], pa.int32())
+
+    # the sample call sees one row, later calls return another type
+    def unstable(values):
+        ty = pa.int32() if len(values) == 1 else pa.int64()
+        return pa.array([0] * len(values), ty)
+
+    func = udf(unstable, [pa.utf8()], "immutable", name="unstable")
+    ctx.register_udf(func)
+    with pytest.raises(Exception, match="returned Int64 but its return type"):
+        ctx.sql("SELECT unstable(s) FROM t").collect()
+
+
 _null_mask = np.array([False, True, False])
 
 
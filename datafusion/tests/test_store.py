@@ -19,7 +19,11 @@ import os
 import pytest
 
 from datafusion import SessionContext
-from datafusion.object_store import LocalFileSystem
+from datafusion.object_store import (
+    GoogleCloudStorage,
+    LocalFileSystem,
+    MicrosoftAzure,
+)
 
 
 @pytest.fixture
@@ -44,3 +48,64 @@ def test_read_parquet(ctx):
     )
     df = ctx.sql("SELECT * FROM test")
     assert isinstance(df.collect(), list)
+
+
+def test_azure_credentials_validated():
+    with pytest.raises(ValueError, match="only one of"):
+        MicrosoftAzure(
+            "container", account="account", access_key="a2V5", sas_token="sv=1"
+        )
+    with pytest.raises(ValueError, match="all set or all None"):
+        MicrosoftAzure("container", account="account", client_id="id")
+
+
+def test_gcs_credentials_validated():
+    with pytest.raises(ValueError, match="not both"):
+        GoogleCloudStorage(
+            "bucket", service_account_path="key.json", service_account_key="{}"
+        )
+
+
+# These read a small object from a real bucket, so they need credentials and
+# the path of the object, e.g. DATAFUSION_TEST_GCS_OBJECT=gs://bucket/x.parquet
+@pytest.mark.skipif(
+    "DATAFUSION_TEST_GCS_OBJECT" not in os.environ
+    or "GOOGLE_SERVICE_ACCOUNT" not in os.environ,
+    reason="needs Google Cloud Storage credentials and an object",
+)
+def test_read_gcs():
+    path = os.environ["DATAFUSION_TEST_GCS_OBJECT"]
+    bucket = path.split("/")[2]
+    ctx = SessionContext()
+    ctx.register_object_store(
+        "gs://",
+        GoogleCloudStorage(
+            bucket, service_account_path=os.environ["GOOGLE_SERVICE_ACCOUNT"]
+        ),
+        None,
+    )
+    assert isinstance(ctx.read_parquet(path).collect(), list)
+
+
+@pytest.mark.skipif(
+    "DATAFUSION_TEST_AZURE_OBJECT" not in os.environ
+    or "AZURE_STORAGE_ACCOUNT_NAME" not in os.environ,
+    reason="needs Azure Blob Storage credentials and an object",
+)
+def test_read_azure():
+    # az://container/data.parquet, with the account key or a SAS token in the
+    # AZURE_STORAGE_ACCOUNT_KEY or AZURE_STORAGE_SAS_TOKEN variable
+    path = os.environ["DATAFUSION_TEST_AZURE_OBJECT"]
+    container = path.split("/")[2]
+    ctx = SessionContext()
+    ctx.register_object_store(
+        "az://",
+        MicrosoftAzure(
+            container,
+            account=os.environ["AZURE_STORAGE_ACCOUNT_NAME"],
+            access_key=os.environ.get("AZURE_STORAGE_ACCOUNT_KEY"),
+            sas_token=os.environ.get("AZURE_STORAGE_SAS_TOKEN"),
+        ),
+        None,
+    )
+    assert isinstance(ctx.read_parquet(path).collect(), list)
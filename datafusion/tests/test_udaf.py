@@ -21,7 +21,13 @@ import pyarrow as pa
 import pyarrow.compute as pc
 import pytest
 
-from datafusion import Accumulator, SessionContext, column, udaf
+from datafusion import (
+    Accumulator,
+    AggregateUDF,
+    SessionContext,
+    column,
+    udaf,
+)
 
 
 class Summarize(Accumulator):
@@ -118,6 +124,23 @@ def test_aggregate(df):
     assert result.column(0) == pa.array([1.0 + 2.0 + 3.0])
 
 
+
+@pytest.mark.parametrize("input_type", [pa.float64(), [pa.float64()]])
+def test_aggregate_udf_input_type(df, input_type):
+    # the constructor takes one type or a list of them under its old keyword
+    summarize = AggregateUDF(
+        name="summarize",
+        accumulator=Summarize,
+        input_type=input_type,
+        return_type=pa.float64(),
+        state_type=[pa.float64()],
+        volatility="immutable",
+    )
+
+    result = df.aggregate([], [summarize(column("a"))]).collect()[0]
+
+    assert result.column(0) == pa.array([1.0 + 2.0 + 3.0])
+
 def test_group_by(df):
     summarize = udaf(
         Summarize,
@@ -134,3 +157,57 @@ def test_group_by(df):
     arrays = [batch.column(1) for batch in batches]
     joined = pa.concat_arrays(arrays)
     assert joined == pa.array([1.0 + 2.0, 3.0])
+
+
+class WeightedMean(Accumulator):
+    def __init__(self):
+        self._total = 0.0
+        self._weight = 0.0
+
+    def state(self) -> List[pa.Scalar]:
+        return [pa.scalar(self._total), pa.scalar(self._weight)]
+
+    def update(self, values: pa.Array, weights: pa.Array) -> None:
+        self._total += pc.sum(pc.multiply(values, weights)).as_py()
+        self._weight += pc.sum(weights).as_py()
+
+    def merge(self, totals: pa.Array, weights: pa.Array) -> None:
+        self._total += pc.sum(totals).as_py()
+        self._weight += pc.sum(weights).as_py()
+
+    def evaluate(self) -> pa.Scalar:
+        return pa.scalar(self._total / self._weight)
+
+
+def test_multiple_arguments():
+    ctx = SessionContext()
+    # two partitions, so that the states of both are merged
+    batches = [
+        pa.RecordBatch.from_pydict(
+            {
+                "key": ["x", "y", "x"],
+                "v": [1.0, 2.0, 3.0],
+                "w": [1.0, 1.0, 3.0],
+            }
+        ),
+        pa.RecordBatch.from_pydict(
+            {"key": ["x", "y"], "v": [6.0, 4.0], "w": [4.0, 3.0]}
+        ),
+    ]
+    df = ctx.create_dataframe([[batches[0]], [batches[1]]])
+    weighted_mean = udaf(
+        WeightedMean,
+        [pa.float64(), pa.float64()],
+        pa.float64(),
+        [pa.float64(), pa.float64()],
+        volatility="immutable",
+    )
+
+    df = df.aggregate(
+        [column("key")], [weighted_mean(column("v"), column("w")).alias("m")]
+    ).sort(column("key").sort())
+
+    assert df.to_pydict() == {
+        "key": ["x", "y"],
+        "m": [(1 + 9 + 24) / 8, (2 + 12) / 4],
+    }
@@ -23,7 +23,9 @@ def is_null(array: pyarrow.Array) -> pyarrow.Array:
     return array.is_null()
 
 
-is_null_arr = udf(is_null, [pyarrow.int64()], pyarrow.bool_(), "stable")
+is_null_arr = udf(
+    is_null, [pyarrow.int64()], "stable", return_type=pyarrow.bool_()
+)
 
 # create a context
 ctx = SessionContext()
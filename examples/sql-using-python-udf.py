@@ -27,8 +27,8 @@ def is_null(array: pa.Array) -> pa.Array:
 is_null_arr = udf(
     is_null,
     [pa.int64()],
-    pa.bool_(),
     "stable",
+    return_type=pa.bool_(),
     # This will be the name of the UDF in SQL
     # If not specified it will by default the same as Python function name
     name="is_null",
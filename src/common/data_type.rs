@@ -15,11 +15,26 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use datafusion::arrow::datatypes::{DataType, IntervalUnit, TimeUnit};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use datafusion::arrow::compute::{can_cast_types, cast_with_options, CastOptions};
+use datafusion::arrow::datatypes::{
+    DataType, Field, IntervalUnit, Schema, TimeUnit, UnionMode, DECIMAL128_MAX_PRECISION,
+    DECIMAL256_MAX_PRECISION,
+};
+use datafusion::arrow::ffi::FFI_ArrowSchema;
+use datafusion::arrow::pyarrow::{PyArrowConvert, PyArrowType};
 use datafusion_common::{DataFusionError, ScalarValue};
-use pyo3::prelude::*;
+use datafusion_expr::type_coercion::binary::comparison_coercion;
+use pyo3::types::{IntoPyDict, PyBool, PyCapsule, PyDict, PyLong};
+use pyo3::{basic::CompareOp, exceptions::PyValueError, prelude::*};
 
-use crate::errors::py_datafusion_err;
+use crate::errors::{py_datafusion_err, py_type_err};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[pyclass(name = "RexType", module = "datafusion.common")]
@@ -32,6 +47,11 @@ pub enum RexType {
     Other,
 }
 
+/// Metadata key holding the `n` of a `CHAR(n)` type
+const CHAR_LENGTH_METADATA_KEY: &str = "length";
+/// Metadata key holding the `n` of a `VARCHAR(n)` type
+const VARCHAR_MAX_LENGTH_METADATA_KEY: &str = "max_length";
+
 /// These bindings are tying together several disparate systems.
 /// You have SQL types for the SQL strings and RDBMS systems itself.
 /// Rust types for the DataFusion code
@@ -50,6 +70,12 @@ pub struct DataTypeMap {
     pub python_type: PythonType,
     #[pyo3(get, set)]
     pub sql_type: SqlType,
+    /// Whether values of this type may be null, as on an Arrow field
+    #[pyo3(get, set)]
+    pub nullable: bool,
+    /// Key/value metadata carried over from an Arrow field
+    #[pyo3(get, set)]
+    pub metadata: HashMap<String, String>,
 }
 
 impl DataTypeMap {
@@ -60,6 +86,8 @@ impl DataTypeMap {
             },
             python_type,
             sql_type,
+            nullable: true,
+            metadata: HashMap::new(),
         }
     }
 
@@ -133,7 +161,11 @@ impl DataTypeMap {
             DataType::Timestamp(unit, tz) => Ok(DataTypeMap::new(
                 DataType::Timestamp(unit.clone(), tz.clone()),
                 PythonType::Datetime,
-                SqlType::DATE,
+                if tz.is_some() {
+                    SqlType::TIMESTAMP_WITH_LOCAL_TIME_ZONE
+                } else {
+                    SqlType::TIMESTAMP
+                },
             )),
             DataType::Date32 => Ok(DataTypeMap::new(
                 DataType::Date32,
@@ -203,9 +235,11 @@ impl DataTypeMap {
             DataType::Struct(_) => Err(py_datafusion_err(DataFusionError::NotImplemented(
                 format!("{:?}", arrow_type),
             ))),
-            DataType::Union(_, _) => Err(py_datafusion_err(DataFusionError::NotImplemented(
-                format!("{:?}", arrow_type),
-            ))),
+            DataType::Union(fields, mode) => Ok(DataTypeMap::new(
+                DataType::Union(fields.clone(), *mode),
+                PythonType::Object,
+                SqlType::OTHER,
+            )),
             DataType::Dictionary(_, _) => Err(py_datafusion_err(DataFusionError::NotImplemented(
                 format!("{:?}", arrow_type),
             ))),
@@ -222,12 +256,42 @@ impl DataTypeMap {
             DataType::Map(_, _) => Err(py_datafusion_err(DataFusionError::NotImplemented(
                 format!("{:?}", arrow_type),
             ))),
-            DataType::RunEndEncoded(_, _) => Err(py_datafusion_err(
-                DataFusionError::NotImplemented(format!("{:?}", arrow_type)),
-            )),
+            DataType::RunEndEncoded(_, values) => {
+                // Run-end encoding only changes the physical layout, so the Python and SQL
+                // types are those of the values
+                let values_map = DataTypeMap::map_from_arrow_type(values.data_type())?;
+                Ok(DataTypeMap::new(
+                    arrow_type.clone(),
+                    values_map.python_type,
+                    values_map.sql_type,
+                ))
+            }
         }
     }
 
+    /// Maps the type of an Arrow field, keeping its nullability and metadata
+    pub fn map_from_arrow_field(field: &Field) -> PyResult<DataTypeMap> {
+        let mut map = DataTypeMap::map_from_arrow_type(field.data_type())?;
+        map.nullable = field.is_nullable();
+        map.metadata = field.metadata().clone();
+        Ok(map)
+    }
+
+    /// Maps each field of `schema` to a `(name, DataTypeMap)` pair, in order. Fields
+    /// whose types have no mapping get an error entry instead.
+    pub fn schema_to_maps(schema: &Schema) -> Vec<(String, PyResult<DataTypeMap>)> {
+        schema
+            .fields()
+            .iter()
+            .map(|field| {
+                (
+                    field.name().clone(),
+                    DataTypeMap::map_from_arrow_field(field),
+                )
+            })
+            .collect()
+    }
+
     /// Generate the `DataTypeMap` from a `ScalarValue` instance
     pub fn map_from_scalar_value(scalar_val: &ScalarValue) -> Result<DataTypeMap, PyErr> {
         DataTypeMap::map_from_arrow_type(&DataTypeMap::map_from_scalar_to_arrow(scalar_val)?)
@@ -294,12 +358,470 @@ impl DataTypeMap {
 
 #[pymethods]
 impl DataTypeMap {
+    /// Creates a `DataTypeMap` from its parts. When built with the `validate-type-maps`
+    /// feature the parts are checked for consistency with `validate`.
     #[new]
-    pub fn py_new(arrow_type: PyDataType, python_type: PythonType, sql_type: SqlType) -> Self {
-        DataTypeMap {
+    #[pyo3(signature = (arrow_type, python_type, sql_type, nullable=true, metadata=None))]
+    pub fn py_new(
+        arrow_type: PyDataType,
+        python_type: PythonType,
+        sql_type: SqlType,
+        nullable: bool,
+        metadata: Option<HashMap<String, String>>,
+    ) -> PyResult<Self> {
+        let map = DataTypeMap {
             arrow_type,
             python_type,
             sql_type,
+            nullable,
+            metadata: metadata.unwrap_or_default(),
+        };
+        #[cfg(feature = "validate-type-maps")]
+        map.validate()?;
+        Ok(map)
+    }
+
+    /// Checks that the Arrow, Python and SQL types agree with each other, either
+    /// according to the mapping from the Arrow type or the mapping from the SQL type.
+    pub fn validate(&self) -> PyResult<()> {
+        let from_arrow = DataTypeMap::map_from_arrow_type(&self.arrow_type.data_type)?;
+        if from_arrow.python_type == self.python_type && from_arrow.sql_type == self.sql_type {
+            return Ok(());
+        }
+        if let Ok(from_sql) = DataTypeMap::py_map_from_sql_type(&self.sql_type, false, None, None) {
+            if from_sql.arrow_type == self.arrow_type && from_sql.python_type == self.python_type {
+                return Ok(());
+            }
+        }
+
+        let mut conflicts = vec![];
+        if from_arrow.python_type != self.python_type {
+            conflicts.push(format!(
+                "python_type {:?} (expected {:?})",
+                self.python_type, from_arrow.python_type
+            ));
+        }
+        if from_arrow.sql_type != self.sql_type {
+            conflicts.push(format!(
+                "sql_type {:?} (expected {:?})",
+                self.sql_type, from_arrow.sql_type
+            ));
+        }
+        Err(PyValueError::new_err(format!(
+            "Inconsistent DataTypeMap for arrow_type {:?}: {}",
+            self.arrow_type.data_type,
+            conflicts.join(", ")
+        )))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DataTypeMap(arrow_type={}, python_type={:?}, sql_type={})",
+            self.arrow_type.data_type, self.python_type, self.sql_type
+        )
+    }
+
+    /// Supports the `arrow`, `sql` and `python` format specifiers, which format one
+    /// of the mapped types, e.g. `f"{type_map:sql}"`. An empty specifier formats
+    /// the whole map as `repr` does.
+    fn __format__(&self, format_spec: &str) -> PyResult<String> {
+        match format_spec {
+            "" => Ok(self.__repr__()),
+            "arrow" => Ok(self.arrow_type.data_type.to_string()),
+            "sql" => Ok(self.sql_type.to_string()),
+            "python" => Ok(format!("{:?}", self.python_type)),
+            other => Err(PyValueError::new_err(format!(
+                "Invalid format specifier '{other}' for DataTypeMap, expected 'arrow', \
+                 'sql' or 'python'"
+            ))),
+        }
+    }
+
+    /// The map of the narrowest type that both this map's and `other`'s values can be
+    /// cast to without loss, such as `Int64` for `Int32` and `Int64`, or `LargeUtf8`
+    /// for `Utf8` and `LargeUtf8`. The result is nullable if either map is. Raises a
+    /// `TypeError` if there is no such type, e.g. for a number and a string.
+    pub fn upcast(&self, other: DataTypeMapArg) -> PyResult<DataTypeMap> {
+        let other = other.0;
+        let (left, right) = (&self.arrow_type.data_type, &other.arrow_type.data_type);
+        let is_string = |t: &DataType| matches!(t, DataType::Utf8 | DataType::LargeUtf8);
+        let is_binary = |t: &DataType| matches!(t, DataType::Binary | DataType::LargeBinary);
+        let common = match (left, right) {
+            _ if left == right => Some(left.clone()),
+            (DataType::Null, t) | (t, DataType::Null) => Some(t.clone()),
+            _ if (left.is_numeric() && right.is_numeric())
+                || (is_string(left) && is_string(right)) =>
+            {
+                comparison_coercion(left, right)
+            }
+            _ if is_binary(left) && is_binary(right) => Some(DataType::LargeBinary),
+            _ => None,
+        };
+        let common = common.ok_or_else(|| {
+            py_type_err(format!("Cannot upcast {left} and {right} to a common type"))
+        })?;
+        let mut map = DataTypeMap::map_from_arrow_type(&common)?;
+        map.nullable = self.nullable || other.nullable;
+        Ok(map)
+    }
+
+    /// Whether values of this type can be cast to `target`'s type by Arrow's cast
+    /// kernel. A cast may still fail for some values, e.g. a string that is not a
+    /// number when casting `Utf8` to `Int32`.
+    pub fn can_cast_to(&self, target: DataTypeMapArg) -> bool {
+        can_cast_types(&self.arrow_type.data_type, &target.0.arrow_type.data_type)
+    }
+
+    /// `a | b` is `a.upcast(b)`, like the `int | float` type union syntax
+    fn __or__(&self, other: &PyAny, py: Python) -> PyResult<PyObject> {
+        match other.extract::<PyRef<DataTypeMap>>() {
+            Ok(other) => Ok(self.upcast(DataTypeMapArg(other.clone()))?.into_py(py)),
+            Err(_) => Ok(py.NotImplemented()),
+        }
+    }
+
+    fn __ror__(&self, other: &PyAny, py: Python) -> PyResult<PyObject> {
+        match other.extract::<PyRef<DataTypeMap>>() {
+            Ok(other) => Ok(other.upcast(DataTypeMapArg(self.clone()))?.into_py(py)),
+            Err(_) => Ok(py.NotImplemented()),
+        }
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Validates the map when leaving a `with` block, so it may be inconsistent
+    /// while its fields are being changed inside the block. An exception raised
+    /// inside the block is propagated without validating.
+    fn __exit__(
+        &self,
+        exc_type: Option<&PyAny>,
+        _exc_value: Option<&PyAny>,
+        _traceback: Option<&PyAny>,
+    ) -> PyResult<bool> {
+        if exc_type.is_none() {
+            self.validate()?;
+        }
+        Ok(false)
+    }
+
+    /// Returns the zero/empty value for this type, useful as a default for
+    /// nullable columns. Integers map to `0`, floats to `0.0`, strings to `""`
+    /// and `Null` to a `NULL` scalar.
+    pub fn default_value(&self) -> PyResult<PyScalarValue> {
+        match &self.arrow_type.data_type {
+            DataType::Null => Ok(ScalarValue::Null.into()),
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64 => Ok(ScalarValue::Int64(Some(0)).into()),
+            DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+                Ok(ScalarValue::Float64(Some(0.0)).into())
+            }
+            DataType::Utf8 | DataType::LargeUtf8 => {
+                Ok(ScalarValue::Utf8(Some("".to_string())).into())
+            }
+            other => Err(py_datafusion_err(DataFusionError::NotImplemented(format!(
+                "No default value for {:?}",
+                other
+            )))),
+        }
+    }
+
+    /// The rank of the SQL type in type promotion, where a type is promoted to one of
+    /// higher rank: exact numbers rank below approximate ones, as in ANSI SQL, so
+    /// `TINYINT < SMALLINT < INTEGER < BIGINT < DECIMAL < FLOAT/REAL < DOUBLE`, and the
+    /// character, binary and datetime types each rank above the previous group. The
+    /// result of `upcast` never ranks below either of its inputs.
+    pub fn sql_type_precedence(&self) -> i32 {
+        match self.sql_type {
+            SqlType::NULL => 0,
+            SqlType::BOOLEAN => 1,
+            SqlType::TINYINT => 10,
+            SqlType::SMALLINT => 11,
+            SqlType::INTEGER => 12,
+            SqlType::BIGINT => 13,
+            SqlType::DECIMAL => 14,
+            SqlType::FLOAT | SqlType::REAL => 15,
+            SqlType::DOUBLE => 16,
+            SqlType::CHAR => 20,
+            SqlType::VARCHAR => 21,
+            SqlType::BINARY => 30,
+            SqlType::VARBINARY => 31,
+            SqlType::DATE => 40,
+            SqlType::TIME | SqlType::TIME_WITH_LOCAL_TIME_ZONE => 41,
+            SqlType::TIMESTAMP | SqlType::TIMESTAMP_WITH_LOCAL_TIME_ZONE => 42,
+            SqlType::INTERVAL
+            | SqlType::INTERVAL_DAY
+            | SqlType::INTERVAL_DAY_HOUR
+            | SqlType::INTERVAL_DAY_MINUTE
+            | SqlType::INTERVAL_DAY_SECOND
+            | SqlType::INTERVAL_HOUR
+            | SqlType::INTERVAL_HOUR_MINUTE
+            | SqlType::INTERVAL_HOUR_SECOND
+            | SqlType::INTERVAL_MINUTE
+            | SqlType::INTERVAL_MINUTE_SECOND
+            | SqlType::INTERVAL_MONTH
+            | SqlType::INTERVAL_SECOND
+            | SqlType::INTERVAL_YEAR
+            | SqlType::INTERVAL_YEAR_MONTH => 50,
+            SqlType::ANY
+            | SqlType::ARRAY
+            | SqlType::COLUMN_LIST
+            | SqlType::CURSOR
+            | SqlType::DISTINCT
+            | SqlType::DYNAMIC_STAR
+            | SqlType::GEOMETRY
+            | SqlType::MAP
+            | SqlType::MULTISET
+            | SqlType::OTHER
+            | SqlType::ROW
+            | SqlType::SARG
+            | SqlType::STRUCTURED
+            | SqlType::SYMBOL
+            | SqlType::UNKNOWN => 100,
+        }
+    }
+
+    /// The Arrow type in the notation Arrow itself prints types in, as `str()` of a
+    /// `pyarrow.DataType` does, e.g. `int64`, `list<item: string>` or
+    /// `timestamp[ms, tz=UTC]`. Note that Arrow spells `Utf8` as `string`.
+    pub fn arrow_schema_string(&self) -> PyResult<String> {
+        arrow_type_string(&self.arrow_type.data_type)
+    }
+
+    /// The `type.name` of the Arrow type in Arrow's JSON schema notation, which Feather
+    /// V2 and IPC tools use, e.g. `int` for every integer type and `floatingpoint` for
+    /// every float type. A dictionary has the name of its value type, since the JSON
+    /// notation records dictionary encoding on the field rather than the type.
+    pub fn to_feather_dtype_string(&self) -> &'static str {
+        feather_type_name(&self.arrow_type.data_type)
+    }
+
+    /// The Spark SQL DDL name of the type, e.g. `bigint` or `array<string>`, as used in
+    /// the schema strings of `StructType.fromDDL`. Unsigned integers take the next
+    /// wider signed type, `UInt64` a `decimal(20,0)`, and timestamps without a time
+    /// zone are `timestamp_ntz`. Types Spark has no equivalent of, such as times and
+    /// unions, raise an error.
+    pub fn to_spark_sql_type(&self) -> PyResult<String> {
+        spark_sql_type(&self.arrow_type.data_type)
+    }
+
+    /// The BigQuery DDL name of the type, e.g. `INT64` or `ARRAY<STRING>`. Decimals are
+    /// `NUMERIC` if they fit its 29 integer and 9 fractional digits, and `BIGNUMERIC`
+    /// if they fit its 38 and 38, and `UInt64` is a `NUMERIC`. Timestamps without a
+    /// time zone are `DATETIME`. Types BigQuery has no equivalent of, such as maps,
+    /// unions and lists of lists, raise an error.
+    pub fn to_bigquery_type(&self) -> PyResult<String> {
+        bigquery_type(&self.arrow_type.data_type)
+    }
+
+    /// Whether values of this type are stored exactly: integers and decimals. Booleans
+    /// are not numeric, as in SQL.
+    pub fn is_exact_numeric(&self) -> bool {
+        matches!(
+            self.arrow_type.data_type,
+            DataType::Int8
+                | DataType::Int16
+                | DataType::Int32
+                | DataType::Int64
+                | DataType::UInt8
+                | DataType::UInt16
+                | DataType::UInt32
+                | DataType::UInt64
+                | DataType::Decimal128(_, _)
+                | DataType::Decimal256(_, _)
+        )
+    }
+
+    /// Whether values of this type are approximations: floating point numbers.
+    /// Booleans are not numeric, as in SQL.
+    pub fn is_approximate_numeric(&self) -> bool {
+        matches!(
+            self.arrow_type.data_type,
+            DataType::Float16 | DataType::Float32 | DataType::Float64
+        )
+    }
+
+    /// The broad family of the type: `"numeric"` for the exact and approximate numeric
+    /// types, or `"string"`, `"temporal"`, `"binary"`, `"boolean"`, `"collection"`
+    /// for lists, structs, maps and unions, and `"other"` for anything else.
+    pub fn sql_type_category(&self) -> &'static str {
+        if self.is_exact_numeric() || self.is_approximate_numeric() {
+            return "numeric";
+        }
+        match &self.arrow_type.data_type {
+            DataType::Boolean => "boolean",
+            DataType::Utf8 | DataType::LargeUtf8 => "string",
+            DataType::Date32
+            | DataType::Date64
+            | DataType::Time32(_)
+            | DataType::Time64(_)
+            | DataType::Timestamp(_, _)
+            | DataType::Duration(_)
+            | DataType::Interval(_) => "temporal",
+            DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => "binary",
+            DataType::List(_)
+            | DataType::LargeList(_)
+            | DataType::FixedSizeList(_, _)
+            | DataType::Struct(_)
+            | DataType::Map(_, _)
+            | DataType::Union(_, _) => "collection",
+            _ => "other",
+        }
+    }
+
+    /// The map as a dict: the three types formatted as by `format`, `nullable`, the
+    /// `is_numeric`, `is_temporal` and `is_nested` flags of `sql_type_category`, the
+    /// `byte_width` of `approx_memory_per_row` and the `metadata`, plus the parameters
+    /// of the Arrow type: `precision` and `scale` for decimals, `unit` and `timezone`
+    /// for timestamps, and `list_size` for fixed size lists.
+    pub fn describe(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let category = self.sql_type_category();
+        let dict = PyDict::new(py);
+        dict.set_item("arrow_type", self.arrow_type.data_type.to_string())?;
+        dict.set_item("python_type", format!("{:?}", self.python_type))?;
+        dict.set_item("sql_type", self.sql_type.to_string())?;
+        dict.set_item("nullable", self.nullable)?;
+        dict.set_item("is_numeric", category == "numeric")?;
+        dict.set_item("is_temporal", category == "temporal")?;
+        dict.set_item("is_nested", category == "collection")?;
+        dict.set_item("byte_width", self.approx_memory_per_row())?;
+        dict.set_item("metadata", self.metadata.clone())?;
+        match &self.arrow_type.data_type {
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                dict.set_item("precision", precision)?;
+                dict.set_item("scale", scale)?;
+            }
+            DataType::Timestamp(unit, timezone) => {
+                dict.set_item("unit", time_unit_string(unit))?;
+                dict.set_item("timezone", timezone.as_deref())?;
+            }
+            DataType::FixedSizeList(_, size) => dict.set_item("list_size", size)?,
+            _ => {}
+        }
+        Ok(dict.into())
+    }
+
+    /// The number of bytes a value of this type takes, for fixed-width types, or
+    /// `None` for variable-width types such as strings and lists. Booleans are
+    /// counted as one byte, although Arrow packs them into bits.
+    pub fn approx_memory_per_row(&self) -> Option<usize> {
+        match &self.arrow_type.data_type {
+            DataType::Boolean | DataType::Int8 | DataType::UInt8 => Some(1),
+            DataType::Int16 | DataType::UInt16 | DataType::Float16 => Some(2),
+            DataType::Int32
+            | DataType::UInt32
+            | DataType::Float32
+            | DataType::Date32
+            | DataType::Time32(_)
+            | DataType::Interval(IntervalUnit::YearMonth) => Some(4),
+            DataType::Int64
+            | DataType::UInt64
+            | DataType::Float64
+            | DataType::Date64
+            | DataType::Time64(_)
+            | DataType::Timestamp(_, _)
+            | DataType::Duration(_)
+            | DataType::Interval(IntervalUnit::DayTime) => Some(8),
+            DataType::Decimal128(_, _) | DataType::Interval(IntervalUnit::MonthDayNano) => Some(16),
+            DataType::Decimal256(_, _) => Some(32),
+            DataType::FixedSizeBinary(size) => usize::try_from(*size).ok(),
+            _ => None,
+        }
+    }
+
+    /// The largest value of a numeric type, the largest finite one for floats, or
+    /// `None` for other types. `Float16` and `Decimal256` are numeric but also give
+    /// `None`, since this version of DataFusion has no scalar values of those types.
+    pub fn max_value(&self) -> Option<PyScalarValue> {
+        let value = match &self.arrow_type.data_type {
+            DataType::Int8 => ScalarValue::Int8(Some(i8::MAX)),
+            DataType::Int16 => ScalarValue::Int16(Some(i16::MAX)),
+            DataType::Int32 => ScalarValue::Int32(Some(i32::MAX)),
+            DataType::Int64 => ScalarValue::Int64(Some(i64::MAX)),
+            DataType::UInt8 => ScalarValue::UInt8(Some(u8::MAX)),
+            DataType::UInt16 => ScalarValue::UInt16(Some(u16::MAX)),
+            DataType::UInt32 => ScalarValue::UInt32(Some(u32::MAX)),
+            DataType::UInt64 => ScalarValue::UInt64(Some(u64::MAX)),
+            DataType::Float32 => ScalarValue::Float32(Some(f32::MAX)),
+            DataType::Float64 => ScalarValue::Float64(Some(f64::MAX)),
+            DataType::Decimal128(precision, scale) => {
+                ScalarValue::Decimal128(Some(max_decimal128(*precision)), *precision, *scale)
+            }
+            _ => return None,
+        };
+        Some(value.into())
+    }
+
+    /// The smallest value of a numeric type, the smallest finite one for floats, or
+    /// `None` for other types, `Float16` and `Decimal256` included as for `max_value`
+    pub fn min_value(&self) -> Option<PyScalarValue> {
+        let value = match &self.arrow_type.data_type {
+            DataType::Int8 => ScalarValue::Int8(Some(i8::MIN)),
+            DataType::Int16 => ScalarValue::Int16(Some(i16::MIN)),
+            DataType::Int32 => ScalarValue::Int32(Some(i32::MIN)),
+            DataType::Int64 => ScalarValue::Int64(Some(i64::MIN)),
+            DataType::UInt8 => ScalarValue::UInt8(Some(u8::MIN)),
+            DataType::UInt16 => ScalarValue::UInt16(Some(u16::MIN)),
+            DataType::UInt32 => ScalarValue::UInt32(Some(u32::MIN)),
+            DataType::UInt64 => ScalarValue::UInt64(Some(u64::MIN)),
+            DataType::Float32 => ScalarValue::Float32(Some(f32::MIN)),
+            DataType::Float64 => ScalarValue::Float64(Some(f64::MIN)),
+            DataType::Decimal128(precision, scale) => {
+                ScalarValue::Decimal128(Some(-max_decimal128(*precision)), *precision, *scale)
+            }
+            _ => return None,
+        };
+        Some(value.into())
+    }
+
+    /// Returns a `NULL` scalar typed as this `DataTypeMap`'s Arrow type, so that
+    /// programmatically built expressions do not rely on type inference for nulls.
+    pub fn null_value(&self) -> PyResult<PyScalarValue> {
+        ScalarValue::try_from(&self.arrow_type.data_type)
+            .map(PyScalarValue::from)
+            .map_err(py_datafusion_err)
+    }
+
+    /// For a union type, returns the `(type_id, name, DataTypeMap)` of each child type
+    pub fn union_children(&self) -> PyResult<Vec<(i8, String, DataTypeMap)>> {
+        match &self.arrow_type.data_type {
+            DataType::Union(fields, _) => fields
+                .iter()
+                .map(|(type_id, field)| {
+                    Ok((
+                        type_id,
+                        field.name().clone(),
+                        DataTypeMap::map_from_arrow_type(field.data_type())?,
+                    ))
+                })
+                .collect(),
+            other => Err(py_type_err(format!("{:?} is not a union type", other))),
+        }
+    }
+
+    /// For a union type, returns `true` if the union is sparse and `false` if it is dense
+    pub fn is_sparse_union(&self) -> PyResult<bool> {
+        match &self.arrow_type.data_type {
+            DataType::Union(_, mode) => Ok(*mode == UnionMode::Sparse),
+            other => Err(py_type_err(format!("{:?} is not a union type", other))),
+        }
+    }
+
+    /// For a run-end encoded type, returns the type of the run ends index
+    pub fn run_ends_type(&self) -> PyResult<PyDataType> {
+        match &self.arrow_type.data_type {
+            DataType::RunEndEncoded(run_ends, _) => Ok(run_ends.data_type().clone().into()),
+            other => Err(py_type_err(format!(
+                "{:?} is not a run-end encoded type",
+                other
+            ))),
         }
     }
 
@@ -309,9 +831,144 @@ impl DataTypeMap {
         DataTypeMap::map_from_arrow_type(&arrow_type.data_type)
     }
 
+    /// Creates a `DataTypeMap` from a `pyarrow.Field`, keeping its nullability and
+    /// metadata. The field is read through the Arrow C Data Interface.
+    #[staticmethod]
+    pub fn from_arrow_field(field: &PyAny) -> PyResult<DataTypeMap> {
+        DataTypeMap::map_from_arrow_field(&Field::from_pyarrow(field)?)
+    }
+
+    /// Creates a `DataTypeMap` from a `pyarrow.DataType`, which is read through its
+    /// `__arrow_c_schema__` PyCapsule
+    #[staticmethod]
+    pub fn from_pyarrow_dtype(dtype: &PyAny) -> PyResult<DataTypeMap> {
+        if !dtype.hasattr("__arrow_c_schema__")? {
+            return Err(py_type_err(format!(
+                "{} does not implement the Arrow PyCapsule interface, which needs \
+                 pyarrow 14 or later; use DataTypeMap.arrow(DataType(dtype)) instead",
+                dtype.get_type().name()?
+            )));
+        }
+        DataTypeMap::map_from_arrow_type(&data_type_from_c_schema(dtype)?)
+    }
+
+    /// Maps every field of a `pyarrow.Schema` to a `(name, DataTypeMap)` pair, in
+    /// order. Raises the error of the first field whose type has no mapping.
+    #[staticmethod]
+    pub fn from_schema(
+        schema: PyArrowType<Schema>,
+        py: Python,
+    ) -> PyResult<Vec<(String, DataTypeMap)>> {
+        DataTypeMap::schema_to_maps(&schema.0)
+            .into_iter()
+            .map(|(name, map)| {
+                let map = map.map_err(|e| {
+                    PyErr::from_type(
+                        e.get_type(py),
+                        format!("Cannot map field {name}: {}", e.value(py)),
+                    )
+                })?;
+                Ok((name, map))
+            })
+            .collect()
+    }
+
+    /// Creates a `pyarrow.Field` with the given name from this map's Arrow type,
+    /// nullability and metadata
+    pub fn to_arrow_field(&self, name: &str) -> PyArrowType<Field> {
+        Field::new(name, self.arrow_type.data_type.clone(), self.nullable)
+            .with_metadata(self.metadata.clone())
+            .into()
+    }
+
+    /// Returns every `SqlType` variant, in order
+    #[staticmethod]
+    pub fn all_sql_types() -> Vec<SqlType> {
+        vec![
+            SqlType::ANY,
+            SqlType::ARRAY,
+            SqlType::BIGINT,
+            SqlType::BINARY,
+            SqlType::BOOLEAN,
+            SqlType::CHAR,
+            SqlType::COLUMN_LIST,
+            SqlType::CURSOR,
+            SqlType::DATE,
+            SqlType::DECIMAL,
+            SqlType::DISTINCT,
+            SqlType::DOUBLE,
+            SqlType::DYNAMIC_STAR,
+            SqlType::FLOAT,
+            SqlType::GEOMETRY,
+            SqlType::INTEGER,
+            SqlType::INTERVAL,
+            SqlType::INTERVAL_DAY,
+            SqlType::INTERVAL_DAY_HOUR,
+            SqlType::INTERVAL_DAY_MINUTE,
+            SqlType::INTERVAL_DAY_SECOND,
+            SqlType::INTERVAL_HOUR,
+            SqlType::INTERVAL_HOUR_MINUTE,
+            SqlType::INTERVAL_HOUR_SECOND,
+            SqlType::INTERVAL_MINUTE,
+            SqlType::INTERVAL_MINUTE_SECOND,
+            SqlType::INTERVAL_MONTH,
+            SqlType::INTERVAL_SECOND,
+            SqlType::INTERVAL_YEAR,
+            SqlType::INTERVAL_YEAR_MONTH,
+            SqlType::MAP,
+            SqlType::MULTISET,
+            SqlType::NULL,
+            SqlType::OTHER,
+            SqlType::REAL,
+            SqlType::ROW,
+            SqlType::SARG,
+            SqlType::SMALLINT,
+            SqlType::STRUCTURED,
+            SqlType::SYMBOL,
+            SqlType::TIME,
+            SqlType::TIME_WITH_LOCAL_TIME_ZONE,
+            SqlType::TIMESTAMP,
+            SqlType::TIMESTAMP_WITH_LOCAL_TIME_ZONE,
+            SqlType::TINYINT,
+            SqlType::UNKNOWN,
+            SqlType::VARBINARY,
+            SqlType::VARCHAR,
+        ]
+    }
+
+    /// Returns every `PythonType` variant, in order
     #[staticmethod]
-    #[pyo3(name = "sql")]
-    pub fn py_map_from_sql_type(sql_type: &SqlType) -> PyResult<DataTypeMap> {
+    pub fn all_python_types() -> Vec<PythonType> {
+        PythonType::all_types()
+    }
+
+    /// Maps a `SqlType` to its Arrow and Python types. `large` picks the 64-bit offset
+    /// variants of the string and binary types. `DECIMAL` takes a `precision` and
+    /// `scale`, as `sql_decimal` does; the other types take neither.
+    #[staticmethod]
+    #[pyo3(name = "sql", signature = (sql_type, large=false, precision=None, scale=None))]
+    pub fn py_map_from_sql_type(
+        sql_type: &SqlType,
+        large: bool,
+        precision: Option<u8>,
+        scale: Option<i8>,
+    ) -> PyResult<DataTypeMap> {
+        match (sql_type, precision, scale) {
+            (SqlType::DECIMAL, Some(precision), scale) => {
+                return DataTypeMap::sql_decimal(precision, scale.unwrap_or(0))
+            }
+            (SqlType::DECIMAL, None, Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "A DECIMAL scale requires a precision",
+                ))
+            }
+            (_, None, None) => {}
+            (_, _, _) => {
+                return Err(PyValueError::new_err(format!(
+                    "Only DECIMAL takes a precision and scale, not {sql_type}"
+                )))
+            }
+        }
         match sql_type {
             SqlType::ANY => Err(py_datafusion_err(DataFusionError::NotImplemented(format!(
                 "{:?}",
@@ -327,7 +984,11 @@ impl DataTypeMap {
                 SqlType::BIGINT,
             )),
             SqlType::BINARY => Ok(DataTypeMap::new(
-                DataType::Binary,
+                if large {
+                    DataType::LargeBinary
+                } else {
+                    DataType::Binary
+                },
                 PythonType::Bytes,
                 SqlType::BINARY,
             )),
@@ -337,8 +998,8 @@ impl DataTypeMap {
                 SqlType::BOOLEAN,
             )),
             SqlType::CHAR => Ok(DataTypeMap::new(
-                DataType::UInt8,
-                PythonType::Int,
+                DataType::Utf8,
+                PythonType::Str,
                 SqlType::CHAR,
             )),
             SqlType::COLUMN_LIST => Err(py_datafusion_err(DataFusionError::NotImplemented(
@@ -475,10 +1136,7 @@ impl DataTypeMap {
             SqlType::TIME_WITH_LOCAL_TIME_ZONE => Err(py_datafusion_err(
                 DataFusionError::NotImplemented(format!("{:?}", sql_type)),
             )),
-            SqlType::TIMESTAMP => Err(py_datafusion_err(DataFusionError::NotImplemented(format!(
-                "{:?}",
-                sql_type
-            )))),
+            SqlType::TIMESTAMP => DataTypeMap::sql_timestamp(6, None),
             SqlType::TIMESTAMP_WITH_LOCAL_TIME_ZONE => Err(py_datafusion_err(
                 DataFusionError::NotImplemented(format!("{:?}", sql_type)),
             )),
@@ -497,12 +1155,97 @@ impl DataTypeMap {
                 SqlType::VARBINARY,
             )),
             SqlType::VARCHAR => Ok(DataTypeMap::new(
-                DataType::Utf8,
+                if large {
+                    DataType::LargeUtf8
+                } else {
+                    DataType::Utf8
+                },
                 PythonType::Str,
                 SqlType::VARCHAR,
             )),
         }
     }
+
+    /// Maps `CHAR(length)` to `Utf8`, as Arrow has no fixed-length string type. The
+    /// length is kept in the metadata under `length`.
+    #[staticmethod]
+    pub fn sql_char(length: u32) -> DataTypeMap {
+        let mut map = DataTypeMap::new(DataType::Utf8, PythonType::Str, SqlType::CHAR);
+        map.metadata
+            .insert(CHAR_LENGTH_METADATA_KEY.to_string(), length.to_string());
+        map
+    }
+
+    /// Maps `VARCHAR(max_length)` to `Utf8`, keeping the maximum length, if any, in
+    /// the metadata under `max_length`
+    #[staticmethod]
+    #[pyo3(signature = (max_length=None))]
+    pub fn sql_varchar(max_length: Option<u32>) -> DataTypeMap {
+        let mut map = DataTypeMap::new(DataType::Utf8, PythonType::Str, SqlType::VARCHAR);
+        if let Some(max_length) = max_length {
+            map.metadata.insert(
+                VARCHAR_MAX_LENGTH_METADATA_KEY.to_string(),
+                max_length.to_string(),
+            );
+        }
+        map
+    }
+
+    /// Maps `TIMESTAMP(precision)`, with `precision` the number of fractional digits
+    /// of the seconds, to the Arrow timestamp with the smallest sufficient unit. A
+    /// timestamp with a `tz` is a `TIMESTAMP WITH LOCAL TIME ZONE`.
+    #[staticmethod]
+    #[pyo3(signature = (precision=6, tz=None))]
+    pub fn sql_timestamp(precision: u8, tz: Option<String>) -> PyResult<DataTypeMap> {
+        let unit = match precision {
+            0 => TimeUnit::Second,
+            1..=3 => TimeUnit::Millisecond,
+            4..=6 => TimeUnit::Microsecond,
+            7..=9 => TimeUnit::Nanosecond,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Timestamp precision must be between 0 and 9, got {precision}"
+                )))
+            }
+        };
+        let sql_type = if tz.is_some() {
+            SqlType::TIMESTAMP_WITH_LOCAL_TIME_ZONE
+        } else {
+            SqlType::TIMESTAMP
+        };
+        Ok(DataTypeMap::new(
+            DataType::Timestamp(unit, tz.map(Into::into)),
+            PythonType::Datetime,
+            sql_type,
+        ))
+    }
+
+    /// Maps `DECIMAL(precision, scale)` to `Decimal128`, or to `Decimal256` when the
+    /// precision is above 38 digits. Raises a `ValueError` for a precision outside
+    /// 1-76 or a scale larger than the precision.
+    #[staticmethod]
+    pub fn sql_decimal(precision: u8, scale: i8) -> PyResult<DataTypeMap> {
+        if precision == 0 || precision > DECIMAL256_MAX_PRECISION {
+            return Err(PyValueError::new_err(format!(
+                "Decimal precision must be between 1 and {DECIMAL256_MAX_PRECISION}, got {precision}"
+            )));
+        }
+        if scale > 0 && scale as u8 > precision {
+            return Err(PyValueError::new_err(format!(
+                "Decimal scale {scale} must not be larger than the precision {precision}"
+            )));
+        }
+        let arrow_type = if precision <= DECIMAL128_MAX_PRECISION {
+            DataType::Decimal128(precision, scale)
+        } else {
+            DataType::Decimal256(precision, scale)
+        };
+        Ok(DataTypeMap::new(
+            arrow_type,
+            PythonType::Float,
+            SqlType::DECIMAL,
+        ))
+    }
 }
 
 /// PyO3 requires that objects passed between Rust and Python implement the trait `PyClass`
@@ -514,18 +1257,693 @@ pub struct PyDataType {
     pub data_type: DataType,
 }
 
+#[pymethods]
+impl PyDataType {
+    #[new]
+    fn py_new(data_type: PyArrowType<DataType>) -> Self {
+        data_type.0.into()
+    }
+
+    fn __richcmp__(&self, other: PyRef<PyDataType>, op: CompareOp) -> bool {
+        op.matches(self.cmp(&other))
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// A `DataType` holds no shared references, so a deep copy is a plain clone and
+    /// the `memo` dict is not needed
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        self.clone()
+    }
+
+    /// Export this type through the Arrow PyCapsule schema interface, so that any
+    /// Arrow-native consumer, such as `pyarrow`, can import it
+    fn __arrow_c_schema__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyCapsule> {
+        let ffi_schema = FFI_ArrowSchema::try_from(&self.data_type).map_err(py_datafusion_err)?;
+        let name = CString::new("arrow_schema").unwrap();
+        PyCapsule::new(py, ArrowSchemaCapsule(ffi_schema), Some(name))
+    }
+
+    /// Creates a `DataType` from an `arrow_schema` PyCapsule, or from an object that
+    /// exports one through `__arrow_c_schema__`, such as a `pyarrow.DataType`
+    #[staticmethod]
+    fn from_arrow_c_schema(schema: &PyAny) -> PyResult<PyDataType> {
+        Ok(data_type_from_c_schema(schema)?.into())
+    }
+
+    /// Creates a `DataType` from the name of a primitive Arrow type, e.g. `"int64"`, or
+    /// of a decimal or temporal type with its parameters, e.g. `"decimal(10, 2)"`
+    #[staticmethod]
+    #[pyo3(name = "from_str")]
+    fn py_from_str(s: &str) -> PyResult<PyDataType> {
+        PyDataType::from_str(s).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// The number of nested `List`, `LargeList` or `FixedSizeList` levels, e.g. 2 for
+    /// `List<List<Int32>>` and 0 for a type that is not a list
+    pub fn list_depth(&self) -> usize {
+        let mut depth = 0;
+        let mut data_type = &self.data_type;
+        while let DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::FixedSizeList(field, _) = data_type
+        {
+            depth += 1;
+            data_type = field.data_type();
+        }
+        depth
+    }
+
+    /// The number of nested `Struct` levels, counting the deepest of the fields, e.g. 2
+    /// for `Struct<a: Int32, b: Struct<c: Int32>>` and 0 for a type that is not a struct
+    pub fn struct_depth(&self) -> usize {
+        struct_depth(&self.data_type)
+    }
+}
+
+impl FromStr for PyDataType {
+    type Err = DataFusionError;
+
+    /// Parses the name of a primitive Arrow type, ignoring case, either as Arrow
+    /// displays it, e.g. `Int64` or `Utf8`, or as `pyarrow` names it, e.g. `int64`
+    /// or `string`. Decimals, timestamps, times and durations take their parameters
+    /// as `pyarrow` prints them, e.g. `decimal128(10, 2)`, `timestamp[ms, tz=UTC]` or
+    /// `duration[s]`, and `decimal(p, s)` is a `Decimal128`, or a `Decimal256` if the
+    /// precision needs it.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let unknown = || DataFusionError::Plan(format!("Unknown or non-primitive Arrow type: {s}"));
+        let (name, params) = match s.find(['(', '[']) {
+            Some(start) => {
+                let close = if s[start..].starts_with('(') {
+                    ')'
+                } else {
+                    ']'
+                };
+                let params = s[start + 1..].strip_suffix(close).ok_or_else(unknown)?;
+                let params: Vec<&str> = params.split(',').map(str::trim).collect();
+                (s[..start].trim().to_lowercase(), Some(params))
+            }
+            None => (s.to_lowercase(), None),
+        };
+        let time_unit = |unit: &str| match unit.to_lowercase().as_str() {
+            "s" => Ok(TimeUnit::Second),
+            "ms" => Ok(TimeUnit::Millisecond),
+            "us" => Ok(TimeUnit::Microsecond),
+            "ns" => Ok(TimeUnit::Nanosecond),
+            _ => Err(unknown()),
+        };
+        let data_type = match (name.as_str(), params.as_deref()) {
+            ("decimal" | "decimal128" | "decimal256", Some([precision]))
+            | ("decimal" | "decimal128" | "decimal256", Some([precision, _])) => {
+                let precision: u8 = precision.parse().map_err(|_| unknown())?;
+                let scale: i8 = match params.as_deref() {
+                    Some([_, scale]) => scale.parse().map_err(|_| unknown())?,
+                    _ => 0,
+                };
+                if precision == 0 || precision > DECIMAL256_MAX_PRECISION {
+                    return Err(unknown());
+                }
+                match name.as_str() {
+                    "decimal128" if precision > DECIMAL128_MAX_PRECISION => return Err(unknown()),
+                    "decimal" if precision > DECIMAL128_MAX_PRECISION => {
+                        DataType::Decimal256(precision, scale)
+                    }
+                    "decimal" | "decimal128" => DataType::Decimal128(precision, scale),
+                    _ => DataType::Decimal256(precision, scale),
+                }
+            }
+            ("timestamp", Some([unit])) => DataType::Timestamp(time_unit(unit)?, None),
+            ("timestamp", Some([unit, tz])) => {
+                let tz = tz.strip_prefix("tz=").ok_or_else(unknown)?;
+                DataType::Timestamp(time_unit(unit)?, Some(tz.to_string().into()))
+            }
+            ("time32", Some([unit])) => match time_unit(unit)? {
+                unit @ (TimeUnit::Second | TimeUnit::Millisecond) => DataType::Time32(unit),
+                _ => return Err(unknown()),
+            },
+            ("time64", Some([unit])) => match time_unit(unit)? {
+                unit @ (TimeUnit::Microsecond | TimeUnit::Nanosecond) => DataType::Time64(unit),
+                _ => return Err(unknown()),
+            },
+            ("duration", Some([unit])) => DataType::Duration(time_unit(unit)?),
+            ("date32", Some(["day"])) => DataType::Date32,
+            ("date64", Some(["ms"])) => DataType::Date64,
+            (_, Some(_)) => return Err(unknown()),
+            ("null", None) => DataType::Null,
+            ("bool" | "boolean", None) => DataType::Boolean,
+            ("int8", None) => DataType::Int8,
+            ("int16", None) => DataType::Int16,
+            ("int32", None) => DataType::Int32,
+            ("int64", None) => DataType::Int64,
+            ("uint8", None) => DataType::UInt8,
+            ("uint16", None) => DataType::UInt16,
+            ("uint32", None) => DataType::UInt32,
+            ("uint64", None) => DataType::UInt64,
+            ("float16" | "halffloat", None) => DataType::Float16,
+            ("float32" | "float", None) => DataType::Float32,
+            ("float64" | "double", None) => DataType::Float64,
+            ("utf8" | "string" | "str", None) => DataType::Utf8,
+            ("large_utf8" | "largeutf8" | "large_string", None) => DataType::LargeUtf8,
+            ("binary", None) => DataType::Binary,
+            ("large_binary" | "largebinary", None) => DataType::LargeBinary,
+            ("date32", None) => DataType::Date32,
+            ("date64", None) => DataType::Date64,
+            _ => return Err(unknown()),
+        };
+        Ok(data_type.into())
+    }
+}
+
+/// A `DataType` argument that Python may pass as a `DataType`, a `pyarrow.DataType` or
+/// a type name that `DataType.from_str` parses, such as `"decimal(10, 2)"`
+#[derive(Debug, Clone)]
+pub struct DataTypeArg(pub DataType);
+
+impl<'source> FromPyObject<'source> for DataTypeArg {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        if let Ok(data_type) = value.extract::<PyDataType>() {
+            return Ok(DataTypeArg(data_type.data_type));
+        }
+        if let Ok(name) = value.extract::<&str>() {
+            let data_type =
+                PyDataType::from_str(name).map_err(|e| PyValueError::new_err(e.to_string()))?;
+            return Ok(DataTypeArg(data_type.data_type));
+        }
+        if let Ok(data_type) = value.extract::<PyArrowType<DataType>>() {
+            return Ok(DataTypeArg(data_type.0));
+        }
+        Err(py_type_err(format!(
+            "Expected a DataType, pyarrow.DataType or type name, got {}",
+            value.get_type().name()?
+        )))
+    }
+}
+
+/// A `DataTypeMap` argument that Python may also pass as a `DataType`, as the name of
+/// an Arrow type such as `"int64"` or as a `SqlType`, tried in that order. `DataTypeMap`
+/// itself cannot implement `FromPyObject`, pyo3 already extracts it as a pyclass.
+#[derive(Debug, Clone)]
+pub struct DataTypeMapArg(pub DataTypeMap);
+
+impl<'source> FromPyObject<'source> for DataTypeMapArg {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        if let Ok(map) = value.extract::<DataTypeMap>() {
+            return Ok(DataTypeMapArg(map));
+        }
+        if let Ok(data_type) = value.extract::<PyDataType>() {
+            return Ok(DataTypeMapArg(DataTypeMap::map_from_arrow_type(
+                &data_type.data_type,
+            )?));
+        }
+        if let Ok(name) = value.extract::<&str>() {
+            let data_type =
+                PyDataType::from_str(name).map_err(|e| PyValueError::new_err(e.to_string()))?;
+            return Ok(DataTypeMapArg(DataTypeMap::map_from_arrow_type(
+                &data_type.data_type,
+            )?));
+        }
+        if let Ok(sql_type) = value.extract::<SqlType>() {
+            return Ok(DataTypeMapArg(DataTypeMap::py_map_from_sql_type(
+                &sql_type, false, None, None,
+            )?));
+        }
+        Err(py_type_err(format!(
+            "Expected a DataTypeMap, DataType, type name or SqlType, got {}",
+            value.get_type().name()?
+        )))
+    }
+}
+
+impl From<DataTypeMapArg> for DataTypeMap {
+    fn from(arg: DataTypeMapArg) -> DataTypeMap {
+        arg.0
+    }
+}
+
+/// The largest unscaled value of a decimal with `precision` digits, `10^precision - 1`
+fn max_decimal128(precision: u8) -> i128 {
+    10_i128.pow(u32::from(precision.min(DECIMAL128_MAX_PRECISION))) - 1
+}
+
+/// Casts `value` to `data_type`, failing rather than giving a null when the value
+/// does not fit, as `ScalarValue` has no cast of its own
+pub(crate) fn cast_scalar(
+    value: &ScalarValue,
+    data_type: &DataType,
+) -> Result<ScalarValue, DataFusionError> {
+    let options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    let array = cast_with_options(&value.to_array(), data_type, &options)?;
+    ScalarValue::try_from_array(&array, 0)
+}
+
+/// Reads the Arrow type described by an `arrow_schema` PyCapsule, or by the capsule
+/// returned from the object's `__arrow_c_schema__` method. The capsule keeps ownership
+/// of the schema, which is only read.
+pub(crate) fn data_type_from_c_schema(schema: &PyAny) -> PyResult<DataType> {
+    let capsule: &PyCapsule = match schema.downcast::<PyCapsule>() {
+        Ok(capsule) => capsule,
+        Err(_) if schema.hasattr("__arrow_c_schema__")? => {
+            schema.call_method0("__arrow_c_schema__")?.downcast()?
+        }
+        Err(_) => {
+            return Err(py_type_err(format!(
+                "Expected an arrow_schema PyCapsule or an object implementing \
+                 __arrow_c_schema__, got {}",
+                schema.get_type().name()?
+            )))
+        }
+    };
+    DataType::try_from(ffi_schema_from_capsule(capsule)?).map_err(py_datafusion_err)
+}
+
+/// The schema held by a PyCapsule, which must be named `arrow_schema` as any other
+/// capsule, such as an `arrow_array_stream`, holds something else
+pub(crate) fn ffi_schema_from_capsule(capsule: &PyCapsule) -> PyResult<&FFI_ArrowSchema> {
+    match capsule.name()? {
+        Some(name) if name.to_bytes() == b"arrow_schema" => {
+            Ok(unsafe { capsule.reference::<FFI_ArrowSchema>() })
+        }
+        name => Err(py_type_err(format!(
+            "Expected a PyCapsule named arrow_schema, got {name:?}"
+        ))),
+    }
+}
+
+/// `FFI_ArrowSchema` holds raw pointers and is therefore not `Send`, which `PyCapsule`
+/// requires. The schema is only ever handed to a single consumer through the capsule.
+#[repr(transparent)]
+struct ArrowSchemaCapsule(FFI_ArrowSchema);
+
+unsafe impl Send for ArrowSchemaCapsule {}
+
 impl From<PyDataType> for DataType {
     fn from(data_type: PyDataType) -> DataType {
         data_type.data_type
     }
 }
 
+/// Formats `data_type` as Arrow's `DataType::ToString` does, see
+/// `DataTypeMap::arrow_schema_string`
+fn arrow_type_string(data_type: &DataType) -> PyResult<String> {
+    let type_string = match data_type {
+        DataType::Null => "null".to_string(),
+        DataType::Boolean => "bool".to_string(),
+        DataType::Int8 => "int8".to_string(),
+        DataType::Int16 => "int16".to_string(),
+        DataType::Int32 => "int32".to_string(),
+        DataType::Int64 => "int64".to_string(),
+        DataType::UInt8 => "uint8".to_string(),
+        DataType::UInt16 => "uint16".to_string(),
+        DataType::UInt32 => "uint32".to_string(),
+        DataType::UInt64 => "uint64".to_string(),
+        DataType::Float16 => "halffloat".to_string(),
+        DataType::Float32 => "float".to_string(),
+        DataType::Float64 => "double".to_string(),
+        DataType::Timestamp(unit, None) => format!("timestamp[{}]", time_unit_string(unit)),
+        DataType::Timestamp(unit, Some(tz)) => {
+            format!("timestamp[{}, tz={tz}]", time_unit_string(unit))
+        }
+        DataType::Date32 => "date32[day]".to_string(),
+        DataType::Date64 => "date64[ms]".to_string(),
+        DataType::Time32(unit) => format!("time32[{}]", time_unit_string(unit)),
+        DataType::Time64(unit) => format!("time64[{}]", time_unit_string(unit)),
+        DataType::Duration(unit) => format!("duration[{}]", time_unit_string(unit)),
+        DataType::Interval(IntervalUnit::YearMonth) => "month_interval".to_string(),
+        DataType::Interval(IntervalUnit::DayTime) => "day_time_interval".to_string(),
+        DataType::Interval(IntervalUnit::MonthDayNano) => "month_day_nano_interval".to_string(),
+        DataType::Binary => "binary".to_string(),
+        DataType::FixedSizeBinary(size) => format!("fixed_size_binary[{size}]"),
+        DataType::LargeBinary => "large_binary".to_string(),
+        DataType::Utf8 => "string".to_string(),
+        DataType::LargeUtf8 => "large_string".to_string(),
+        DataType::List(field) => format!("list<{}>", arrow_field_string(field)?),
+        DataType::FixedSizeList(field, size) => {
+            format!("fixed_size_list<{}>[{size}]", arrow_field_string(field)?)
+        }
+        DataType::LargeList(field) => format!("large_list<{}>", arrow_field_string(field)?),
+        DataType::Struct(fields) => format!(
+            "struct<{}>",
+            fields
+                .iter()
+                .map(|field| arrow_field_string(field))
+                .collect::<PyResult<Vec<_>>>()?
+                .join(", ")
+        ),
+        DataType::Union(fields, mode) => format!(
+            "{}_union<{}>",
+            match mode {
+                UnionMode::Sparse => "sparse",
+                UnionMode::Dense => "dense",
+            },
+            fields
+                .iter()
+                .map(|(type_id, field)| Ok(format!("{}={type_id}", arrow_field_string(field)?)))
+                .collect::<PyResult<Vec<_>>>()?
+                .join(", ")
+        ),
+        DataType::Dictionary(key_type, value_type) => format!(
+            "dictionary<values={}, indices={}, ordered=0>",
+            arrow_type_string(value_type)?,
+            arrow_type_string(key_type)?
+        ),
+        DataType::Decimal128(precision, scale) => format!("decimal128({precision}, {scale})"),
+        DataType::Decimal256(precision, scale) => format!("decimal256({precision}, {scale})"),
+        DataType::Map(entries, keys_sorted) => {
+            let (key, value) = match entries.data_type() {
+                DataType::Struct(fields) if fields.len() == 2 => {
+                    (fields[0].data_type(), fields[1].data_type())
+                }
+                other => {
+                    return Err(py_type_err(format!(
+                        "Map entries must be a struct of a key and a value, got {other}"
+                    )))
+                }
+            };
+            format!(
+                "map<{}, {}{}>",
+                arrow_type_string(key)?,
+                arrow_type_string(value)?,
+                if *keys_sorted { ", keys_sorted" } else { "" }
+            )
+        }
+        DataType::RunEndEncoded(run_ends, values) => format!(
+            "run_end_encoded<run_ends: {}, values: {}>",
+            arrow_type_string(run_ends.data_type())?,
+            arrow_type_string(values.data_type())?
+        ),
+    };
+    Ok(type_string)
+}
+
+/// Formats a nested field as Arrow does, e.g. `item: int32 not null`
+fn arrow_field_string(field: &Field) -> PyResult<String> {
+    let not_null = if field.is_nullable() { "" } else { " not null" };
+    Ok(format!(
+        "{}: {}{not_null}",
+        field.name(),
+        arrow_type_string(field.data_type())?
+    ))
+}
+
+fn spark_sql_type(data_type: &DataType) -> PyResult<String> {
+    let spark_type = match data_type {
+        DataType::Null => "void".to_string(),
+        DataType::Boolean => "boolean".to_string(),
+        DataType::Int8 => "tinyint".to_string(),
+        DataType::Int16 | DataType::UInt8 => "smallint".to_string(),
+        DataType::Int32 | DataType::UInt16 => "int".to_string(),
+        DataType::Int64 | DataType::UInt32 => "bigint".to_string(),
+        DataType::UInt64 => "decimal(20,0)".to_string(),
+        DataType::Float16 | DataType::Float32 => "float".to_string(),
+        DataType::Float64 => "double".to_string(),
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale)
+            if *precision <= DECIMAL128_MAX_PRECISION =>
+        {
+            format!("decimal({precision},{scale})")
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => "string".to_string(),
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+            "binary".to_string()
+        }
+        DataType::Date32 | DataType::Date64 => "date".to_string(),
+        DataType::Timestamp(_, Some(_)) => "timestamp".to_string(),
+        DataType::Timestamp(_, None) => "timestamp_ntz".to_string(),
+        DataType::Duration(_) => "interval day to second".to_string(),
+        DataType::Interval(IntervalUnit::YearMonth) => "interval year to month".to_string(),
+        DataType::List(field) | DataType::LargeList(field) | DataType::FixedSizeList(field, _) => {
+            format!("array<{}>", spark_sql_type(field.data_type())?)
+        }
+        DataType::Struct(fields) => format!(
+            "struct<{}>",
+            fields
+                .iter()
+                .map(|field| Ok(format!(
+                    "{}:{}",
+                    ddl_field_name(field.name()),
+                    spark_sql_type(field.data_type())?
+                )))
+                .collect::<PyResult<Vec<_>>>()?
+                .join(",")
+        ),
+        DataType::Map(entries, _) => match entries.data_type() {
+            DataType::Struct(fields) if fields.len() == 2 => format!(
+                "map<{},{}>",
+                spark_sql_type(fields[0].data_type())?,
+                spark_sql_type(fields[1].data_type())?
+            ),
+            other => {
+                return Err(py_type_err(format!(
+                    "Map entries must be a struct of a key and a value, got {other}"
+                )))
+            }
+        },
+        DataType::Dictionary(_, value_type) => spark_sql_type(value_type)?,
+        DataType::RunEndEncoded(_, values) => spark_sql_type(values.data_type())?,
+        other => {
+            return Err(py_datafusion_err(DataFusionError::NotImplemented(format!(
+                "Spark SQL has no type for {other:?}"
+            ))))
+        }
+    };
+    Ok(spark_type)
+}
+
+fn bigquery_type(data_type: &DataType) -> PyResult<String> {
+    let unsupported = || {
+        Err(py_datafusion_err(DataFusionError::NotImplemented(format!(
+            "BigQuery has no type for {data_type:?}"
+        ))))
+    };
+    let bigquery_type = match data_type {
+        DataType::Boolean => "BOOL".to_string(),
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32 => "INT64".to_string(),
+        DataType::UInt64 => "NUMERIC".to_string(),
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => "FLOAT64".to_string(),
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+            let integer_digits = i16::from(*precision) - i16::from(*scale);
+            if (0..=9).contains(scale) && integer_digits <= 29 {
+                "NUMERIC".to_string()
+            } else if (0..=38).contains(scale) && integer_digits <= 38 {
+                "BIGNUMERIC".to_string()
+            } else {
+                return unsupported();
+            }
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => "STRING".to_string(),
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+            "BYTES".to_string()
+        }
+        DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+        DataType::Time32(_) | DataType::Time64(_) => "TIME".to_string(),
+        DataType::Timestamp(_, Some(_)) => "TIMESTAMP".to_string(),
+        DataType::Timestamp(_, None) => "DATETIME".to_string(),
+        DataType::Interval(_) => "INTERVAL".to_string(),
+        DataType::List(field) | DataType::LargeList(field) | DataType::FixedSizeList(field, _) => {
+            let element_type = match field.data_type() {
+                DataType::Dictionary(_, value_type) => value_type.as_ref(),
+                element_type => element_type,
+            };
+            if matches!(
+                element_type,
+                DataType::List(_) | DataType::LargeList(_) | DataType::FixedSizeList(_, _)
+            ) {
+                return unsupported();
+            }
+            format!("ARRAY<{}>", bigquery_type(element_type)?)
+        }
+        DataType::Struct(fields) => format!(
+            "STRUCT<{}>",
+            fields
+                .iter()
+                .map(|field| Ok(format!(
+                    "{} {}",
+                    ddl_field_name(field.name()),
+                    bigquery_type(field.data_type())?
+                )))
+                .collect::<PyResult<Vec<_>>>()?
+                .join(", ")
+        ),
+        DataType::Dictionary(_, value_type) => bigquery_type(value_type)?,
+        DataType::RunEndEncoded(_, values) => bigquery_type(values.data_type())?,
+        _ => return unsupported(),
+    };
+    Ok(bigquery_type)
+}
+
+/// Quotes a struct field name for Spark SQL or BigQuery DDL, which both quote with
+/// backticks, if it is not a plain identifier
+fn ddl_field_name(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+fn feather_type_name(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::Null => "null",
+        DataType::Boolean => "bool",
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => "int",
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => "floatingpoint",
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => "decimal",
+        DataType::Utf8 => "utf8",
+        DataType::LargeUtf8 => "largeutf8",
+        DataType::Binary => "binary",
+        DataType::LargeBinary => "largebinary",
+        DataType::FixedSizeBinary(_) => "fixedsizebinary",
+        DataType::Date32 | DataType::Date64 => "date",
+        DataType::Time32(_) | DataType::Time64(_) => "time",
+        DataType::Timestamp(_, _) => "timestamp",
+        DataType::Duration(_) => "duration",
+        DataType::Interval(_) => "interval",
+        DataType::List(_) => "list",
+        DataType::LargeList(_) => "largelist",
+        DataType::FixedSizeList(_, _) => "fixedsizelist",
+        DataType::Struct(_) => "struct",
+        DataType::Map(_, _) => "map",
+        DataType::Union(_, _) => "union",
+        DataType::Dictionary(_, value_type) => feather_type_name(value_type),
+        DataType::RunEndEncoded(_, _) => "runendencoded",
+    }
+}
+
+fn time_unit_string(unit: &TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Second => "s",
+        TimeUnit::Millisecond => "ms",
+        TimeUnit::Microsecond => "us",
+        TimeUnit::Nanosecond => "ns",
+    }
+}
+
+fn struct_depth(data_type: &DataType) -> usize {
+    match data_type {
+        DataType::Struct(fields) => {
+            1 + fields
+                .iter()
+                .map(|field| struct_depth(field.data_type()))
+                .max()
+                .unwrap_or(0)
+        }
+        _ => 0,
+    }
+}
+
 impl From<DataType> for PyDataType {
     fn from(data_type: DataType) -> PyDataType {
         PyDataType { data_type }
     }
 }
 
+/// Wraps `ScalarValue` so it can be handed back and forth with Python, where it
+/// is represented as a `pyarrow.Scalar`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PyScalarValue(pub ScalarValue);
+
+impl From<ScalarValue> for PyScalarValue {
+    fn from(value: ScalarValue) -> PyScalarValue {
+        PyScalarValue(value)
+    }
+}
+
+impl From<PyScalarValue> for ScalarValue {
+    fn from(value: PyScalarValue) -> ScalarValue {
+        value.0
+    }
+}
+
+impl PyScalarValue {
+    /// Converts a `pyarrow.Scalar`, or a plain Python value through `pyarrow.scalar`,
+    /// which infers `Int64`, `Float64`, `Utf8`, `Binary`, timestamps with or without
+    /// a time zone, dates, times, decimals keeping their scale and lists. A
+    /// `timedelta` becomes a month-day-nano interval.
+    /// An int out of the `Int64` range becomes a `UInt64`, or a decimal if it is out of
+    /// that range too. With `data_type` the value is converted to that type instead,
+    /// which also types a `None` that would otherwise be a `Null`.
+    pub fn from_python(value: &PyAny, data_type: Option<&DataType>) -> PyResult<Self> {
+        let py = value.py();
+        let pyarrow = py.import("pyarrow")?;
+        let is_scalar = value.is_instance(pyarrow.getattr("Scalar")?)?;
+        let scalar = match data_type {
+            Some(data_type) if is_scalar => {
+                value.call_method1("cast", (data_type.to_pyarrow(py)?,))?
+            }
+            Some(data_type) => pyarrow.getattr("scalar")?.call(
+                (value,),
+                Some([("type", data_type.to_pyarrow(py)?)].into_py_dict(py)),
+            )?,
+            None if is_scalar => value,
+            None if value.is_instance(py.import("datetime")?.getattr("timedelta")?)? => {
+                // DataFusion adds intervals rather than durations to timestamps
+                let days: i64 = value.getattr("days")?.extract()?;
+                let seconds: i64 = value.getattr("seconds")?.extract()?;
+                let microseconds: i64 = value.getattr("microseconds")?.extract()?;
+                let nanoseconds = seconds * 1_000_000_000 + microseconds * 1_000;
+                pyarrow.getattr("scalar")?.call(
+                    ((0, days, nanoseconds),),
+                    Some(
+                        [(
+                            "type",
+                            DataType::Interval(IntervalUnit::MonthDayNano).to_pyarrow(py)?,
+                        )]
+                        .into_py_dict(py),
+                    ),
+                )?
+            }
+            None if value.downcast::<PyLong>().is_ok()
+                && value.downcast::<PyBool>().is_err()
+                && value.extract::<i64>().is_err() =>
+            {
+                if value.extract::<u64>().is_ok() {
+                    pyarrow.getattr("scalar")?.call(
+                        (value,),
+                        Some([("type", DataType::UInt64.to_pyarrow(py)?)].into_py_dict(py)),
+                    )?
+                } else {
+                    let decimal = py.import("decimal")?.getattr("Decimal")?.call1((value,))?;
+                    pyarrow.call_method1("scalar", (decimal,))?
+                }
+            }
+            None => pyarrow.call_method1("scalar", (value,))?,
+        };
+        Ok(PyScalarValue(scalar.extract()?))
+    }
+}
+
+impl<'source> FromPyObject<'source> for PyScalarValue {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        PyScalarValue::from_python(value, None)
+    }
+}
+
+impl IntoPy<PyObject> for PyScalarValue {
+    fn into_py(self, py: Python) -> PyObject {
+        self.0.into_py(py)
+    }
+}
+
 /// Represents the possible Python types that can be mapped to the SQL types
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[pyclass(name = "PythonType", module = "datafusion.common")]
@@ -542,6 +1960,36 @@ pub enum PythonType {
     Str,
 }
 
+#[pymethods]
+impl PythonType {
+    fn __richcmp__(&self, other: PythonType, op: CompareOp) -> bool {
+        op.matches(self.cmp(&other))
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns every `PythonType` variant, in order
+    #[staticmethod]
+    fn all_types() -> Vec<PythonType> {
+        vec![
+            PythonType::Array,
+            PythonType::Bool,
+            PythonType::Bytes,
+            PythonType::Datetime,
+            PythonType::Float,
+            PythonType::Int,
+            PythonType::List,
+            PythonType::None,
+            PythonType::Object,
+            PythonType::Str,
+        ]
+    }
+}
+
 /// Represents the types that are possible for DataFusion to parse
 /// from a SQL query. Aka "SqlType" and are valid values for
 /// ANSI SQL
@@ -599,3 +2047,154 @@ pub enum SqlType {
     VARBINARY,
     VARCHAR,
 }
+
+impl fmt::Display for SqlType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keyword = match self {
+            SqlType::ANY => "ANY",
+            SqlType::ARRAY => "ARRAY",
+            SqlType::BIGINT => "BIGINT",
+            SqlType::BINARY => "BINARY",
+            SqlType::BOOLEAN => "BOOLEAN",
+            SqlType::CHAR => "CHAR",
+            SqlType::COLUMN_LIST => "COLUMN LIST",
+            SqlType::CURSOR => "CURSOR",
+            SqlType::DATE => "DATE",
+            SqlType::DECIMAL => "DECIMAL",
+            SqlType::DISTINCT => "DISTINCT",
+            SqlType::DOUBLE => "DOUBLE",
+            SqlType::DYNAMIC_STAR => "DYNAMIC STAR",
+            SqlType::FLOAT => "FLOAT",
+            SqlType::GEOMETRY => "GEOMETRY",
+            SqlType::INTEGER => "INTEGER",
+            SqlType::INTERVAL => "INTERVAL",
+            SqlType::INTERVAL_DAY => "INTERVAL DAY",
+            SqlType::INTERVAL_DAY_HOUR => "INTERVAL DAY TO HOUR",
+            SqlType::INTERVAL_DAY_MINUTE => "INTERVAL DAY TO MINUTE",
+            SqlType::INTERVAL_DAY_SECOND => "INTERVAL DAY TO SECOND",
+            SqlType::INTERVAL_HOUR => "INTERVAL HOUR",
+            SqlType::INTERVAL_HOUR_MINUTE => "INTERVAL HOUR TO MINUTE",
+            SqlType::INTERVAL_HOUR_SECOND => "INTERVAL HOUR TO SECOND",
+            SqlType::INTERVAL_MINUTE => "INTERVAL MINUTE",
+            SqlType::INTERVAL_MINUTE_SECOND => "INTERVAL MINUTE TO SECOND",
+            SqlType::INTERVAL_MONTH => "INTERVAL MONTH",
+            SqlType::INTERVAL_SECOND => "INTERVAL SECOND",
+            SqlType::INTERVAL_YEAR => "INTERVAL YEAR",
+            SqlType::INTERVAL_YEAR_MONTH => "INTERVAL YEAR TO MONTH",
+            SqlType::MAP => "MAP",
+            SqlType::MULTISET => "MULTISET",
+            SqlType::NULL => "NULL",
+            SqlType::OTHER => "OTHER",
+            SqlType::REAL => "REAL",
+            SqlType::ROW => "ROW",
+            SqlType::SARG => "SARG",
+            SqlType::SMALLINT => "SMALLINT",
+            SqlType::STRUCTURED => "STRUCTURED",
+            SqlType::SYMBOL => "SYMBOL",
+            SqlType::TIME => "TIME",
+            SqlType::TIME_WITH_LOCAL_TIME_ZONE => "TIME WITH LOCAL TIME ZONE",
+            SqlType::TIMESTAMP => "TIMESTAMP",
+            SqlType::TIMESTAMP_WITH_LOCAL_TIME_ZONE => "TIMESTAMP WITH LOCAL TIME ZONE",
+            SqlType::TINYINT => "TINYINT",
+            SqlType::UNKNOWN => "UNKNOWN",
+            SqlType::VARBINARY => "VARBINARY",
+            SqlType::VARCHAR => "VARCHAR",
+        };
+        write!(f, "{keyword}")
+    }
+}
+
+impl FromStr for SqlType {
+    type Err = DataFusionError;
+
+    /// Parses the SQL spelling of a type, ignoring case and whitespace. Multi-word
+    /// types such as `INTERVAL DAY TO HOUR` may also be given in their underscored
+    /// form, e.g. `INTERVAL_DAY_HOUR`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s
+            .split(|c: char| c.is_whitespace() || c == '_')
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_uppercase())
+            .collect::<Vec<_>>();
+        let normalized = match normalized.as_slice() {
+            [interval, from, to, until] if interval == "INTERVAL" && to == "TO" => {
+                format!("{interval}_{from}_{until}")
+            }
+            words => words.join("_"),
+        };
+        match normalized.as_str() {
+            "ANY" => Ok(SqlType::ANY),
+            "ARRAY" => Ok(SqlType::ARRAY),
+            "BIGINT" => Ok(SqlType::BIGINT),
+            "BINARY" => Ok(SqlType::BINARY),
+            "BOOLEAN" => Ok(SqlType::BOOLEAN),
+            "CHAR" => Ok(SqlType::CHAR),
+            "COLUMN_LIST" => Ok(SqlType::COLUMN_LIST),
+            "CURSOR" => Ok(SqlType::CURSOR),
+            "DATE" => Ok(SqlType::DATE),
+            "DECIMAL" => Ok(SqlType::DECIMAL),
+            "DISTINCT" => Ok(SqlType::DISTINCT),
+            "DOUBLE" => Ok(SqlType::DOUBLE),
+            "DYNAMIC_STAR" => Ok(SqlType::DYNAMIC_STAR),
+            "FLOAT" => Ok(SqlType::FLOAT),
+            "GEOMETRY" => Ok(SqlType::GEOMETRY),
+            "INTEGER" => Ok(SqlType::INTEGER),
+            "INTERVAL" => Ok(SqlType::INTERVAL),
+            "INTERVAL_DAY" => Ok(SqlType::INTERVAL_DAY),
+            "INTERVAL_DAY_HOUR" => Ok(SqlType::INTERVAL_DAY_HOUR),
+            "INTERVAL_DAY_MINUTE" => Ok(SqlType::INTERVAL_DAY_MINUTE),
+            "INTERVAL_DAY_SECOND" => Ok(SqlType::INTERVAL_DAY_SECOND),
+            "INTERVAL_HOUR" => Ok(SqlType::INTERVAL_HOUR),
+            "INTERVAL_HOUR_MINUTE" => Ok(SqlType::INTERVAL_HOUR_MINUTE),
+            "INTERVAL_HOUR_SECOND" => Ok(SqlType::INTERVAL_HOUR_SECOND),
+            "INTERVAL_MINUTE" => Ok(SqlType::INTERVAL_MINUTE),
+            "INTERVAL_MINUTE_SECOND" => Ok(SqlType::INTERVAL_MINUTE_SECOND),
+            "INTERVAL_MONTH" => Ok(SqlType::INTERVAL_MONTH),
+            "INTERVAL_SECOND" => Ok(SqlType::INTERVAL_SECOND),
+            "INTERVAL_YEAR" => Ok(SqlType::INTERVAL_YEAR),
+            "INTERVAL_YEAR_MONTH" => Ok(SqlType::INTERVAL_YEAR_MONTH),
+            "MAP" => Ok(SqlType::MAP),
+            "MULTISET" => Ok(SqlType::MULTISET),
+            "NULL" => Ok(SqlType::NULL),
+            "OTHER" => Ok(SqlType::OTHER),
+            "REAL" => Ok(SqlType::REAL),
+            "ROW" => Ok(SqlType::ROW),
+            "SARG" => Ok(SqlType::SARG),
+            "SMALLINT" => Ok(SqlType::SMALLINT),
+            "STRUCTURED" => Ok(SqlType::STRUCTURED),
+            "SYMBOL" => Ok(SqlType::SYMBOL),
+            "TIME" => Ok(SqlType::TIME),
+            "TIME_WITH_LOCAL_TIME_ZONE" => Ok(SqlType::TIME_WITH_LOCAL_TIME_ZONE),
+            "TIMESTAMP" => Ok(SqlType::TIMESTAMP),
+            "TIMESTAMP_WITH_LOCAL_TIME_ZONE" => Ok(SqlType::TIMESTAMP_WITH_LOCAL_TIME_ZONE),
+            "TINYINT" => Ok(SqlType::TINYINT),
+            "UNKNOWN" => Ok(SqlType::UNKNOWN),
+            "VARBINARY" => Ok(SqlType::VARBINARY),
+            "VARCHAR" => Ok(SqlType::VARCHAR),
+            _ => Err(DataFusionError::Plan(format!("Unknown SQL type: {s}"))),
+        }
+    }
+}
+
+#[pymethods]
+impl SqlType {
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    fn __richcmp__(&self, other: SqlType, op: CompareOp) -> bool {
+        op.matches(self.cmp(&other))
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_str")]
+    fn py_from_str(s: &str) -> PyResult<SqlType> {
+        SqlType::from_str(s).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
@@ -15,7 +15,10 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use datafusion::arrow::datatypes::DataType;
+use std::collections::HashMap;
+
+use datafusion::arrow::datatypes::{DataType, Field};
+use datafusion::arrow::pyarrow::PyArrowType;
 use datafusion_common::{DFField, OwnedTableReference};
 use pyo3::prelude::*;
 
@@ -100,9 +103,22 @@ impl PyDFField {
         Ok(self.field.qualifier().map(|q| format!("{}", q)))
     }
 
-    // TODO: Need bindings for Arrow `Field` first
-    // #[pyo3(name = "field")]
-    // fn py_field(&self) -> PyResult<Field> {}
+    /// The unqualified field as a `pyarrow.Field`, with its metadata
+    #[pyo3(name = "field")]
+    fn py_field(&self) -> PyArrowType<Field> {
+        Field::new(
+            self.field.name(),
+            self.field.data_type().clone(),
+            self.field.is_nullable(),
+        )
+        .with_metadata(self.field.field().metadata().clone())
+        .into()
+    }
+
+    /// The field-level metadata, such as the key-value metadata of a Parquet column
+    fn metadata(&self) -> HashMap<String, String> {
+        self.field.field().metadata().clone()
+    }
 
     #[pyo3(name = "strip_qualifier")]
     fn py_strip_qualifier(&self) -> PyResult<Self> {
@@ -15,11 +15,15 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use datafusion_common::DFSchema;
+use datafusion_common::{Column, DFSchema};
+use pyo3::exceptions::PyKeyError;
 use pyo3::prelude::*;
 
+use super::df_field::PyDFField;
+
 #[derive(Debug, Clone)]
 #[pyclass(name = "DFSchema", module = "datafusion.common", subclass)]
 pub struct PyDFSchema {
@@ -54,4 +58,31 @@ impl PyDFSchema {
     fn py_field_names(&self) -> PyResult<Vec<String>> {
         Ok(self.schema.field_names())
     }
+
+    /// The fields with their relation qualifiers, in order
+    fn fields(&self) -> Vec<PyDFField> {
+        self.schema
+            .fields()
+            .iter()
+            .map(|field| field.clone().into())
+            .collect()
+    }
+
+    /// The field with the given name, which may be qualified as in `"t.a"`. Raises a
+    /// `KeyError` if there is no such field, or if an unqualified name is ambiguous.
+    fn field(&self, name: &str) -> PyResult<PyDFField> {
+        self.schema
+            .field_from_column(&Column::from_qualified_name(name))
+            .map(|field| field.clone().into())
+            .map_err(|e| PyKeyError::new_err(e.to_string()))
+    }
+
+    /// The schema-level metadata
+    fn metadata(&self) -> HashMap<String, String> {
+        self.schema.metadata().clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.schema.fields().len()
+    }
 }
@@ -23,35 +23,50 @@ use object_store::ObjectStore;
 use url::Url;
 use uuid::Uuid;
 
-use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::exceptions::{PyKeyError, PyNotImplementedError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 
 use crate::catalog::{PyCatalog, PyTable};
+use crate::common::data_type::{cast_scalar, PyScalarValue};
 use crate::dataframe::PyDataFrame;
 use crate::dataset::Dataset;
+#[cfg(feature = "delta")]
+use crate::delta;
 use crate::errors::{py_datafusion_err, DataFusionError};
+use crate::expr::sort_expr::SortKey;
+use crate::listing::LazyListingTable;
 use crate::physical_plan::PyExecutionPlan;
 use crate::record_batch::PyRecordBatchStream;
 use crate::sql::logical::PyLogicalPlan;
 use crate::store::StorageContexts;
 use crate::udaf::PyAggregateUDF;
-use crate::udf::PyScalarUDF;
-use crate::utils::{get_tokio_runtime, wait_for_future};
-use datafusion::arrow::datatypes::{DataType, Schema};
+use crate::udf::{PyScalarUDF, UdfCacheScope};
+use crate::utils::{get_tokio_runtime, list_all_files, wait_for_future};
+use datafusion::arrow::datatypes::{DataType, Schema, SchemaRef};
 use datafusion::arrow::pyarrow::PyArrowType;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::datasource::datasource::TableProvider;
+use datafusion::datasource::file_format::csv::CsvFormat;
+#[cfg(feature = "delta")]
+use datafusion::datasource::file_format::parquet::ParquetFormat;
+use datafusion::datasource::file_format::FileFormat;
+use datafusion::datasource::listing::{
+    ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+};
 use datafusion::datasource::MemTable;
-use datafusion::execution::context::{SessionConfig, SessionContext, TaskContext};
+use datafusion::execution::context::{SessionConfig, SessionContext, SessionState, TaskContext};
 use datafusion::execution::disk_manager::DiskManagerConfig;
 use datafusion::execution::memory_pool::{FairSpillPool, GreedyMemoryPool, UnboundedMemoryPool};
+use datafusion::execution::options::ReadOptions;
 use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
+use datafusion::optimizer::optimizer::Optimizer;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use datafusion::prelude::{
     AvroReadOptions, CsvReadOptions, DataFrame, NdJsonReadOptions, ParquetReadOptions,
 };
 use datafusion_common::ScalarValue;
-use pyo3::types::PyTuple;
+use datafusion_expr::Expr;
+use pyo3::types::{PyDict, PyTuple};
 use tokio::task::JoinHandle;
 
 /// Configuration options for a SessionContext
@@ -218,9 +233,13 @@ pub(crate) struct PySessionContext {
 
 #[pymethods]
 impl PySessionContext {
-    #[pyo3(signature = (config=None, runtime=None))]
+    #[pyo3(signature = (config=None, runtime=None, disabled_optimizer_rules=vec![]))]
     #[new]
-    fn new(config: Option<PySessionConfig>, runtime: Option<PyRuntimeConfig>) -> PyResult<Self> {
+    fn new(
+        config: Option<PySessionConfig>,
+        runtime: Option<PyRuntimeConfig>,
+        disabled_optimizer_rules: Vec<String>,
+    ) -> PyResult<Self> {
         let config = if let Some(c) = config {
             c.config
         } else {
@@ -232,9 +251,15 @@ impl PySessionContext {
             RuntimeConfig::default()
         };
         let runtime = Arc::new(RuntimeEnv::new(runtime_config)?);
-        Ok(PySessionContext {
-            ctx: SessionContext::with_config_rt(config, runtime),
-        })
+        let state = if disabled_optimizer_rules.is_empty() {
+            SessionState::with_config_rt(config, runtime)
+        } else {
+            session_state_without_rules(config, runtime, &disabled_optimizer_rules)?
+        };
+        // gives the UDFs created with a cache_size a new cache in every query
+        let state = state.add_analyzer_rule(Arc::new(UdfCacheScope));
+        let ctx = SessionContext::with_state(state);
+        Ok(PySessionContext { ctx })
     }
 
     /// Register a an object store with the given name
@@ -271,10 +296,18 @@ impl PySessionContext {
         Ok(())
     }
 
-    /// Returns a PyDataFrame whose plan corresponds to the SQL statement.
-    fn sql(&mut self, query: &str, py: Python) -> PyResult<PyDataFrame> {
+    /// Returns a PyDataFrame whose plan corresponds to the SQL statement. `params` binds
+    /// the `$1`, `$2`, ... placeholders of the statement, as a list of their values or a
+    /// dict from their positions, e.g. `{"$1": 5}` or `{1: 5}`. Values are cast to the
+    /// type inferred for their placeholder, such as that of a column it is compared to.
+    #[pyo3(signature = (query, params=None))]
+    fn sql(&mut self, query: &str, params: Option<&PyAny>, py: Python) -> PyResult<PyDataFrame> {
         let result = self.ctx.sql(query);
         let df = wait_for_future(py, result).map_err(DataFusionError::from)?;
+        let df = match params {
+            Some(params) => bind_params(df, params)?,
+            None => df,
+        };
         Ok(PyDataFrame::new(df))
     }
 
@@ -443,21 +476,60 @@ impl PySessionContext {
     #[allow(clippy::too_many_arguments)]
     #[pyo3(signature = (name, path, table_partition_cols=vec![],
                         parquet_pruning=true,
-                        file_extension=".parquet"))]
+                        file_extension=".parquet",
+                        schema=None,
+                        lazy=false,
+                        file_sort_order=vec![]))]
     fn register_parquet(
         &mut self,
         name: &str,
         path: &str,
-        table_partition_cols: Vec<(String, String)>,
+        table_partition_cols: Vec<(String, PartitionColType)>,
         parquet_pruning: bool,
         file_extension: &str,
+        schema: Option<PyArrowType<Schema>>,
+        lazy: bool,
+        file_sort_order: Vec<SortKey>,
         py: Python,
     ) -> PyResult<()> {
+        let table_partition_cols = convert_table_partition_cols(table_partition_cols)?;
         let mut options = ParquetReadOptions::default()
-            .table_partition_cols(convert_table_partition_cols(table_partition_cols)?)
+            .table_partition_cols(table_partition_cols.clone())
             .parquet_pruning(parquet_pruning);
         options.file_extension = file_extension;
-        let result = self.ctx.register_parquet(name, path, options);
+        // the order in which the rows of every file are already sorted, which lets
+        // DataFusion skip sorting them again
+        let file_sort_order: Vec<Expr> = file_sort_order
+            .into_iter()
+            .map(SortKey::into_sort_expr)
+            .collect();
+        if lazy {
+            if !file_sort_order.is_empty() {
+                return Err(PyNotImplementedError::new_err(
+                    "A lazy Parquet read cannot declare a file_sort_order",
+                ));
+            }
+            let table = lazy_parquet_table(&self.ctx, path, schema, options)?;
+            self.ctx
+                .register_table(name, Arc::new(table))
+                .map_err(DataFusionError::from)?;
+            return Ok(());
+        }
+        validate_partition_values(path, &table_partition_cols)?;
+        if file_sort_order.is_empty() && schema.is_none() {
+            let result = self.ctx.register_parquet(name, path, options);
+            wait_for_future(py, result).map_err(DataFusionError::from)?;
+            return Ok(());
+        }
+        // `ParquetReadOptions` has no schema, so a given one goes through a listing table
+        let mut listing_options = options.to_listing_options(&self.ctx.copied_config());
+        if !file_sort_order.is_empty() {
+            listing_options = listing_options.with_file_sort_order(vec![file_sort_order]);
+        }
+        let schema = schema.map(|schema| Arc::new(schema.0));
+        let result = self
+            .ctx
+            .register_listing_table(name, path, listing_options, schema, None);
         wait_for_future(py, result).map_err(DataFusionError::from)?;
         Ok(())
     }
@@ -504,6 +576,80 @@ impl PySessionContext {
         Ok(())
     }
 
+    /// Infers a schema from the first CSV file found under `path`, registers all of
+    /// the files under `path` as a table using that schema and returns the schema.
+    /// Files whose columns differ from the inferred schema are rejected.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (name,
+                        path,
+                        has_header=true,
+                        delimiter=",",
+                        schema_infer_max_records=1000,
+                        file_extension=".csv"))]
+    fn register_csv_with_inferred_schema(
+        &mut self,
+        name: &str,
+        path: &str,
+        has_header: bool,
+        delimiter: &str,
+        schema_infer_max_records: usize,
+        file_extension: &str,
+        py: Python,
+    ) -> PyResult<PyArrowType<Schema>> {
+        let delimiter = delimiter.as_bytes();
+        if delimiter.len() != 1 {
+            return Err(PyValueError::new_err(
+                "Delimiter must be a single character",
+            ));
+        }
+
+        let result = self._register_csv_with_inferred_schema(
+            name,
+            path,
+            has_header,
+            delimiter[0],
+            schema_infer_max_records,
+            file_extension,
+        );
+        let schema = wait_for_future(py, result)?;
+        Ok(PyArrowType(schema.as_ref().clone()))
+    }
+
+    /// Registers the Delta Lake table at `table_uri`, a local path or a URL of a
+    /// registered object store, as a table of its Parquet files. The latest version is
+    /// read unless a `version` number, or an `as_of_timestamp` given as a `datetime` or
+    /// as seconds since the epoch, selects an earlier one.
+    #[cfg(feature = "delta")]
+    #[pyo3(signature = (name, table_uri, version=None, as_of_timestamp=None))]
+    fn register_delta(
+        &self,
+        name: &str,
+        table_uri: &str,
+        version: Option<u64>,
+        as_of_timestamp: Option<&PyAny>,
+        py: Python,
+    ) -> PyResult<()> {
+        let version = match (version, as_of_timestamp) {
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "Only one of version and as_of_timestamp may be given",
+                ))
+            }
+            (Some(version), None) => delta::DeltaVersion::Version(version),
+            (None, Some(timestamp)) => {
+                let seconds: f64 = if timestamp.hasattr("timestamp")? {
+                    timestamp.call_method0("timestamp")?.extract()?
+                } else {
+                    timestamp.extract()?
+                };
+                delta::DeltaVersion::AsOfTimestamp((seconds * 1000.0).round() as i64)
+            }
+            (None, None) => delta::DeltaVersion::Latest,
+        };
+        wait_for_future(py, self._register_delta(name, table_uri, version))?;
+        Ok(())
+    }
+
     // Registers a PyArrow.Dataset
     fn register_dataset(&self, name: &str, dataset: &PyAny, py: Python) -> PyResult<()> {
         let table: Arc<dyn TableProvider> = Arc::new(Dataset::new(dataset, py)?);
@@ -558,6 +704,28 @@ impl PySessionContext {
         self.ctx.session_id()
     }
 
+    /// Sets the configuration option `key` of this running session, e.g.
+    /// `datafusion.execution.parquet.pushdown_filters`, to `value`. Booleans and numbers
+    /// are given as their SQL spelling; unknown keys raise a KeyError.
+    fn set_config(&self, key: &str, value: &PyAny, py: Python) -> PyResult<()> {
+        self.config_entry(key)?;
+        let value = if value.is_instance_of::<pyo3::types::PyBool>()? {
+            value.extract::<bool>()?.to_string()
+        } else {
+            value.str()?.to_string()
+        };
+        // The session state is only mutable through a SET statement
+        let statement = format!("SET {} TO '{}'", key, value.replace('\'', "''"));
+        wait_for_future(py, self.ctx.sql(&statement)).map_err(DataFusionError::from)?;
+        Ok(())
+    }
+
+    /// Returns the value of the configuration option `key` of this running session, as
+    /// a string, or None when it is unset. Unknown keys raise a KeyError.
+    fn get_config(&self, key: &str) -> PyResult<Option<String>> {
+        self.config_entry(key)
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[pyo3(signature = (path, schema=None, schema_infer_max_records=1000, file_extension=".json", table_partition_cols=vec![]))]
     fn read_json(
@@ -566,7 +734,7 @@ impl PySessionContext {
         schema: Option<PyArrowType<Schema>>,
         schema_infer_max_records: usize,
         file_extension: &str,
-        table_partition_cols: Vec<(String, String)>,
+        table_partition_cols: Vec<(String, PartitionColType)>,
         py: Python,
     ) -> PyResult<PyDataFrame> {
         let path = path
@@ -604,7 +772,7 @@ impl PySessionContext {
         delimiter: &str,
         schema_infer_max_records: usize,
         file_extension: &str,
-        table_partition_cols: Vec<(String, String)>,
+        table_partition_cols: Vec<(String, PartitionColType)>,
         py: Python,
     ) -> PyResult<PyDataFrame> {
         let path = path
@@ -643,21 +811,40 @@ impl PySessionContext {
         table_partition_cols=vec![],
         parquet_pruning=true,
         file_extension=".parquet",
-        skip_metadata=true))]
+        skip_metadata=true,
+        schema=None,
+        lazy=false))]
     fn read_parquet(
         &self,
         path: &str,
-        table_partition_cols: Vec<(String, String)>,
+        table_partition_cols: Vec<(String, PartitionColType)>,
         parquet_pruning: bool,
         file_extension: &str,
         skip_metadata: bool,
+        schema: Option<PyArrowType<Schema>>,
+        lazy: bool,
         py: Python,
     ) -> PyResult<PyDataFrame> {
+        let table_partition_cols = convert_table_partition_cols(table_partition_cols)?;
         let mut options = ParquetReadOptions::default()
-            .table_partition_cols(convert_table_partition_cols(table_partition_cols)?)
+            .table_partition_cols(table_partition_cols.clone())
             .parquet_pruning(parquet_pruning)
             .skip_metadata(skip_metadata);
         options.file_extension = file_extension;
+        if lazy {
+            let table = lazy_parquet_table(&self.ctx, path, schema, options)?;
+            let df = self.ctx.read_table(Arc::new(table))?;
+            return Ok(PyDataFrame::new(df));
+        }
+        validate_partition_values(path, &table_partition_cols)?;
+        if let Some(schema) = schema {
+            // `ParquetReadOptions` has no schema, so a given one goes through a listing table
+            let config = ListingTableConfig::new(ListingTableUrl::parse(path)?)
+                .with_listing_options(options.to_listing_options(&self.ctx.copied_config()))
+                .with_schema(Arc::new(schema.0));
+            let table = ListingTable::try_new(config)?;
+            return Ok(PyDataFrame::new(self.ctx.read_table(Arc::new(table))?));
+        }
 
         let result = self.ctx.read_parquet(path, options);
         let df = PyDataFrame::new(wait_for_future(py, result).map_err(DataFusionError::from)?);
@@ -670,7 +857,7 @@ impl PySessionContext {
         &self,
         path: &str,
         schema: Option<PyArrowType<Schema>>,
-        table_partition_cols: Vec<(String, String)>,
+        table_partition_cols: Vec<(String, PartitionColType)>,
         file_extension: &str,
         py: Python,
     ) -> PyResult<PyDataFrame> {
@@ -724,25 +911,355 @@ impl PySessionContext {
 }
 
 impl PySessionContext {
+    /// The current value of the known configuration option `key`
+    fn config_entry(&self, key: &str) -> PyResult<Option<String>> {
+        let state = self.ctx.state();
+        let entries = state.config().options().entries();
+        if let Some(entry) = entries.iter().find(|entry| entry.key == key) {
+            return Ok(entry.value.clone());
+        }
+        let prefixed = format!("datafusion.{key}");
+        let hint = if entries.iter().any(|entry| entry.key == prefixed) {
+            format!(", did you mean {prefixed}?")
+        } else {
+            String::new()
+        };
+        Err(PyKeyError::new_err(format!(
+            "Unknown configuration option {key}{hint}"
+        )))
+    }
+
     async fn _table(&self, name: &str) -> datafusion_common::Result<DataFrame> {
         self.ctx.table(name).await
     }
+
+    async fn _register_csv_with_inferred_schema(
+        &self,
+        name: &str,
+        path: &str,
+        has_header: bool,
+        delimiter: u8,
+        schema_infer_max_records: usize,
+        file_extension: &str,
+    ) -> Result<SchemaRef, DataFusionError> {
+        let csv_format = |max_records: usize| {
+            CsvFormat::default()
+                .with_has_header(has_header)
+                .with_delimiter(delimiter)
+                .with_schema_infer_max_rec(Some(max_records))
+        };
+
+        let state = self.ctx.state();
+        let table_path = ListingTableUrl::parse(path)?;
+        let store = state.runtime_env().object_store(&table_path)?;
+        let mut files = list_all_files(&table_path, store.as_ref(), file_extension).await?;
+        files.sort_by(|a, b| a.location.cmp(&b.location));
+
+        let (first, rest) = files.split_first().ok_or_else(|| {
+            DataFusionError::Common(format!(
+                "No files with extension '{file_extension}' found in {path}"
+            ))
+        })?;
+
+        let format = Arc::new(csv_format(schema_infer_max_records));
+        let schema = format
+            .infer_schema(&state, &store, std::slice::from_ref(first))
+            .await?;
+
+        // Only the header is needed to check the remaining files against the schema
+        let header_format = csv_format(1);
+        for file in rest {
+            let file_schema = header_format
+                .infer_schema(&state, &store, std::slice::from_ref(file))
+                .await?;
+            if field_names(&file_schema) != field_names(&schema) {
+                return Err(DataFusionError::Common(format!(
+                    "File {} has columns {:?} which differ from the columns {:?} inferred from {}",
+                    file.location,
+                    field_names(&file_schema),
+                    field_names(&schema),
+                    first.location
+                )));
+            }
+        }
+
+        let options = ListingOptions::new(format).with_file_extension(file_extension);
+        let config = ListingTableConfig::new(table_path)
+            .with_listing_options(options)
+            .with_schema(schema.clone());
+        let table = ListingTable::try_new(config)?;
+        self.ctx.register_table(name, Arc::new(table))?;
+
+        Ok(schema)
+    }
+
+    #[cfg(feature = "delta")]
+    async fn _register_delta(
+        &self,
+        name: &str,
+        table_uri: &str,
+        version: delta::DeltaVersion,
+    ) -> Result<(), DataFusionError> {
+        let state = self.ctx.state();
+        let table_url = ListingTableUrl::parse(table_uri)?;
+        let store = state.runtime_env().object_store(&table_url)?;
+        let snapshot = delta::load_snapshot(store.as_ref(), table_url.prefix(), version).await?;
+
+        let root = table_uri.trim_end_matches('/');
+        let file_urls = snapshot
+            .files
+            .iter()
+            .map(|file| {
+                if file.contains("://") {
+                    ListingTableUrl::parse(file)
+                } else {
+                    ListingTableUrl::parse(format!("{root}/{file}"))
+                }
+            })
+            .collect::<datafusion_common::Result<Vec<_>>>()?;
+
+        let format = Arc::new(ParquetFormat::default());
+        let schema = match snapshot.schema {
+            Some(schema) => Arc::new(schema),
+            None if !file_urls.is_empty() => {
+                ListingTableConfig::new_with_multi_paths(file_urls.clone())
+                    .with_listing_options(ListingOptions::new(format.clone()))
+                    .infer_schema(&state)
+                    .await?
+                    .file_schema
+                    .ok_or_else(|| {
+                        DataFusionError::Common(format!(
+                            "Unable to infer the schema of {table_uri}"
+                        ))
+                    })?
+            }
+            None => {
+                return Err(DataFusionError::Common(format!(
+                    "Delta table {table_uri} has no schema and no data files at version {}",
+                    snapshot.version
+                )))
+            }
+        };
+
+        let config = ListingTableConfig::new_with_multi_paths(file_urls)
+            .with_listing_options(ListingOptions::new(format))
+            .with_schema(schema);
+        let table = ListingTable::try_new(config)?;
+        self.ctx.register_table(name, Arc::new(table))?;
+        Ok(())
+    }
+}
+
+fn field_names(schema: &Schema) -> Vec<&String> {
+    schema.fields().iter().map(|field| field.name()).collect()
+}
+
+/// Builds a `SessionState` from which the named logical and physical optimizer
+/// rules have been removed. Unknown rule names are reported as an error.
+fn session_state_without_rules(
+    config: SessionConfig,
+    runtime: Arc<RuntimeEnv>,
+    disabled_rules: &[String],
+) -> Result<SessionState, DataFusionError> {
+    let state = SessionState::with_config_rt(config, runtime);
+    let optimizer_rules = Optimizer::new().rules;
+    let physical_optimizer_rules = state.physical_optimizers().to_vec();
+
+    for name in disabled_rules {
+        if !optimizer_rules.iter().any(|rule| rule.name() == name)
+            && !physical_optimizer_rules
+                .iter()
+                .any(|rule| rule.name() == name)
+        {
+            return Err(DataFusionError::Common(format!(
+                "Unknown optimizer rule '{name}'"
+            )));
+        }
+    }
+
+    let is_enabled = |name: &str| !disabled_rules.iter().any(|disabled| disabled == name);
+    let optimizer_rules = optimizer_rules
+        .into_iter()
+        .filter(|rule| is_enabled(rule.name()))
+        .collect();
+    let physical_optimizer_rules = physical_optimizer_rules
+        .into_iter()
+        .filter(|rule| is_enabled(rule.name()))
+        .collect();
+
+    Ok(state
+        .with_optimizer_rules(optimizer_rules)
+        .with_physical_optimizer_rules(physical_optimizer_rules))
+}
+
+/// The type of a partition column, either a pyarrow type or the name "string"
+#[derive(FromPyObject)]
+enum PartitionColType {
+    Arrow(PyArrowType<DataType>),
+    Name(String),
 }
 
 fn convert_table_partition_cols(
-    table_partition_cols: Vec<(String, String)>,
+    table_partition_cols: Vec<(String, PartitionColType)>,
 ) -> Result<Vec<(String, DataType)>, DataFusionError> {
     table_partition_cols
         .into_iter()
-        .map(|(name, ty)| match ty.as_str() {
-            "string" => Ok((name, DataType::Utf8)),
-            _ => Err(DataFusionError::Common(format!(
+        .map(|(name, ty)| match ty {
+            PartitionColType::Arrow(ty) => Ok((name, ty.0)),
+            PartitionColType::Name(ty) if ty == "string" => Ok((name, DataType::Utf8)),
+            PartitionColType::Name(ty) => Err(DataFusionError::Common(format!(
                 "Unsupported data type '{ty}' for partition column"
             ))),
         })
         .collect::<Result<Vec<_>, _>>()
 }
 
+/// Binds the values of `params`, a sequence or a dict keyed by position, to the
+/// placeholders of `df`'s plan
+fn bind_params(df: DataFrame, params: &PyAny) -> PyResult<DataFrame> {
+    let values: Vec<PyScalarValue> = match params.downcast::<PyDict>() {
+        Ok(params) => {
+            let mut values = vec![None; params.len()];
+            for (key, value) in params.iter() {
+                let position = match key.extract::<usize>() {
+                    Ok(position) => Some(position),
+                    Err(_) => key
+                        .extract::<&str>()
+                        .ok()
+                        .and_then(|key| key.strip_prefix('$').unwrap_or(key).parse().ok()),
+                };
+                let slot = position
+                    .filter(|position| (1..=values.len()).contains(position))
+                    .ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Invalid parameter {key}, parameters are bound by position as \
+                             $1 to ${}, named placeholders are not supported",
+                            values.len()
+                        ))
+                    })?;
+                if values[slot - 1].is_some() {
+                    return Err(PyValueError::new_err(format!(
+                        "parameter ${slot} given twice"
+                    )));
+                }
+                values[slot - 1] = Some(value.extract()?);
+            }
+            values
+                .into_iter()
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| PyValueError::new_err("Every parameter must be given a value"))?
+        }
+        Err(_) => params.extract()?,
+    };
+
+    let types = df
+        .logical_plan()
+        .get_parameter_types()
+        .map_err(DataFusionError::from)?;
+    if types.len() != values.len() {
+        return Err(PyValueError::new_err(format!(
+            "The query has {} parameters but {} values were given",
+            types.len(),
+            values.len()
+        )));
+    }
+    let values = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let id = format!("${}", i + 1);
+            let value = value.0;
+            match types.get(&id) {
+                Some(Some(data_type)) if value.get_datatype() != *data_type => {
+                    cast_scalar(&value, data_type).map_err(|_| {
+                        PyTypeError::new_err(format!(
+                            "Cannot bind {value:?} to parameter {id} of type {data_type}"
+                        ))
+                    })
+                }
+                _ => Ok(value),
+            }
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let df = df
+        .with_param_values(values)
+        .map_err(DataFusionError::from)?;
+    Ok(df)
+}
+
+/// A table over the Parquet files under `path`, as `read_parquet` would create, except
+/// that the files are only listed when it is first scanned. Inferring the schema would
+/// list them, so `schema` must be given.
+fn lazy_parquet_table(
+    ctx: &SessionContext,
+    path: &str,
+    schema: Option<PyArrowType<Schema>>,
+    options: ParquetReadOptions,
+) -> PyResult<LazyListingTable> {
+    let schema = schema.ok_or_else(|| {
+        PyValueError::new_err(
+            "A lazy Parquet read needs a schema, since inferring it would list the files",
+        )
+    })?;
+    let table_path = ListingTableUrl::parse(path)?;
+    let options = options.to_listing_options(&ctx.copied_config());
+    Ok(LazyListingTable::new(
+        table_path,
+        options,
+        Arc::new(schema.0),
+    ))
+}
+
+/// Checks that the values of the typed partition columns found in the directory names
+/// under a local `path`, such as `year=2024`, can be parsed to the declared types.
+/// Remote paths are left for DataFusion to check when the files are listed.
+fn validate_partition_values(
+    path: &str,
+    table_partition_cols: &[(String, DataType)],
+) -> Result<(), DataFusionError> {
+    let root = PathBuf::from(path.strip_prefix("file://").unwrap_or(path));
+    if !root.is_dir()
+        || table_partition_cols
+            .iter()
+            .all(|(_, ty)| *ty == DataType::Utf8)
+    {
+        return Ok(());
+    }
+
+    let io_err = |dir: &PathBuf, e: std::io::Error| {
+        DataFusionError::Common(format!("{}: {e}", dir.display()))
+    };
+    let mut dirs = vec![root];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(|e| io_err(&dir, e))? {
+            let entry_path = entry.map_err(|e| io_err(&dir, e))?.path();
+            if !entry_path.is_dir() {
+                continue;
+            }
+            let dir_name = entry_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if let Some((key, value)) = dir_name.split_once('=') {
+                let column = table_partition_cols.iter().find(|(name, _)| name == key);
+                if let Some((_, ty)) = column {
+                    if value != "__HIVE_DEFAULT_PARTITION__" {
+                        ScalarValue::try_from_string(value.to_string(), ty).map_err(|e| {
+                            DataFusionError::Common(format!(
+                                "Invalid value '{value}' for partition column {key} of type \
+                                 {ty} in {}: {e}",
+                                entry_path.display()
+                            ))
+                        })?;
+                    }
+                }
+            }
+            dirs.push(entry_path);
+        }
+    }
+    Ok(())
+}
+
 impl From<PySessionContext> for SessionContext {
     fn from(ctx: PySessionContext) -> SessionContext {
         ctx.ctx
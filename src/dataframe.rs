@@ -15,19 +15,68 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::common::data_type::{cast_scalar, ffi_schema_from_capsule, PyScalarValue};
+use crate::common::df_schema::PyDFSchema;
 use crate::physical_plan::PyExecutionPlan;
+use crate::record_batch::PyRecordBatchStream;
+use crate::sql::exceptions::py_parsing_exp;
 use crate::sql::logical::PyLogicalPlan;
-use crate::utils::wait_for_future;
-use crate::{errors::DataFusionError, expr::PyExpr};
-use datafusion::arrow::datatypes::Schema;
+use crate::sql::predicate::sql_to_expr;
+use crate::utils::{
+    deadline_after, get_tokio_runtime, timeout_err, wait_for_future, wait_for_future_until,
+    wait_for_future_with_timeout,
+};
+use crate::writer::{write_files, CsvWriter, JsonWriter, ParquetWriter};
+use crate::{
+    errors::DataFusionError,
+    expr::{sort_expr::SortKey, PyExpr},
+};
+use datafusion::arrow::array::{
+    make_array, new_null_array, Array, ArrayData, ArrayRef, BooleanArray, Float64Array,
+    StringArray, UInt32Array,
+};
+use datafusion::arrow::buffer::Buffer;
+use datafusion::arrow::compute::{can_cast_types, cast, concat, take};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::ffi_stream::FFI_ArrowArrayStream;
 use datafusion::arrow::pyarrow::{PyArrowConvert, PyArrowType};
+use datafusion::arrow::record_batch::{RecordBatch, RecordBatchReader};
+use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
 use datafusion::arrow::util::pretty;
 use datafusion::dataframe::DataFrame;
+use datafusion::execution::context::TaskContext;
+use datafusion::parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+use datafusion::parquet::errors::ParquetError;
+use datafusion::parquet::file::properties::{EnabledStatistics, WriterProperties};
+use datafusion::physical_plan::functions::make_scalar_function;
+use datafusion::physical_plan::udf::ScalarUDF;
+use datafusion::physical_plan::{collect, displayable, ExecutionPlan, SendableRecordBatchStream};
 use datafusion::prelude::*;
-use pyo3::exceptions::PyTypeError;
+use datafusion_common::cast::{as_float64_array, as_list_array};
+use datafusion_common::parsers::CompressionTypeVariant;
+use datafusion_common::{DFField, DataFusionError as InnerDataFusionError, ScalarValue};
+use datafusion_expr::type_coercion::binary::comparison_coercion;
+use datafusion_expr::{
+    approx_distinct, approx_median, avg, cast as cast_expr, count, create_udf,
+    expr::{GetIndexedField, Sort as SortExpr, WindowFunction},
+    max, min, stddev, sum, when, window_function, AggregateFunction, BuiltInWindowFunction,
+    ExprSchemable, LogicalPlan, Volatility, WindowFrame,
+};
+use futures::StreamExt;
+use pyo3::exceptions::{
+    PyKeyError, PyNotImplementedError, PyRuntimeError, PyTypeError, PyValueError,
+};
 use pyo3::prelude::*;
-use pyo3::types::PyTuple;
+use pyo3::types::{PyCapsule, PyDict, PyList, PyTuple};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use tokio::runtime::Handle;
 
 /// A PyDataFrame is a representation of a logical plan and an API to compose statements.
 /// Use it to build a plan and `.collect()` to execute the plan and collect the result.
@@ -36,51 +85,143 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub(crate) struct PyDataFrame {
     df: Arc<DataFrame>,
+    /// The number of rows and bytes held in memory, for a `DataFrame` returned by `cache`
+    cache_stats: Option<(usize, usize)>,
 }
 
 impl PyDataFrame {
     /// creates a new PyDataFrame
     pub fn new(df: DataFrame) -> Self {
-        Self { df: Arc::new(df) }
+        Self {
+            df: Arc::new(df),
+            cache_stats: None,
+        }
+    }
+
+    /// Project the named, possibly qualified, columns
+    fn select_names(&self, names: &[&str]) -> PyResult<Self> {
+        let expr = names
+            .iter()
+            .map(|name| column_expr(&self.df, name))
+            .collect::<PyResult<Vec<Expr>>>()?;
+        let df = self.df.as_ref().clone().select(expr)?;
+        Ok(Self::new(df))
+    }
+
+    /// Limit to the `num` rows `show` displays and one more, which tells whether rows
+    /// were left out
+    fn show_df(&self, num: usize) -> PyResult<DataFrame> {
+        Ok(self.df.as_ref().clone().limit(0, Some(num + 1))?)
     }
 }
 
 #[pymethods]
 impl PyDataFrame {
-    fn __getitem__(&self, key: PyObject) -> PyResult<Self> {
-        Python::with_gil(|py| {
-            if let Ok(key) = key.extract::<&str>(py) {
-                self.select_columns(vec![key])
-            } else if let Ok(tuple) = key.extract::<&PyTuple>(py) {
-                let keys = tuple
-                    .iter()
-                    .map(|item| item.extract::<&str>())
-                    .collect::<PyResult<Vec<&str>>>()?;
-                self.select_columns(keys)
-            } else if let Ok(keys) = key.extract::<Vec<&str>>(py) {
-                self.select_columns(keys)
-            } else {
-                let message = "DataFrame can only be indexed by string index or indices";
-                Err(PyTypeError::new_err(message))
-            }
-        })
+    /// `df["a"]` is an expression referring to column `a`, which may be qualified as in
+    /// `df["t.a"]`, and `df[["a", "b"]]` or `df["a", "b"]` selects those columns. Raises
+    /// a `KeyError` for a column that does not exist.
+    fn __getitem__(&self, py: Python, key: PyObject) -> PyResult<PyObject> {
+        if let Ok(key) = key.extract::<&str>(py) {
+            Ok(PyExpr::from(column_expr(&self.df, key)?).into_py(py))
+        } else if let Ok(tuple) = key.extract::<&PyTuple>(py) {
+            let keys = tuple
+                .iter()
+                .map(|item| item.extract::<&str>())
+                .collect::<PyResult<Vec<&str>>>()?;
+            Ok(self.select_names(&keys)?.into_py(py))
+        } else if let Ok(keys) = key.extract::<Vec<&str>>(py) {
+            Ok(self.select_names(&keys)?.into_py(py))
+        } else {
+            let message = "DataFrame can only be indexed by string index or indices";
+            Err(PyTypeError::new_err(message))
+        }
     }
 
     fn __repr__(&self, py: Python) -> PyResult<String> {
-        let df = self.df.as_ref().clone().limit(0, Some(10))?;
-        let batches = wait_for_future(py, df.collect())?;
-        let batches_as_string = pretty::pretty_format_batches(&batches);
-        match batches_as_string {
-            Ok(batch) => Ok(format!("DataFrame()\n{batch}")),
-            Err(err) => Ok(format!("Error: {:?}", err.to_string())),
-        }
+        let table = DisplayTable::collect(py, self.df.as_ref().clone())?;
+        Ok(format!("DataFrame()\n{}", table.to_ascii()?))
     }
 
-    /// Calculate summary statistics for a DataFrame
-    fn describe(&self, py: Python) -> PyResult<Self> {
-        let df = self.df.as_ref().clone();
-        let stat_df = wait_for_future(py, df.describe())?;
-        Ok(Self::new(stat_df))
+    /// Render the first rows and columns of the `DataFrame` as an HTML table, used by
+    /// Jupyter notebooks. The limits are set with `datafusion.set_display_options`.
+    fn _repr_html_(&self, py: Python) -> PyResult<String> {
+        let table = DisplayTable::collect(py, self.df.as_ref().clone())?;
+        table.to_html()
+    }
+
+    /// Summary statistics of every column, one row per statistic named in the
+    /// `statistic` column. Numeric columns are summarized as floats and other columns
+    /// as strings. A statistic that a column's type does not support is null. All the
+    /// statistics are computed by a single aggregation.
+    #[pyo3(signature = (statistics=None))]
+    fn describe(&self, statistics: Option<Vec<String>>, py: Python) -> PyResult<Self> {
+        let statistics = match statistics {
+            Some(statistics) => {
+                let mut seen = HashSet::new();
+                for statistic in &statistics {
+                    if !DESCRIBE_STATISTICS.contains(&statistic.as_str()) {
+                        return Err(PyValueError::new_err(format!(
+                            "Unknown statistic '{statistic}', expected one of {}",
+                            DESCRIBE_STATISTICS.join(", ")
+                        )));
+                    }
+                    if !seen.insert(statistic) {
+                        return Err(PyValueError::new_err(format!(
+                            "Statistic '{statistic}' is requested more than once"
+                        )));
+                    }
+                }
+                if statistics.is_empty() {
+                    return Err(PyValueError::new_err("At least one statistic is required"));
+                }
+                statistics
+            }
+            None => DESCRIBE_STATISTICS.iter().map(|s| s.to_string()).collect(),
+        };
+
+        let fields = self.df.schema().fields().clone();
+        let mut aggregates = vec![];
+        for (i, field) in fields.iter().enumerate() {
+            for statistic in &statistics {
+                let column = Expr::Column(field.qualified_column());
+                if let Some(aggregate) = describe_aggregate(statistic, column, field.data_type()) {
+                    aggregates.push(aggregate.alias(format!("{statistic}_{i}")));
+                }
+            }
+        }
+        let batches = if aggregates.is_empty() {
+            vec![]
+        } else {
+            let df = self.df.as_ref().clone().aggregate(vec![], aggregates)?;
+            wait_for_future(py, df.collect())?
+        };
+        let row = batches.iter().find(|batch| batch.num_rows() > 0);
+
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(StringArray::from(statistics.clone()))];
+        let mut output_fields = vec![Field::new("statistic", DataType::Utf8, false)];
+        for (i, field) in fields.iter().enumerate() {
+            let output_type = if field.data_type().is_numeric() {
+                DataType::Float64
+            } else {
+                DataType::Utf8
+            };
+            let values = statistics
+                .iter()
+                .map(|statistic| {
+                    match row.and_then(|row| row.column_by_name(&format!("{statistic}_{i}"))) {
+                        Some(value) => cast(value, &output_type),
+                        None => Ok(new_null_array(&output_type, 1)),
+                    }
+                })
+                .collect::<Result<Vec<_>, ArrowError>>()
+                .map_err(DataFusionError::from)?;
+            let values: Vec<&dyn Array> = values.iter().map(|value| value.as_ref()).collect();
+            columns.push(concat(&values).map_err(DataFusionError::from)?);
+            output_fields.push(Field::new(field.name(), output_type, true));
+        }
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(output_fields)), columns)
+            .map_err(DataFusionError::from)?;
+        Ok(Self::new(SessionContext::new().read_batch(batch)?))
     }
 
     /// Returns the schema from the logical plan
@@ -88,15 +229,29 @@ impl PyDataFrame {
         PyArrowType(self.df.schema().into())
     }
 
+    /// Returns the DataFusion schema of the logical plan, whose fields keep the
+    /// relation they come from, e.g. to tell apart the columns of a join
+    fn df_schema(&self) -> PyDFSchema {
+        self.df.schema().clone().into()
+    }
+
     #[pyo3(signature = (*args))]
     fn select_columns(&self, args: Vec<&str>) -> PyResult<Self> {
         let df = self.df.as_ref().clone().select_columns(&args)?;
         Ok(Self::new(df))
     }
 
+    /// Project the given expressions, where a string is taken as a column name, e.g.
+    /// `df.select("a", col("b") + lit(1))`
     #[pyo3(signature = (*args))]
-    fn select(&self, args: Vec<PyExpr>) -> PyResult<Self> {
-        let expr = args.into_iter().map(|e| e.into()).collect();
+    fn select(&self, args: Vec<ExprOrColumn>) -> PyResult<Self> {
+        let expr = args
+            .into_iter()
+            .map(|arg| match arg {
+                ExprOrColumn::Expr(expr) => Ok(expr.expr),
+                ExprOrColumn::Column(name) => column_expr(&self.df, &name),
+            })
+            .collect::<PyResult<Vec<Expr>>>()?;
         let df = self.df.as_ref().clone().select(expr)?;
         Ok(Self::new(df))
     }
@@ -106,11 +261,621 @@ impl PyDataFrame {
         Ok(Self::new(df))
     }
 
+    /// Filter with a SQL predicate such as `"x > 10 AND y IS NOT NULL"`, which may refer
+    /// to the columns of the `DataFrame` and call registered functions
+    fn filter_sql(&self, predicate: &str) -> PyResult<Self> {
+        let (state, plan) = self.df.as_ref().clone().into_parts();
+        let expr = sql_to_expr(&TaskContext::from(&state), plan.schema(), predicate)
+            .map_err(|e| py_parsing_exp(format!("Invalid filter predicate '{predicate}': {e}")))?;
+        let df = DataFrame::new(state, plan).filter(expr)?;
+        Ok(Self::new(df))
+    }
+
+    /// Return `func(df, *args, **kwargs)`, which must be a `DataFrame`, so that reusable
+    /// steps can be chained, e.g. `df.transform(add_features).transform(clean_nulls)`.
+    /// `func` may also be a list of callables, applied in order with the same extra
+    /// arguments. An exception raised by a step becomes the cause of a `RuntimeError`
+    /// naming that step.
+    #[pyo3(signature = (func, *args, **kwargs))]
+    fn transform(
+        &self,
+        py: Python,
+        func: &PyAny,
+        args: &PyTuple,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<Self> {
+        let steps: Vec<&PyAny> = match func.downcast::<PyList>() {
+            Ok(list) => list.iter().collect(),
+            Err(_) => vec![func],
+        };
+        if let Some(step) = steps.iter().find(|step| !step.is_callable()) {
+            return Err(PyTypeError::new_err(format!(
+                "Transform step {} is not callable",
+                callable_name(step)
+            )));
+        }
+        let mut df = self.clone();
+        for step in steps {
+            let name = callable_name(step);
+            let mut step_args = vec![df.into_py(py)];
+            step_args.extend(args.iter().map(|arg| arg.into_py(py)));
+            let result = step
+                .call(PyTuple::new(py, step_args), kwargs)
+                .map_err(|err| {
+                    let wrapped =
+                        PyRuntimeError::new_err(format!("Transform step {name} failed: {err}"));
+                    wrapped.set_cause(py, Some(err));
+                    wrapped
+                })?;
+            df = result.extract::<PyDataFrame>().map_err(|_| {
+                PyTypeError::new_err(format!(
+                    "Transform step {name} returned {}, expected a DataFrame",
+                    result.get_type().name().unwrap_or("<unknown>")
+                ))
+            })?;
+        }
+        Ok(df)
+    }
+
+    /// Add a column computed by `expr`, replacing any existing column called `name`
     fn with_column(&self, name: &str, expr: PyExpr) -> PyResult<Self> {
         let df = self.df.as_ref().clone().with_column(name, expr.into())?;
         Ok(Self::new(df))
     }
 
+    /// Add or replace several columns in a single projection, given as aliased
+    /// expressions, or lists of them, and as keywords, e.g.
+    /// `df.with_columns([(col("a") * lit(2)).alias("d")], b=col("c"))`. All expressions
+    /// are computed from the input columns, so one may not refer to a column assigned by
+    /// another.
+    #[pyo3(signature = (*exprs, **columns))]
+    fn with_columns(&self, exprs: &PyTuple, columns: Option<&PyDict>) -> PyResult<Self> {
+        let mut named: Vec<(String, Expr)> = vec![];
+        for item in exprs {
+            let items: Vec<PyExpr> = match item.downcast::<PyList>() {
+                Ok(list) => list.extract()?,
+                Err(_) => vec![item.extract()?],
+            };
+            for expr in items {
+                match expr.expr {
+                    Expr::Alias(expr, name) => named.push((name, *expr)),
+                    other => {
+                        return Err(DataFusionError::Common(format!(
+                            "Expression {other} passed to with_columns must be aliased to \
+                             name its column"
+                        ))
+                        .into())
+                    }
+                }
+            }
+        }
+        for (name, expr) in columns.into_iter().flatten() {
+            named.push((name.extract()?, expr.extract::<PyExpr>()?.into()));
+        }
+
+        let mut assigned: Vec<(String, Expr)> = vec![];
+        for (name, expr) in named {
+            if assigned.iter().any(|(other, _)| *other == name) {
+                return Err(DataFusionError::Common(format!(
+                    "Column {name} is assigned more than once in the same with_columns call"
+                ))
+                .into());
+            }
+            for column in expr.to_columns()? {
+                if assigned.iter().any(|(other, _)| *other == column.name) {
+                    return Err(DataFusionError::Common(format!(
+                        "Column {} is assigned in the same with_columns call as {name}, \
+                         which refers to it; use separate calls to chain them",
+                        column.name
+                    ))
+                    .into());
+                }
+            }
+            assigned.push((name, expr));
+        }
+
+        let schema = self.df.schema();
+        let names = field_names(&self.df);
+        let mut exprs: Vec<Expr> = schema
+            .fields()
+            .iter()
+            .map(
+                |field| match assigned.iter().find(|(name, _)| name == field.name()) {
+                    Some((name, expr)) => expr.clone().alias(name),
+                    None => Expr::Column(field.qualified_column()),
+                },
+            )
+            .collect();
+        for (name, expr) in &assigned {
+            if !names.contains(name) {
+                exprs.push(expr.clone().alias(name));
+            }
+        }
+        let df = self.df.as_ref().clone().select(exprs)?;
+        Ok(Self::new(df))
+    }
+
+    /// Expand the list column `column` into one row per element of its lists, repeating
+    /// the values of the other columns. With `preserve_nulls` a null list yields one
+    /// row with a null element, otherwise no row. Empty lists yield no rows.
+    #[pyo3(signature = (column, preserve_nulls=true))]
+    fn unnest_column(&self, column: &str, preserve_nulls: bool) -> PyResult<Self> {
+        self.unnest_columns(vec![column], preserve_nulls)
+    }
+
+    /// Like `unnest_column` for several list columns, whose lists are zipped: the lists
+    /// of a row must have the same length, and their n-th elements make up the n-th
+    /// row. A null list is zipped as null elements, and yields no rows without
+    /// `preserve_nulls` only if the other lists of its row are null as well.
+    #[pyo3(signature = (*columns, preserve_nulls=true))]
+    fn unnest_columns(&self, columns: Vec<&str>, preserve_nulls: bool) -> PyResult<Self> {
+        if columns.is_empty() {
+            return Err(PyValueError::new_err(
+                "At least one column to unnest is required",
+            ));
+        }
+        let schema = self.df.schema();
+        let mut fields = vec![];
+        for name in &columns {
+            let field = schema.field_with_unqualified_name(name)?;
+            match field.data_type() {
+                DataType::List(_) => {}
+                DataType::LargeList(_) | DataType::FixedSizeList(_, _) if columns.len() == 1 => {}
+                DataType::Struct(_) => {
+                    return Err(PyTypeError::new_err(format!(
+                        "Cannot unnest struct column {name}, flattening structs into columns \
+                         is not supported; select their fields with col(\"{name}\")[\"field\"]"
+                    )))
+                }
+                other => {
+                    return Err(PyTypeError::new_err(format!(
+                        "Cannot unnest column {name} of type {other}, only list columns can be \
+                         unnested, and only List columns together with others"
+                    )))
+                }
+            }
+            fields.push(field.clone());
+        }
+
+        let mut df = self.df.as_ref().clone();
+        if !preserve_nulls {
+            let predicate = fields
+                .iter()
+                .map(|field| Expr::Column(field.qualified_column()).is_not_null())
+                .reduce(Expr::or)
+                .unwrap();
+            df = df.filter(predicate)?;
+        }
+        if let [field] = fields.as_slice() {
+            return Ok(Self::new(df.unnest_column(field.name())?));
+        }
+
+        // the lists are zipped into a list of structs, whose fields are extracted again
+        // after unnesting it
+        let zipped = "__unnest_zipped";
+        let is_unnested = |field: &DFField| columns.contains(&field.name().as_str());
+        let args = fields
+            .iter()
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect();
+        let mut projection: Vec<Expr> = schema
+            .fields()
+            .iter()
+            .filter(|field| !is_unnested(field))
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect();
+        projection.push(zip_lists_udf(&fields).call(args).alias(zipped));
+        let df = df.select(projection)?.unnest_column(zipped)?;
+
+        let output = schema
+            .fields()
+            .iter()
+            .map(|field| match is_unnested(field) {
+                true => Expr::GetIndexedField(GetIndexedField::new(
+                    Box::new(col(zipped)),
+                    ScalarValue::Utf8(Some(field.name().clone())),
+                ))
+                .alias(field.name()),
+                false => Expr::Column(field.qualified_column()),
+            })
+            .collect();
+        Ok(Self::new(df.select(output)?))
+    }
+
+    /// Reshape from wide to long: for each row and each of `value_vars`, which default to
+    /// the columns not in `id_vars`, output the `id_vars`, the name of the value column as
+    /// `variable_name` (or `var_name`, its pandas spelling) and its value as `value_name`.
+    /// The value columns are cast to a common type. Null values are dropped unless
+    /// `include_nulls` is set.
+    #[pyo3(signature = (
+        id_vars,
+        value_vars=None,
+        var_name=None,
+        value_name="value",
+        include_nulls=true,
+        variable_name=None
+    ))]
+    fn unpivot(
+        &self,
+        id_vars: Vec<&str>,
+        value_vars: Option<Vec<&str>>,
+        var_name: Option<&str>,
+        value_name: &str,
+        include_nulls: bool,
+        variable_name: Option<&str>,
+    ) -> PyResult<Self> {
+        let var_name = match (var_name, variable_name) {
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "Pass either var_name or variable_name, not both",
+                ))
+            }
+            (Some(name), None) | (None, Some(name)) => name,
+            (None, None) => "variable",
+        };
+        let schema = self.df.schema();
+        let id_fields = id_vars
+            .iter()
+            .map(|name| schema.field_with_unqualified_name(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let value_fields = match value_vars {
+            Some(value_vars) => value_vars
+                .iter()
+                .map(|name| schema.field_with_unqualified_name(name))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => schema
+                .fields()
+                .iter()
+                .filter(|field| !id_vars.contains(&field.name().as_str()))
+                .collect(),
+        };
+        if value_fields.is_empty() {
+            return Err(PyValueError::new_err(
+                "At least one column to unpivot is required",
+            ));
+        }
+
+        let value_type = value_fields
+            .iter()
+            .try_fold(DataType::Null, |common, field| {
+                comparison_coercion(&common, field.data_type())
+            })
+            .ok_or_else(|| {
+                let columns: Vec<String> = value_fields
+                    .iter()
+                    .map(|field| format!("{} ({})", field.name(), field.data_type()))
+                    .collect();
+                PyTypeError::new_err(format!(
+                    "Cannot unpivot columns {}, their types have no common type",
+                    columns.join(", ")
+                ))
+            })?;
+
+        let mut df: Option<DataFrame> = None;
+        for field in &value_fields {
+            let mut projection: Vec<Expr> = id_fields
+                .iter()
+                .map(|field| Expr::Column(field.qualified_column()))
+                .collect();
+            projection.push(lit(field.name().as_str()).alias(var_name));
+            projection.push(
+                cast_expr(Expr::Column(field.qualified_column()), value_type.clone())
+                    .alias(value_name),
+            );
+            let mut projected = self.df.as_ref().clone();
+            if !include_nulls {
+                projected =
+                    projected.filter(Expr::Column(field.qualified_column()).is_not_null())?;
+            }
+            let projected = projected.select(projection)?;
+            df = Some(match df {
+                Some(df) => df.union(projected)?,
+                None => projected,
+            });
+        }
+        Ok(Self::new(df.unwrap()))
+    }
+
+    /// `unpivot` with the argument names and defaults of `pandas.melt`
+    #[pyo3(signature = (
+        id_vars=vec![],
+        value_vars=None,
+        var_name="variable",
+        value_name="value",
+        include_nulls=true
+    ))]
+    fn melt(
+        &self,
+        id_vars: Vec<&str>,
+        value_vars: Option<Vec<&str>>,
+        var_name: &str,
+        value_name: &str,
+        include_nulls: bool,
+    ) -> PyResult<Self> {
+        self.unpivot(
+            id_vars,
+            value_vars,
+            Some(var_name),
+            value_name,
+            include_nulls,
+            None,
+        )
+    }
+
+    /// Reshape from long to wide: group by the `index` columns and output a column for
+    /// each distinct value of the `columns` column, aggregating with `aggfunc` the values
+    /// of `values` in the rows with that value. `aggfunc` is one of `sum`, `mean`, `min`,
+    /// `max` or `count`. Finding the distinct values executes the `DataFrame`, and more
+    /// than `max_columns` of them raise a `ValueError`.
+    #[pyo3(signature = (index, columns, values, aggfunc="sum", max_columns=100))]
+    fn pivot(
+        &self,
+        index: Vec<&str>,
+        columns: &str,
+        values: &str,
+        aggfunc: &str,
+        max_columns: usize,
+        py: Python,
+    ) -> PyResult<Self> {
+        let schema = self.df.schema();
+        let group_by = index
+            .iter()
+            .map(|name| {
+                Ok(Expr::Column(
+                    schema.field_with_unqualified_name(name)?.qualified_column(),
+                ))
+            })
+            .collect::<Result<Vec<_>, InnerDataFusionError>>()?;
+        let pivot_column = Expr::Column(
+            schema
+                .field_with_unqualified_name(columns)?
+                .qualified_column(),
+        );
+        let value_column = Expr::Column(
+            schema
+                .field_with_unqualified_name(values)?
+                .qualified_column(),
+        );
+
+        let aggregate: fn(Expr) -> Expr = match aggfunc.to_lowercase().as_str() {
+            "sum" => sum,
+            "mean" | "avg" => avg,
+            "min" => min,
+            "max" => max,
+            "count" => count,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "Unsupported aggfunc '{other}', expected one of sum, mean, min, max or count"
+                )))
+            }
+        };
+
+        let keys = self
+            .df
+            .as_ref()
+            .clone()
+            .select(vec![pivot_column.clone()])?
+            .distinct()?;
+        let batches = wait_for_future(py, keys.collect())?;
+        let mut keys = vec![];
+        for batch in &batches {
+            for i in 0..batch.num_rows() {
+                keys.push(ScalarValue::try_from_array(batch.column(0), i)?);
+            }
+        }
+        if keys.len() > max_columns {
+            return Err(PyValueError::new_err(format!(
+                "Cannot pivot on {columns}, its {} distinct values would create more than \
+                 max_columns={max_columns} columns",
+                keys.len()
+            )));
+        }
+        keys.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let aggs = keys
+            .into_iter()
+            .map(|key| {
+                let (condition, name) = match key.is_null() {
+                    true => (pivot_column.clone().is_null(), "null".to_string()),
+                    false => (pivot_column.clone().eq(lit(key.clone())), key.to_string()),
+                };
+                let value = when(condition, value_column.clone()).end()?;
+                Ok(aggregate(value).alias(name))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let df = self.df.as_ref().clone().aggregate(group_by, aggs)?;
+        Ok(Self::new(df))
+    }
+
+    /// Replace the nulls of the `subset` columns, by default all of them, with `value`
+    /// cast to the type of each column. Columns `value` cannot be cast to are left
+    /// unchanged, or raise a `TypeError` if `strict` is set.
+    #[pyo3(signature = (value, subset=None, strict=false))]
+    fn fill_null(
+        &self,
+        value: PyScalarValue,
+        subset: Option<Vec<&str>>,
+        strict: bool,
+    ) -> PyResult<Self> {
+        let value = value.0;
+        let filled = subset_fields(&self.df, subset)?;
+        let mut exprs = vec![];
+        for field in self.df.schema().fields() {
+            let column = Expr::Column(field.qualified_column());
+            if !filled.contains(&field) {
+                exprs.push(column);
+                continue;
+            }
+            match cast_scalar(&value, field.data_type()) {
+                Ok(value) => exprs.push(coalesce(vec![column, lit(value)]).alias(field.name())),
+                Err(_) if strict => {
+                    return Err(PyTypeError::new_err(format!(
+                        "Cannot fill nulls of column {} of type {} with {value}",
+                        field.name(),
+                        field.data_type()
+                    )))
+                }
+                Err(_) => exprs.push(column),
+            }
+        }
+        Ok(Self::new(self.df.as_ref().clone().select(exprs)?))
+    }
+
+    /// Replace the NaN values of the float columns in `subset`, by default all of them,
+    /// with `value`. Nulls are left unchanged, see `fill_null`.
+    #[pyo3(signature = (value, subset=None))]
+    fn fill_nan(&self, value: f64, subset: Option<Vec<&str>>) -> PyResult<Self> {
+        let all_columns = subset.is_none();
+        let filled = subset_fields(&self.df, subset)?;
+        let mut exprs = vec![];
+        for field in self.df.schema().fields() {
+            let column = Expr::Column(field.qualified_column());
+            let is_float = matches!(
+                field.data_type(),
+                DataType::Float16 | DataType::Float32 | DataType::Float64
+            );
+            if !filled.contains(&field) || (all_columns && !is_float) {
+                exprs.push(column);
+                continue;
+            }
+            if !is_float {
+                return Err(PyTypeError::new_err(format!(
+                    "Cannot fill NaN values of column {} of type {}, only float columns \
+                     have them",
+                    field.name(),
+                    field.data_type()
+                )));
+            }
+            let value = cast_expr(lit(value), field.data_type().clone());
+            let is_nan = is_nan_udf().call(vec![cast_expr(column.clone(), DataType::Float64)]);
+            let filled = when(is_nan, value).otherwise(column)?;
+            exprs.push(filled.alias(field.name()));
+        }
+        Ok(Self::new(self.df.as_ref().clone().select(exprs)?))
+    }
+
+    /// Remove the rows with nulls in the `subset` columns, by default all of them: with
+    /// `how="any"` the rows with a null in any of those columns, with `how="all"` the
+    /// rows with nulls in all of them. NaN values are not nulls.
+    #[pyo3(signature = (subset=None, how="any"))]
+    fn drop_null(&self, subset: Option<Vec<&str>>, how: &str) -> PyResult<Self> {
+        let not_nulls = subset_fields(&self.df, subset)?
+            .into_iter()
+            .map(|field| Expr::Column(field.qualified_column()).is_not_null());
+        let predicate = match how {
+            "any" => not_nulls.reduce(Expr::and),
+            "all" => not_nulls.reduce(Expr::or),
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid how '{other}', expected 'any' or 'all'"
+                )))
+            }
+        };
+        match predicate {
+            Some(predicate) => Ok(Self::new(self.df.as_ref().clone().filter(predicate)?)),
+            None => Ok(self.clone()),
+        }
+    }
+
+    /// Count the rows with each distinct value of `column`, nulls included, as a
+    /// `DataFrame` of the values and their `count`, or with `normalize` their
+    /// `proportion` of all rows. With `sort` the most frequent values come first.
+    /// The counts are computed by an aggregation, so high-cardinality columns stream
+    /// like any other query. Nested columns raise a `TypeError`.
+    #[pyo3(signature = (column, sort=true, normalize=false))]
+    fn value_counts(&self, column: &str, sort: bool, normalize: bool) -> PyResult<Self> {
+        let df = self.df.as_ref().clone();
+        let value = column_expr(&df, column)?;
+        let data_type = value.get_type(df.schema())?;
+        if matches!(
+            data_type,
+            DataType::List(_)
+                | DataType::LargeList(_)
+                | DataType::FixedSizeList(_, _)
+                | DataType::Struct(_)
+                | DataType::Map(_, _)
+                | DataType::Union(_, _)
+        ) {
+            return Err(PyTypeError::new_err(format!(
+                "Cannot count the values of column {column} of nested type {data_type}, \
+                 select one of its fields or unnest it first"
+            )));
+        }
+        let name = if normalize { "proportion" } else { "count" };
+        if column == name {
+            return Err(PyValueError::new_err(format!(
+                "Cannot count the values of column {column}, it has the name of the result \
+                 column"
+            )));
+        }
+
+        let df = df.aggregate(vec![value.clone()], vec![count(lit(1u8)).alias("__count")])?;
+        let counts = if normalize {
+            let total = Expr::WindowFunction(WindowFunction::new(
+                window_function::WindowFunction::AggregateFunction(AggregateFunction::Sum),
+                vec![col("__count")],
+                vec![],
+                vec![],
+                WindowFrame::new(false),
+            ));
+            cast_expr(col("__count"), DataType::Float64) / cast_expr(total, DataType::Float64)
+        } else {
+            col("__count")
+        };
+        let df = df.select(vec![value.clone(), counts.alias(name)])?;
+        let df = if sort {
+            df.sort(vec![col(name).sort(false, false), value.sort(true, false)])?
+        } else {
+            df
+        };
+        Ok(Self::new(df))
+    }
+
+    /// The number of nulls in each column, as a `DataFrame` with a single row. All the
+    /// columns are counted by one aggregation.
+    fn null_count(&self) -> PyResult<Self> {
+        let df = self.df.as_ref().clone();
+        let fields = df.schema().fields().clone();
+        let mut aggregates = vec![count(lit(1u8)).alias("__rows")];
+        aggregates.extend(fields.iter().enumerate().map(|(i, field)| {
+            count(Expr::Column(field.qualified_column())).alias(format!("__values_{i}"))
+        }));
+        let df = df.aggregate(vec![], aggregates)?;
+        let null_counts = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (col("__rows") - col(format!("__values_{i}"))).alias(field.name()))
+            .collect();
+        Ok(Self::new(df.select(null_counts)?))
+    }
+
+    /// Remove the given columns. Raises a `KeyError` for a column that does not
+    /// exist, unless `ignore_missing` is set.
+    #[pyo3(signature = (*columns, ignore_missing=false))]
+    fn drop(&self, columns: Vec<&str>, ignore_missing: bool) -> PyResult<Self> {
+        let names = field_names(&self.df);
+        if !ignore_missing {
+            if let Some(missing) = columns
+                .iter()
+                .find(|name| !names.iter().any(|existing| existing == **name))
+            {
+                return Err(PyKeyError::new_err(format!(
+                    "Cannot drop column {missing}, it does not exist"
+                )));
+            }
+        }
+        let exprs = self
+            .df
+            .schema()
+            .fields()
+            .iter()
+            .filter(|field| !columns.contains(&field.name().as_str()))
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect();
+        let df = self.df.as_ref().clone().select(exprs)?;
+        Ok(Self::new(df))
+    }
+
     /// Rename one column by applying a new projection. This is a no-op if the column to be
     /// renamed does not exist.
     fn with_column_renamed(&self, old_name: &str, new_name: &str) -> PyResult<Self> {
@@ -122,39 +887,221 @@ impl PyDataFrame {
         Ok(Self::new(df))
     }
 
-    fn aggregate(&self, group_by: Vec<PyExpr>, aggs: Vec<PyExpr>) -> PyResult<Self> {
-        let group_by = group_by.into_iter().map(|e| e.into()).collect();
-        let aggs = aggs.into_iter().map(|e| e.into()).collect();
+    /// Group by the `group_by` keys, each a column name or an expression such as
+    /// `date_trunc(lit("day"), col("ts"))`, and compute the `aggs` aggregates for
+    /// every group. An empty `group_by` aggregates all rows into a single one.
+    /// Aggregates can be filtered afterwards, like SQL's `HAVING`, by calling
+    /// `filter` with an expression referring to their aliases.
+    fn aggregate(&self, group_by: Vec<ExprOrColumn>, aggs: Vec<PyExpr>) -> PyResult<Self> {
+        let group_by: Vec<Expr> = group_by.into_iter().map(|e| e.into()).collect();
+        let aggs: Vec<Expr> = aggs.into_iter().map(|e| e.into()).collect();
+
+        let mut names = HashSet::new();
+        for expr in group_by.iter().chain(&aggs) {
+            let name = expr.display_name()?;
+            if !names.insert(name.clone()) {
+                return Err(DataFusionError::from(InnerDataFusionError::Plan(format!(
+                    "Duplicate output column name {name} in aggregate, use alias to rename it"
+                )))
+                .into());
+            }
+        }
+
         let df = self.df.as_ref().clone().aggregate(group_by, aggs)?;
         Ok(Self::new(df))
     }
 
-    #[pyo3(signature = (*exprs))]
-    fn sort(&self, exprs: Vec<PyExpr>) -> PyResult<Self> {
-        let exprs = exprs.into_iter().map(|e| e.into()).collect();
+    /// Sort by any number of keys. Each key is either a sort expression such as
+    /// `col("a").sort(ascending=False)`, or a column name or plain expression whose
+    /// direction is given by the matching entry of `ascending` (ascending by default).
+    /// Unless `nulls_first` says otherwise, nulls sort last in ascending order and
+    /// first in descending order.
+    #[pyo3(signature = (*keys, ascending=None, nulls_first=None))]
+    fn sort(
+        &self,
+        keys: Vec<SortKey>,
+        ascending: Option<Vec<bool>>,
+        nulls_first: Option<Vec<bool>>,
+    ) -> PyResult<Self> {
+        for (name, values) in [("ascending", &ascending), ("nulls_first", &nulls_first)] {
+            if let Some(values) = values {
+                if values.len() != keys.len() {
+                    return Err(DataFusionError::Common(format!(
+                        "Expected {} values for {name} but got {}",
+                        keys.len(),
+                        values.len()
+                    ))
+                    .into());
+                }
+            }
+        }
+
+        let exprs = keys
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let expr = Expr::from(key);
+                let asc = ascending.as_ref().map(|values| values[i]);
+                let nulls_first = nulls_first.as_ref().map(|values| values[i]);
+                match expr {
+                    Expr::Sort(_) if asc.is_some() || nulls_first.is_some() => {
+                        Err(DataFusionError::Common(format!(
+                            "Cannot set the sort order of the sort expression {expr}"
+                        )))
+                    }
+                    Expr::Sort(_) => Ok(expr),
+                    expr => {
+                        let asc = asc.unwrap_or(true);
+                        Ok(expr.sort(asc, nulls_first.unwrap_or(!asc)))
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
         let df = self.df.as_ref().clone().sort(exprs)?;
         Ok(Self::new(df))
     }
 
-    fn limit(&self, count: usize) -> PyResult<Self> {
-        let df = self.df.as_ref().clone().limit(0, Some(count))?;
+    /// Return at most `count` rows, skipping the first `offset` rows. An offset past
+    /// the end of the data results in an empty `DataFrame`.
+    #[pyo3(signature = (count, offset=0))]
+    fn limit(&self, count: usize, offset: usize) -> PyResult<Self> {
+        let df = self.df.as_ref().clone().limit(offset, Some(count))?;
+        Ok(Self::new(df))
+    }
+
+    /// Return a random sample of about `fraction` of the rows, keeping each row with that
+    /// probability. With a `seed`, whether a row is kept is decided by hashing the seed
+    /// with the row's values, so the same seed selects the same rows whatever the order
+    /// or partitioning of the data, and identical rows are kept or dropped together.
+    #[pyo3(signature = (fraction, with_replacement=false, seed=None))]
+    fn sample(&self, fraction: f64, with_replacement: bool, seed: Option<u64>) -> PyResult<Self> {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(PyValueError::new_err(format!(
+                "The sample fraction must be between 0 and 1, got {fraction}"
+            )));
+        }
+        if with_replacement {
+            return Err(PyNotImplementedError::new_err(
+                "Sampling with replacement is not supported",
+            ));
+        }
+        let df = self.df.as_ref().clone();
+        let key = sample_key(&df, seed);
+        let df = df.filter(key.lt(lit(fraction)))?;
+        Ok(Self::new(df))
+    }
+
+    /// Return exactly `n` rows chosen at random, or all of them if there are fewer, by
+    /// sorting on a random key. That sorts every row, so `sample` is cheaper when an
+    /// approximate size will do. A `seed` selects the same rows as it does for `sample`.
+    #[pyo3(signature = (n, seed=None))]
+    fn sample_n(&self, n: usize, seed: Option<u64>) -> PyResult<Self> {
+        let df = self.df.as_ref().clone();
+        let key = sample_key(&df, seed);
+        let df = df.sort(vec![key.sort(true, false)])?.limit(0, Some(n))?;
+        Ok(Self::new(df))
+    }
+
+    /// Return the first `n` rows
+    #[pyo3(signature = (n=5))]
+    fn head(&self, n: usize) -> PyResult<Self> {
+        self.limit(n, 0)
+    }
+
+    /// Return the last `n` rows. If the `DataFrame` is sorted, the sort is reversed to find
+    /// the last rows without counting. Otherwise the rows are counted first and the result
+    /// follows the order in which the plan produces rows, which is only deterministic for
+    /// ordered sources.
+    #[pyo3(signature = (n=5))]
+    fn tail(&self, py: Python, n: usize) -> PyResult<Self> {
+        let df = self.df.as_ref().clone();
+        if let LogicalPlan::Sort(sort) = df.logical_plan() {
+            let sort_exprs = sort.expr.clone();
+            let reversed = sort_exprs
+                .iter()
+                .map(|expr| match expr {
+                    Expr::Sort(sort) => Ok(Expr::Sort(SortExpr::new(
+                        sort.expr.clone(),
+                        !sort.asc,
+                        !sort.nulls_first,
+                    ))),
+                    other => Err(DataFusionError::Common(format!(
+                        "Expected a sort expression, found {other}"
+                    ))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let df = df.sort(reversed)?.limit(0, Some(n))?.sort(sort_exprs)?;
+            return Ok(Self::new(df));
+        }
+
+        let count = wait_for_future(py, df.clone().count())?;
+        let df = df.limit(count.saturating_sub(n), Some(n))?;
         Ok(Self::new(df))
     }
 
     /// Executes the plan, returning a list of `RecordBatch`es.
     /// Unless some order is specified in the plan, there is no
     /// guarantee of the order of the result.
-    fn collect(&self, py: Python) -> PyResult<Vec<PyObject>> {
-        let batches = wait_for_future(py, self.df.as_ref().clone().collect())?;
+    /// If `timeout` seconds pass before the results are in, the query is cancelled and
+    /// a `TimeoutError` is raised.
+    #[pyo3(signature = (timeout=None))]
+    fn collect(&self, py: Python, timeout: Option<f64>) -> PyResult<Vec<PyObject>> {
+        let batches =
+            wait_for_future_with_timeout(py, self.df.as_ref().clone().collect(), timeout)??;
         // cannot use PyResult<Vec<RecordBatch>> return type due to
         // https://github.com/PyO3/pyo3/issues/1813
         batches.into_iter().map(|rb| rb.to_pyarrow(py)).collect()
     }
 
-    /// Cache DataFrame.
+    /// Executes the plan, returning a stream of `RecordBatch`es. If `timeout` is given,
+    /// the query is cancelled and `next` raises a `TimeoutError` once that many seconds
+    /// have passed since the call.
+    #[pyo3(signature = (timeout=None))]
+    fn execute_stream(&self, py: Python, timeout: Option<f64>) -> PyResult<PyRecordBatchStream> {
+        let df = self.df.as_ref().clone();
+        let stream = match timeout {
+            Some(timeout) => {
+                let deadline = deadline_after(timeout)?;
+                let stream = wait_for_future_until(py, df.execute_stream(), deadline)
+                    .ok_or_else(|| timeout_err(timeout))??;
+                PyRecordBatchStream::new(stream).with_deadline(deadline, timeout)
+            }
+            None => PyRecordBatchStream::new(wait_for_future(py, df.execute_stream())?),
+        };
+        Ok(stream)
+    }
+
+    /// Execute the plan once and keep the results in memory, with the same schema and
+    /// partitioning. The returned `DataFrame` scans the in-memory batches, so
+    /// operations on it do not run the original plan again.
     fn cache(&self, py: Python) -> PyResult<Self> {
         let df = wait_for_future(py, self.df.as_ref().clone().cache())?;
-        Ok(Self::new(df))
+        // reading the cached batches back only clones references to their buffers
+        let batches = wait_for_future(py, df.clone().collect())?;
+        let num_rows = batches.iter().map(|batch| batch.num_rows()).sum();
+        let size_bytes = batches
+            .iter()
+            .map(|batch| batch.get_array_memory_size())
+            .sum();
+        Ok(Self {
+            df: Arc::new(df),
+            cache_stats: Some((num_rows, size_bytes)),
+        })
+    }
+
+    /// The number of rows and the memory size of the batches held by a `DataFrame`
+    /// returned by `cache`, as `{"num_rows": ..., "size_bytes": ...}`, or `None`
+    /// for any other `DataFrame`
+    fn cache_stats(&self, py: Python) -> PyResult<PyObject> {
+        match self.cache_stats {
+            Some((num_rows, size_bytes)) => {
+                let stats = PyDict::new(py);
+                stats.set_item("num_rows", num_rows)?;
+                stats.set_item("size_bytes", size_bytes)?;
+                Ok(stats.into())
+            }
+            None => Ok(py.None()),
+        }
     }
 
     /// Executes this DataFrame and collects all results into a vector of vector of RecordBatch
@@ -168,11 +1115,43 @@ impl PyDataFrame {
             .collect()
     }
 
-    /// Print the result, 20 lines by default
+    /// Print the result, 20 lines by default. A `LIMIT` of one row past `num` is pushed
+    /// into the plan, so only the rows shown and one more are ever produced.
     #[pyo3(signature = (num=20))]
     fn show(&self, py: Python, num: usize) -> PyResult<()> {
-        let df = self.df.as_ref().clone().limit(0, Some(num))?;
-        print_dataframe(py, df)
+        let result = self.show_str(py, num)?;
+        let print = py.import("builtins")?.getattr("print")?;
+        print.call1((result,))?;
+        Ok(())
+    }
+
+    /// Return the table printed by `show` as a string. When rows are left out, the total
+    /// number of rows is only reported if the plan's statistics know it exactly.
+    #[pyo3(signature = (num=20))]
+    fn show_str(&self, py: Python, num: usize) -> PyResult<String> {
+        let df = self.show_df(num)?;
+        let schema: Schema = df.schema().into();
+        let batches = wait_for_future(py, df.collect())?;
+
+        let table = DisplayTable::try_new(schema, batches, num)?;
+        let mut result = format!("DataFrame()\n{}", table.to_ascii()?);
+        if table.more_rows {
+            let plan = wait_for_future(py, self.df.as_ref().clone().create_physical_plan())?;
+            let statistics = plan.statistics();
+            let footer = match statistics.num_rows.filter(|_| statistics.is_exact) {
+                Some(total_rows) => format!("showing {num} of {total_rows} rows"),
+                None => format!("showing the first {num} rows, more not shown"),
+            };
+            result.push_str(&format!("\n{footer}"));
+        }
+        Ok(result)
+    }
+
+    /// The execution plan that `show` and `show_str` run for `num` rows
+    #[pyo3(signature = (num=20))]
+    fn _show_plan(&self, py: Python, num: usize) -> PyResult<PyExecutionPlan> {
+        let plan = wait_for_future(py, self.show_df(num)?.create_physical_plan())?;
+        Ok(plan.into())
     }
 
     /// Filter out duplicate rows
@@ -181,50 +1160,330 @@ impl PyDataFrame {
         Ok(Self::new(df))
     }
 
+    /// Keep the first row of each group of rows with equal `on_exprs`, where first is
+    /// according to `sort_by`, and compute `select_exprs` for it, like PostgreSQL's
+    /// `SELECT DISTINCT ON (on_exprs) select_exprs ... ORDER BY sort_by`. Without
+    /// `sort_by`, which row of a group is kept is unspecified.
+    #[pyo3(signature = (on_exprs, select_exprs, sort_by=vec![]))]
+    fn distinct_on(
+        &self,
+        on_exprs: Vec<PyExpr>,
+        select_exprs: Vec<PyExpr>,
+        sort_by: Vec<SortKey>,
+    ) -> PyResult<Self> {
+        if on_exprs.is_empty() {
+            return Err(DataFusionError::Common(
+                "distinct_on requires at least one expression to deduplicate on".to_string(),
+            )
+            .into());
+        }
+        let sort_by: Vec<Expr> = sort_by.into_iter().map(SortKey::into_sort_expr).collect();
+        let row_number = Expr::WindowFunction(WindowFunction::new(
+            window_function::WindowFunction::BuiltInWindowFunction(
+                BuiltInWindowFunction::RowNumber,
+            ),
+            vec![],
+            on_exprs.into_iter().map(|e| e.into()).collect(),
+            sort_by.clone(),
+            WindowFrame::new(!sort_by.is_empty()),
+        ));
+
+        let df = self
+            .df
+            .as_ref()
+            .clone()
+            .with_column(DISTINCT_ON_ROW_NUMBER, row_number)?
+            .filter(col(DISTINCT_ON_ROW_NUMBER).eq(lit(1u64)))?
+            .select(select_exprs.into_iter().map(|e| e.into()).collect())?;
+        Ok(Self::new(df))
+    }
+
+    /// Join with another `DataFrame` on equality of the given keys, passed either as
+    /// `join_keys=(left_keys, right_keys)` or as `left_on` and `right_on`. Non-key columns
+    /// present on both sides are renamed with `suffixes`, or with `left_suffix` and
+    /// `right_suffix`, which default to `_l` and `_r`. A key with the same name on both
+    /// sides appears once in the result. Semi and anti joins only return the columns of
+    /// the left side.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (right,
+                        join_keys=None,
+                        how="inner",
+                        left_on=None,
+                        right_on=None,
+                        suffixes=None,
+                        left_suffix=None,
+                        right_suffix=None))]
     fn join(
         &self,
         right: PyDataFrame,
-        join_keys: (Vec<&str>, Vec<&str>),
+        join_keys: Option<(Vec<&str>, Vec<&str>)>,
         how: &str,
+        left_on: Option<Vec<&str>>,
+        right_on: Option<Vec<&str>>,
+        suffixes: Option<(&str, &str)>,
+        left_suffix: Option<&str>,
+        right_suffix: Option<&str>,
     ) -> PyResult<Self> {
-        let join_type = match how {
-            "inner" => JoinType::Inner,
-            "left" => JoinType::Left,
-            "right" => JoinType::Right,
-            "full" => JoinType::Full,
-            "semi" => JoinType::LeftSemi,
-            "anti" => JoinType::LeftAnti,
-            how => {
-                return Err(DataFusionError::Common(format!(
-                    "The join type {how} does not exist or is not implemented"
-                ))
+        let join_type = parse_join_type(how)?;
+        let suffixes = match (suffixes, left_suffix, right_suffix) {
+            (Some(suffixes), None, None) => suffixes,
+            (None, left_suffix, right_suffix) => {
+                (left_suffix.unwrap_or("_l"), right_suffix.unwrap_or("_r"))
+            }
+            _ => {
+                return Err(DataFusionError::Common(
+                    "Specify either suffixes or left_suffix and right_suffix".to_string(),
+                )
+                .into())
+            }
+        };
+
+        let (left_keys, right_keys) = match (join_keys, left_on, right_on) {
+            (Some(join_keys), None, None) => join_keys,
+            (None, Some(left_on), Some(right_on)) => (left_on, right_on),
+            _ => {
+                return Err(DataFusionError::Common(
+                    "Specify either join_keys or both left_on and right_on".to_string(),
+                )
+                .into())
+            }
+        };
+        if left_keys.len() != right_keys.len() {
+            return Err(DataFusionError::Common(format!(
+                "Expected the same number of left and right join keys but got {} and {}",
+                left_keys.len(),
+                right_keys.len()
+            ))
+            .into());
+        }
+
+        let mut left = self.df.as_ref().clone();
+        let mut right = right.df.as_ref().clone();
+
+        for (left_key, right_key) in left_keys.iter().zip(&right_keys) {
+            let left_type = left
+                .schema()
+                .field_with_unqualified_name(left_key)?
+                .data_type();
+            let right_type = right
+                .schema()
+                .field_with_unqualified_name(right_key)?
+                .data_type();
+            if comparison_coercion(left_type, right_type).is_none() {
+                return Err(DataFusionError::from(InnerDataFusionError::Plan(format!(
+                    "Join keys {left_key} ({left_type}) and {right_key} ({right_type}) \
+                     have incompatible types"
+                )))
                 .into());
             }
+        }
+
+        // Rename the columns present on both sides, leaving a column alone on the side
+        // where it is used as a join key
+        if !matches!(join_type, JoinType::LeftSemi | JoinType::LeftAnti) {
+            let right_names = field_names(&right);
+            let collisions: Vec<String> = field_names(&left)
+                .into_iter()
+                .filter(|name| right_names.contains(name))
+                .collect();
+            for name in collisions {
+                let is_left_key = left_keys.contains(&name.as_str());
+                let is_right_key = right_keys.contains(&name.as_str());
+                if is_left_key && is_right_key {
+                    continue;
+                }
+                if !is_left_key {
+                    left = left.with_column_renamed(&name, &format!("{name}{}", suffixes.0))?;
+                }
+                if !is_right_key {
+                    right = right.with_column_renamed(&name, &format!("{name}{}", suffixes.1))?;
+                }
+            }
+        }
+
+        let left_len = left.schema().fields().len();
+        let df = left.join(right, join_type, &left_keys, &right_keys, None)?;
+        if matches!(join_type, JoinType::LeftSemi | JoinType::LeftAnti) {
+            return Ok(Self::new(df));
+        }
+
+        // Keep a single column for each key with the same name on both sides, taking its
+        // value from the side whose rows are all kept
+        let shared_keys: Vec<&str> = left_keys
+            .iter()
+            .zip(&right_keys)
+            .filter(|(left_key, right_key)| left_key == right_key)
+            .map(|(key, _)| *key)
+            .collect();
+        if shared_keys.is_empty() {
+            return Ok(Self::new(df));
+        }
+        let (left_fields, right_fields) = df.schema().fields().split_at(left_len);
+        let right_key_column = |name: &str| {
+            right_fields
+                .iter()
+                .find(|field| field.name() == name)
+                .map(|field| Expr::Column(field.qualified_column()))
         };
+        let mut exprs = Vec::with_capacity(df.schema().fields().len() - shared_keys.len());
+        for field in left_fields {
+            let column = Expr::Column(field.qualified_column());
+            let right_column = if shared_keys.contains(&field.name().as_str()) {
+                right_key_column(field.name())
+            } else {
+                None
+            };
+            exprs.push(match (join_type, right_column) {
+                (JoinType::Right, Some(right_column)) => right_column,
+                (JoinType::Full, Some(right_column)) => {
+                    coalesce(vec![column, right_column]).alias(field.name())
+                }
+                _ => column,
+            });
+        }
+        exprs.extend(
+            right_fields
+                .iter()
+                .filter(|field| !shared_keys.contains(&field.name().as_str()))
+                .map(|field| Expr::Column(field.qualified_column())),
+        );
+        let df = df.select(exprs)?;
+        Ok(Self::new(df))
+    }
 
-        let df = self.df.as_ref().clone().join(
+    /// Join with another `DataFrame` on the conjunction of arbitrary boolean
+    /// expressions, which may include non-equality predicates. Columns present on
+    /// both sides can be told apart by qualifying them with the table name.
+    #[pyo3(signature = (right, *on_exprs, how="inner"))]
+    fn join_on(&self, right: PyDataFrame, on_exprs: Vec<PyExpr>, how: &str) -> PyResult<Self> {
+        if on_exprs.is_empty() {
+            return Err(DataFusionError::Common(
+                "join_on requires at least one join condition, use cross_join for a cartesian product"
+                    .to_string(),
+            )
+            .into());
+        }
+        let join_type = parse_join_type(how)?;
+        let on_exprs: Vec<Expr> = on_exprs.into_iter().map(|e| e.into()).collect();
+        let df =
+            self.df
+                .as_ref()
+                .clone()
+                .join_on(right.df.as_ref().clone(), join_type, on_exprs)?;
+        Ok(Self::new(df))
+    }
+
+    /// Cartesian product of this `DataFrame` with another one
+    fn cross_join(&self, right: PyDataFrame) -> PyResult<Self> {
+        let df = self.df.as_ref().clone().join_on(
             right.df.as_ref().clone(),
-            join_type,
-            &join_keys.0,
-            &join_keys.1,
-            None,
+            JoinType::Inner,
+            Vec::<Expr>::new(),
         )?;
         Ok(Self::new(df))
     }
 
-    /// Print the query plan
+    /// Print the logical and physical query plans. With `verbose` every intermediate
+    /// plan is shown, such as the logical plan after each optimizer rule that changed
+    /// it. With `analyze` the plan is executed and the physical plan is shown with the
+    /// metrics of each operator.
+    ///
+    /// With `format="stages"` the plans are returned instead as a list of
+    /// `(plan_type, plan)` pairs. With `format="dict"` the physical plan is returned
+    /// as nested dicts with the `name`, `description`, `metrics` and `children` of
+    /// each operator, where `metrics` is empty unless `analyze` is set and `verbose`
+    /// has no effect.
+    #[pyo3(signature = (verbose=false, analyze=false, format="text"))]
+    fn explain(
+        &self,
+        py: Python,
+        verbose: bool,
+        analyze: bool,
+        format: &str,
+    ) -> PyResult<PyObject> {
+        match format {
+            "text" => {
+                let text = self.explain_string(py, verbose, analyze)?;
+                py.import("builtins")?.getattr("print")?.call1((text,))?;
+                Ok(py.None())
+            }
+            "dict" => {
+                let (state, plan) = self.df.as_ref().clone().into_parts();
+                let task_ctx = Arc::new(TaskContext::from(&state));
+                let plan = wait_for_future(py, state.create_physical_plan(&plan))?;
+                if analyze {
+                    wait_for_future(py, collect(plan.clone(), task_ctx))?;
+                }
+                plan_to_dict(py, &plan)
+            }
+            "stages" => {
+                let df = self.df.as_ref().clone().explain(verbose, analyze)?;
+                let batches = wait_for_future(py, df.collect())?;
+                let mut stages: Vec<(String, String)> = vec![];
+                for batch in &batches {
+                    let column = |name: &str| {
+                        batch
+                            .column_by_name(name)
+                            .and_then(|array| array.as_any().downcast_ref::<StringArray>())
+                            .ok_or_else(|| {
+                                DataFusionError::Common(format!(
+                                    "Explain output has no {name} string column"
+                                ))
+                            })
+                    };
+                    let (plan_types, plans) = (column("plan_type")?, column("plan")?);
+                    for row in 0..batch.num_rows() {
+                        stages.push((
+                            plan_types.value(row).to_string(),
+                            plans.value(row).to_string(),
+                        ));
+                    }
+                }
+                Ok(stages.into_py(py))
+            }
+            other => Err(PyValueError::new_err(format!(
+                "Unknown explain format '{other}', expected 'text', 'stages' or 'dict'"
+            ))),
+        }
+    }
+
+    /// The text printed by `explain`
     #[pyo3(signature = (verbose=false, analyze=false))]
-    fn explain(&self, py: Python, verbose: bool, analyze: bool) -> PyResult<()> {
+    fn explain_string(&self, py: Python, verbose: bool, analyze: bool) -> PyResult<String> {
         let df = self.df.as_ref().clone().explain(verbose, analyze)?;
-        print_dataframe(py, df)
+        let batches = wait_for_future(py, df.collect())?;
+        Ok(pretty::pretty_format_batches(&batches)
+            .map_err(DataFusionError::from)?
+            .to_string())
+    }
+
+    /// Describe the logical plan, or the physical plan with `physical`, as a Graphviz
+    /// DOT graph with a node for each operator, labelled like `explain` shows it, e.g.
+    /// with the join type and keys or the filter predicate
+    #[pyo3(signature = (physical=false))]
+    fn plan_to_dot(&self, physical: bool, py: Python) -> PyResult<String> {
+        let root = match physical {
+            true => {
+                let plan = wait_for_future(py, self.df.as_ref().clone().create_physical_plan())?;
+                DotNode::from_physical(&plan)
+            }
+            false => DotNode::from_logical(self.df.logical_plan()),
+        };
+        let mut lines = vec![
+            "digraph plan {".to_string(),
+            "  node [shape=box];".to_string(),
+        ];
+        root.write(&mut lines, &mut 0);
+        lines.push("}".to_string());
+        Ok(lines.join("\n"))
     }
 
-    /// Get the logical plan for this `DataFrame`
+    /// Get the unoptimized logical plan for this `DataFrame`, as it was built
     fn logical_plan(&self) -> PyResult<PyLogicalPlan> {
-        Ok(self.df.as_ref().clone().logical_plan().clone().into())
+        Ok(self.df.logical_plan().clone().into())
     }
 
-    /// Get the optimized logical plan for this `DataFrame`
+    /// Get the logical plan for this `DataFrame` after the optimizer rules have run
     fn optimized_logical_plan(&self) -> PyResult<PyLogicalPlan> {
         Ok(self.df.as_ref().clone().into_optimized_plan()?.into())
     }
@@ -235,6 +1494,54 @@ impl PyDataFrame {
         Ok(plan.into())
     }
 
+    /// Get the statistics the execution plan knows without running it, such as those
+    /// read from Parquet metadata, as `{"num_rows": ..., "total_byte_size": ...,
+    /// "is_exact": ..., "columns": {name: {"min": ..., "max": ..., "null_count": ...,
+    /// "distinct_count": ...}}}`. Statistics that are not available are `None`.
+    /// DataFusion tracks exactness for the plan as a whole, so `is_exact` tells whether
+    /// all the known statistics are exact or only estimates.
+    fn statistics(&self, py: Python) -> PyResult<PyObject> {
+        let plan = wait_for_future(py, self.df.as_ref().clone().create_physical_plan())?;
+        let statistics = plan.statistics();
+        let schema = plan.schema();
+
+        let scalar_to_py = |value: Option<&ScalarValue>| -> PyResult<PyObject> {
+            match value {
+                Some(value) => value.to_pyarrow(py)?.call_method0(py, "as_py"),
+                None => Ok(py.None()),
+            }
+        };
+        let columns = PyDict::new(py);
+        for (i, field) in schema.fields().iter().enumerate() {
+            let column_statistics = statistics
+                .column_statistics
+                .as_ref()
+                .and_then(|columns| columns.get(i));
+            let column = PyDict::new(py);
+            column.set_item(
+                "min",
+                scalar_to_py(column_statistics.and_then(|c| c.min_value.as_ref()))?,
+            )?;
+            column.set_item(
+                "max",
+                scalar_to_py(column_statistics.and_then(|c| c.max_value.as_ref()))?,
+            )?;
+            column.set_item("null_count", column_statistics.and_then(|c| c.null_count))?;
+            column.set_item(
+                "distinct_count",
+                column_statistics.and_then(|c| c.distinct_count),
+            )?;
+            columns.set_item(field.name(), column)?;
+        }
+
+        let result = PyDict::new(py);
+        result.set_item("num_rows", statistics.num_rows)?;
+        result.set_item("total_byte_size", statistics.total_byte_size)?;
+        result.set_item("is_exact", statistics.is_exact)?;
+        result.set_item("columns", columns)?;
+        Ok(result.into())
+    }
+
     /// Repartition a `DataFrame` based on a logical partitioning scheme.
     fn repartition(&self, num: usize) -> PyResult<Self> {
         let new_df = self
@@ -245,10 +1552,29 @@ impl PyDataFrame {
         Ok(Self::new(new_df))
     }
 
-    /// Repartition a `DataFrame` based on a logical partitioning scheme.
+    /// Repartition a `DataFrame` based on a logical partitioning scheme. The
+    /// expressions must have types that can be hashed, which excludes nested types.
     #[pyo3(signature = (*args, num))]
     fn repartition_by_hash(&self, args: Vec<PyExpr>, num: usize) -> PyResult<Self> {
-        let expr = args.into_iter().map(|py_expr| py_expr.into()).collect();
+        let expr: Vec<Expr> = args.into_iter().map(|py_expr| py_expr.into()).collect();
+        for e in &expr {
+            let data_type = e.get_type(self.df.schema())?;
+            let nested = matches!(
+                data_type,
+                DataType::List(_)
+                    | DataType::LargeList(_)
+                    | DataType::FixedSizeList(_, _)
+                    | DataType::Struct(_)
+                    | DataType::Map(_, _)
+                    | DataType::Union(_, _)
+            );
+            if nested {
+                return Err(DataFusionError::from(InnerDataFusionError::Plan(format!(
+                    "Cannot repartition by hash of {e}, its type {data_type} cannot be hashed"
+                )))
+                .into());
+            }
+        }
         let new_df = self
             .df
             .as_ref()
@@ -258,34 +1584,63 @@ impl PyDataFrame {
     }
 
     /// Calculate the union of two `DataFrame`s, preserving duplicate rows.The
-    /// two `DataFrame`s must have exactly the same schema
-    #[pyo3(signature = (py_df, distinct=false))]
-    fn union(&self, py_df: PyDataFrame, distinct: bool) -> PyResult<Self> {
+    /// two `DataFrame`s must have exactly the same schema, unless `by_name` is set,
+    /// in which case the columns of `py_df` are matched by name and may be in any order.
+    #[pyo3(signature = (py_df, distinct=false, by_name=false))]
+    fn union(&self, py_df: PyDataFrame, distinct: bool, by_name: bool) -> PyResult<Self> {
+        let other = if by_name {
+            select_by_name(py_df.df.as_ref().clone(), &field_names(&self.df))?
+        } else {
+            py_df.df.as_ref().clone()
+        };
+        check_set_operation_schemas("union", &self.df, &other)?;
         let new_df = if distinct {
-            self.df
-                .as_ref()
-                .clone()
-                .union_distinct(py_df.df.as_ref().clone())?
+            self.df.as_ref().clone().union_distinct(other)?
         } else {
-            self.df.as_ref().clone().union(py_df.df.as_ref().clone())?
+            self.df.as_ref().clone().union(other)?
         };
 
         Ok(Self::new(new_df))
     }
 
+    /// Calculate the union of two `DataFrame`s whose columns are matched by name: the
+    /// result has the columns of this `DataFrame` followed by those only `py_df` has.
+    /// With `fill_missing` a column missing from either side is null on that side,
+    /// otherwise it is an error. Columns of the same name must have compatible types.
+    #[pyo3(signature = (py_df, fill_missing=true, distinct=false))]
+    fn union_by_name(
+        &self,
+        py_df: PyDataFrame,
+        fill_missing: bool,
+        distinct: bool,
+    ) -> PyResult<Self> {
+        let mut names = field_names(&self.df);
+        for name in field_names(&py_df.df) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        let left = align_by_name(&self.df, &py_df.df, &names, fill_missing)?;
+        let right = align_by_name(&py_df.df, &self.df, &names, fill_missing)?;
+        check_set_operation_schemas("union", &left, &right)?;
+        let new_df = if distinct {
+            left.union_distinct(right)?
+        } else {
+            left.union(right)?
+        };
+        Ok(Self::new(new_df))
+    }
+
     /// Calculate the distinct union of two `DataFrame`s.  The
     /// two `DataFrame`s must have exactly the same schema
-    fn union_distinct(&self, py_df: PyDataFrame) -> PyResult<Self> {
-        let new_df = self
-            .df
-            .as_ref()
-            .clone()
-            .union_distinct(py_df.df.as_ref().clone())?;
-        Ok(Self::new(new_df))
+    #[pyo3(signature = (py_df, by_name=false))]
+    fn union_distinct(&self, py_df: PyDataFrame, by_name: bool) -> PyResult<Self> {
+        self.union(py_df, true, by_name)
     }
 
     /// Calculate the intersection of two `DataFrame`s.  The two `DataFrame`s must have exactly the same schema
     fn intersect(&self, py_df: PyDataFrame) -> PyResult<Self> {
+        check_set_operation_schemas("intersect", &self.df, &py_df.df)?;
         let new_df = self
             .df
             .as_ref()
@@ -296,32 +1651,160 @@ impl PyDataFrame {
 
     /// Calculate the exception of two `DataFrame`s.  The two `DataFrame`s must have exactly the same schema
     fn except_all(&self, py_df: PyDataFrame) -> PyResult<Self> {
+        check_set_operation_schemas("except", &self.df, &py_df.df)?;
         let new_df = self.df.as_ref().clone().except(py_df.df.as_ref().clone())?;
         Ok(Self::new(new_df))
     }
 
-    /// Write a `DataFrame` to a CSV file.
-    fn write_csv(&self, path: &str, py: Python) -> PyResult<()> {
-        wait_for_future(py, self.df.as_ref().clone().write_csv(path))?;
+    /// Write a `DataFrame` to CSV, as a `part-{i}.csv` file per partition in the
+    /// directory `path`, or as the single file `path` with `single_file`. Nulls are
+    /// written as `null_value`, and dates and timestamps with `date_format` and
+    /// `timestamp_format`, chrono format strings, if given. `partition_by` splits the
+    /// files into Hive-style directories as for `write_parquet`.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (path,
+                        with_header=true,
+                        delimiter=",",
+                        quote="\"",
+                        null_value="",
+                        date_format=None,
+                        timestamp_format=None,
+                        single_file=false,
+                        partition_by=vec![]))]
+    fn write_csv(
+        &self,
+        path: &str,
+        with_header: bool,
+        delimiter: &str,
+        quote: &str,
+        null_value: &str,
+        date_format: Option<String>,
+        timestamp_format: Option<String>,
+        single_file: bool,
+        partition_by: Vec<String>,
+        py: Python,
+    ) -> PyResult<()> {
+        let single_char = |name: &str, value: &str| {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(PyValueError::new_err(format!(
+                    "{name} must be a single character"
+                ))),
+            }
+        };
+        let format = CsvWriter {
+            with_header,
+            delimiter: single_char("Delimiter", delimiter)?,
+            quote: single_char("Quote", quote)?,
+            null_value: null_value.to_string(),
+            date_format,
+            timestamp_format,
+        };
+        let df = self.df.as_ref().clone();
+        wait_for_future(
+            py,
+            write_files(df, path, single_file, &partition_by, &format),
+        )?;
         Ok(())
     }
 
-    /// Write a `DataFrame` to a Parquet file.
-    fn write_parquet(&self, path: &str, py: Python) -> PyResult<()> {
-        wait_for_future(py, self.df.as_ref().clone().write_parquet(path, None))?;
+    /// Write a `DataFrame` to Parquet, as a `part-{i}.parquet` file per partition in the
+    /// directory `path`, or as the single file `path` with `single_file`. `path` may be
+    /// the URL of a registered object store. `compression_level` applies to the gzip,
+    /// brotli and zstd codecs, and `statistics_enabled` is one of `none`, `chunk` or
+    /// `page`. Row groups end after `row_group_size` rows, or once their batches take
+    /// `max_row_group_bytes` of memory. With `partition_by`, each file is further split
+    /// into Hive-style directories such as `year=2023/month=07` by the values of those
+    /// columns, which are left out of the files.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (path,
+                        compression="uncompressed",
+                        compression_level=None,
+                        row_group_size=None,
+                        data_page_size=None,
+                        statistics_enabled="page",
+                        max_row_group_bytes=None,
+                        single_file=false,
+                        partition_by=vec![]))]
+    fn write_parquet(
+        &self,
+        path: &str,
+        compression: &str,
+        compression_level: Option<i32>,
+        row_group_size: Option<usize>,
+        data_page_size: Option<usize>,
+        statistics_enabled: &str,
+        max_row_group_bytes: Option<usize>,
+        single_file: bool,
+        partition_by: Vec<String>,
+        py: Python,
+    ) -> PyResult<()> {
+        let mut properties = WriterProperties::builder()
+            .set_compression(parquet_compression(compression, compression_level)?)
+            .set_statistics_enabled(match statistics_enabled.to_lowercase().as_str() {
+                "none" => EnabledStatistics::None,
+                "chunk" => EnabledStatistics::Chunk,
+                "page" => EnabledStatistics::Page,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "Invalid statistics_enabled '{other}', expected 'none', 'chunk' or 'page'"
+                    )))
+                }
+            });
+        if let Some(row_group_size) = row_group_size {
+            properties = properties.set_max_row_group_size(row_group_size);
+        }
+        if let Some(data_page_size) = data_page_size {
+            properties = properties.set_data_pagesize_limit(data_page_size);
+        }
+        let format = ParquetWriter {
+            properties: properties.build(),
+            max_row_group_bytes,
+        };
+        let df = self.df.as_ref().clone();
+        wait_for_future(
+            py,
+            write_files(df, path, single_file, &partition_by, &format),
+        )?;
         Ok(())
     }
 
-    /// Executes a query and writes the results to a partitioned JSON file.
-    fn write_json(&self, path: &str, py: Python) -> PyResult<()> {
-        wait_for_future(py, self.df.as_ref().clone().write_json(path))?;
+    /// Write a `DataFrame` to newline-delimited JSON, as a `part-{i}.json` file per
+    /// partition in the directory `path`, or as the single file `path` with
+    /// `single_file`. `compression` is one of `gzip`, `bzip2`, `xz` or `zstd`.
+    /// `partition_by` splits the files into Hive-style directories as for
+    /// `write_parquet`.
+    #[pyo3(signature = (path, compression=None, single_file=false, partition_by=vec![]))]
+    fn write_json(
+        &self,
+        path: &str,
+        compression: Option<&str>,
+        single_file: bool,
+        partition_by: Vec<String>,
+        py: Python,
+    ) -> PyResult<()> {
+        let compression = match compression {
+            Some(name) => CompressionTypeVariant::from_str(name).map_err(|_| {
+                PyValueError::new_err(format!(
+                    "Unsupported compression '{name}', expected one of gzip, bzip2, xz or zstd"
+                ))
+            })?,
+            None => CompressionTypeVariant::UNCOMPRESSED,
+        };
+        let format = JsonWriter { compression };
+        let df = self.df.as_ref().clone();
+        wait_for_future(
+            py,
+            write_files(df, path, single_file, &partition_by, &format),
+        )?;
         Ok(())
     }
 
     /// Convert to Arrow Table
     /// Collect the batches and pass to Arrow Table
     fn to_arrow_table(&self, py: Python) -> PyResult<PyObject> {
-        let batches = self.collect(py)?.to_object(py);
+        let batches = self.collect(py, None)?.to_object(py);
         let schema: PyObject = self.schema().into_py(py);
 
         Python::with_gil(|py| {
@@ -333,6 +1816,37 @@ impl PyDataFrame {
         })
     }
 
+    /// Export this `DataFrame` through the Arrow PyCapsule stream interface, so that any
+    /// Arrow-native consumer can read it. The query is executed lazily as batches are
+    /// pulled from the stream. If `requested_schema` is given, columns are selected by
+    /// name and cast to the requested types.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<&PyCapsule>,
+    ) -> PyResult<&'py PyCapsule> {
+        let stream = wait_for_future(py, self.df.as_ref().clone().execute_stream())?;
+        let schema = match requested_schema {
+            Some(capsule) => {
+                let ffi_schema = ffi_schema_from_capsule(capsule)?;
+                let requested = Schema::try_from(ffi_schema).map_err(DataFusionError::from)?;
+                validate_requested_schema(stream.schema().as_ref(), &requested)?;
+                Arc::new(requested)
+            }
+            None => stream.schema(),
+        };
+
+        let reader = DataFrameStreamReader {
+            stream,
+            schema,
+            runtime: get_tokio_runtime(py).0.handle().clone(),
+        };
+        let ffi_stream = ArrowArrayStreamCapsule(FFI_ArrowArrayStream::new(Box::new(reader)));
+        let name = CString::new("arrow_array_stream").unwrap();
+        PyCapsule::new(py, ffi_stream, Some(name))
+    }
+
     /// Convert to pandas dataframe with pyarrow
     /// Collect the batches, pass to Arrow Table & then convert to Pandas DataFrame
     fn to_pandas(&self, py: Python) -> PyResult<PyObject> {
@@ -346,7 +1860,8 @@ impl PyDataFrame {
     }
 
     /// Convert to Python list using pyarrow
-    /// Each list item represents one row encoded as dictionary
+    /// Each list item represents one row encoded as dictionary. Values are converted
+    /// to native Python objects the same way as `Expr.python_value`
     fn to_pylist(&self, py: Python) -> PyResult<PyObject> {
         let table = self.to_arrow_table(py)?;
 
@@ -358,7 +1873,8 @@ impl PyDataFrame {
     }
 
     /// Convert to Python dictionary using pyarrow
-    /// Each dictionary key is a column and the dictionary value represents the column values
+    /// Each dictionary key is a column and the dictionary value represents the column values.
+    /// Values are converted to native Python objects the same way as `Expr.python_value`
     fn to_pydict(&self, py: Python) -> PyResult<PyObject> {
         let table = self.to_arrow_table(py)?;
 
@@ -382,25 +1898,762 @@ impl PyDataFrame {
         })
     }
 
-    // Executes this DataFrame to get the total number of rows.
-    fn count(&self, py: Python) -> PyResult<usize> {
-        Ok(wait_for_future(py, self.df.as_ref().clone().count())?)
+    // Executes this DataFrame to get the total number of rows, or of distinct rows.
+    #[pyo3(signature = (distinct=false))]
+    fn count(&self, distinct: bool, py: Python) -> PyResult<usize> {
+        let df = self.df.as_ref().clone();
+        let df = if distinct { df.distinct()? } else { df };
+        Ok(wait_for_future(py, df.count())?)
+    }
+
+    /// Whether the DataFrame has no rows. Execution stops at the first row found.
+    fn is_empty(&self, py: Python) -> PyResult<bool> {
+        let df = self.df.as_ref().clone().limit(0, Some(1))?;
+        let batches = wait_for_future(py, df.collect())?;
+        Ok(batches.iter().all(|batch| batch.num_rows() == 0))
     }
 }
 
-/// Print DataFrame
-fn print_dataframe(py: Python, df: DataFrame) -> PyResult<()> {
-    // Get string representation of record batches
-    let batches = wait_for_future(py, df.collect())?;
-    let batches_as_string = pretty::pretty_format_batches(&batches);
-    let result = match batches_as_string {
-        Ok(batch) => format!("DataFrame()\n{batch}"),
-        Err(err) => format!("Error: {:?}", err.to_string()),
+fn parse_join_type(how: &str) -> Result<JoinType, DataFusionError> {
+    match how {
+        "inner" => Ok(JoinType::Inner),
+        "left" => Ok(JoinType::Left),
+        "right" => Ok(JoinType::Right),
+        "full" => Ok(JoinType::Full),
+        "semi" => Ok(JoinType::LeftSemi),
+        "anti" => Ok(JoinType::LeftAnti),
+        how => Err(DataFusionError::Common(format!(
+            "The join type {how} does not exist or is not implemented"
+        ))),
+    }
+}
+
+/// Column holding the position of each row within its group in `distinct_on`
+const DISTINCT_ON_ROW_NUMBER: &str = "__distinct_on_row_number";
+
+/// Selects the columns of `df` named by `names`, in that order
+fn select_by_name(df: DataFrame, names: &[String]) -> Result<DataFrame, DataFusionError> {
+    let available = field_names(&df);
+    if let Some(missing) = names.iter().find(|name| !available.contains(name)) {
+        return Err(DataFusionError::Common(format!(
+            "Cannot union by name, column {missing} is missing from [{}]",
+            available.join(", ")
+        )));
+    }
+    Ok(df.select(names.iter().map(|name| col(name.as_str())).collect())?)
+}
+
+/// Selects the columns `names` of `df` in order. With `fill_missing` a column `df`
+/// lacks is a null of the type it has in `other`, otherwise it is an error.
+fn align_by_name(
+    df: &DataFrame,
+    other: &DataFrame,
+    names: &[String],
+    fill_missing: bool,
+) -> Result<DataFrame, DataFusionError> {
+    let find = |df: &DataFrame, name: &str| {
+        df.schema()
+            .fields()
+            .iter()
+            .find(|field| field.name() == name)
+            .cloned()
     };
+    let exprs = names
+        .iter()
+        .map(|name| match (find(df, name), find(other, name)) {
+            (Some(field), _) => Ok(Expr::Column(field.qualified_column())),
+            (None, Some(field)) if fill_missing => {
+                Ok(cast_expr(lit(ScalarValue::Null), field.data_type().clone()).alias(name))
+            }
+            _ => Err(DataFusionError::Common(format!(
+                "Cannot union by name, column {name} is missing from [{}]",
+                field_names(df).join(", ")
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(df.clone().select(exprs)?)
+}
+
+/// Checks that the results of `left` and `right` can be combined by the set operation
+/// `op`: they must have as many columns, of compatible types
+fn check_set_operation_schemas(
+    op: &str,
+    left: &DataFrame,
+    right: &DataFrame,
+) -> Result<(), DataFusionError> {
+    let left_fields = left.schema().fields();
+    let right_fields = right.schema().fields();
+    let compatible = left_fields.len() == right_fields.len()
+        && left_fields
+            .iter()
+            .zip(right_fields)
+            .all(|(l, r)| comparison_coercion(l.data_type(), r.data_type()).is_some());
+    if compatible {
+        return Ok(());
+    }
+    let describe = |df: &DataFrame| {
+        df.schema()
+            .fields()
+            .iter()
+            .map(|field| format!("{}: {}", field.name(), field.data_type()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Err(DataFusionError::from(InnerDataFusionError::Plan(format!(
+        "Cannot {op} DataFrames with incompatible schemas [{}] and [{}]",
+        describe(left),
+        describe(right)
+    ))))
+}
+
+/// Describes an operator of a physical plan and its children as nested dicts. The
+/// metrics of an operator are summed over its partitions.
+fn plan_to_dict(py: Python, plan: &Arc<dyn ExecutionPlan>) -> PyResult<PyObject> {
+    let description = displayable(plan.as_ref()).one_line().to_string();
+    let description = description.trim_end();
+    let name = description
+        .split_once(':')
+        .map_or(description, |(name, _)| name);
+
+    let metrics = PyDict::new(py);
+    if let Some(metrics_set) = plan.metrics() {
+        for metric in metrics_set.aggregate_by_name().iter() {
+            metrics.set_item(metric.value().name(), metric.value().as_usize())?;
+        }
+    }
+    let children = plan
+        .children()
+        .iter()
+        .map(|child| plan_to_dict(py, child))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let result = PyDict::new(py);
+    result.set_item("name", name)?;
+    result.set_item("description", description)?;
+    result.set_item("metrics", metrics)?;
+    result.set_item("children", children)?;
+    Ok(result.into())
+}
+
+/// An operator of a plan and its inputs, to be drawn by `plan_to_dot`
+struct DotNode {
+    label: String,
+    children: Vec<DotNode>,
+}
+
+impl DotNode {
+    fn from_logical(plan: &LogicalPlan) -> Self {
+        DotNode {
+            label: plan.display().to_string(),
+            children: plan.inputs().into_iter().map(Self::from_logical).collect(),
+        }
+    }
+
+    fn from_physical(plan: &Arc<dyn ExecutionPlan>) -> Self {
+        DotNode {
+            label: displayable(plan.as_ref()).one_line().to_string(),
+            children: plan.children().iter().map(Self::from_physical).collect(),
+        }
+    }
 
-    // Import the Python 'builtins' module to access the print function
-    // Note that println! does not print to the Python debug console and is not visible in notebooks for instance
-    let print = py.import("builtins")?.getattr("print")?;
-    print.call1((result,))?;
+    /// Appends the statements declaring this node, numbered from `next_id`, its inputs
+    /// and the edges to them, returning the id of this node
+    fn write(&self, lines: &mut Vec<String>, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let label = self
+            .label
+            .trim_end()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        lines.push(format!("  node{id} [label=\"{label}\"];"));
+        for child in &self.children {
+            let child_id = child.write(lines, next_id);
+            lines.push(format!("  node{id} -> node{child_id};"));
+        }
+        id
+    }
+}
+
+/// A UDF zipping the `List` columns `fields` into a list of structs with a field named
+/// after each column. The lists of a row must have the same length, except that null
+/// lists are zipped as null elements.
+fn zip_lists_udf(fields: &[DFField]) -> ScalarUDF {
+    let struct_fields: Vec<Field> = fields
+        .iter()
+        .map(|field| match field.data_type() {
+            DataType::List(item) => Field::new(field.name(), item.data_type().clone(), true),
+            other => unreachable!("only List columns are zipped, got {other}"),
+        })
+        .collect();
+    let struct_type = DataType::Struct(struct_fields.into());
+    let return_type = DataType::List(Arc::new(Field::new("item", struct_type.clone(), true)));
+    let names: Vec<String> = fields.iter().map(|field| field.name().clone()).collect();
+
+    let zip = move |args: &[ArrayRef]| -> datafusion_common::Result<ArrayRef> {
+        let lists = args
+            .iter()
+            .map(|arg| as_list_array(arg))
+            .collect::<datafusion_common::Result<Vec<_>>>()?;
+        let num_rows = lists.first().map_or(0, |list| list.len());
+        let mut offsets = vec![0i32];
+        let mut validity = Vec::with_capacity(num_rows);
+        let mut indices: Vec<Vec<Option<u32>>> = vec![vec![]; lists.len()];
+        for row in 0..num_rows {
+            let mut length: Option<(usize, i32)> = None;
+            for (k, list) in lists.iter().enumerate() {
+                if list.is_null(row) {
+                    continue;
+                }
+                match length {
+                    Some((first, len)) if len != list.value_length(row) => {
+                        return Err(InnerDataFusionError::Execution(format!(
+                            "Cannot unnest columns {} and {} together, their lists in row \
+                             {row} have lengths {len} and {}",
+                            names[first],
+                            names[k],
+                            list.value_length(row)
+                        )))
+                    }
+                    Some(_) => {}
+                    None => length = Some((k, list.value_length(row))),
+                }
+            }
+            let len = length.map_or(0, |(_, len)| len);
+            for (k, list) in lists.iter().enumerate() {
+                let start = list.value_offsets()[row];
+                indices[k].extend((0..len).map(|i| match list.is_null(row) {
+                    true => None,
+                    false => Some((start + i) as u32),
+                }));
+            }
+            offsets.push(offsets[row] + len);
+            validity.push(length.is_some());
+        }
+
+        let children = lists
+            .iter()
+            .zip(indices)
+            .map(|(list, indices)| {
+                Ok(take(list.values().as_ref(), &UInt32Array::from(indices), None)?.to_data())
+            })
+            .collect::<datafusion_common::Result<Vec<_>>>()?;
+        let values = ArrayData::builder(struct_type.clone())
+            .len(*offsets.last().unwrap() as usize)
+            .child_data(children)
+            .build()?;
+        let item = Field::new("item", struct_type.clone(), true);
+        let zipped = ArrayData::builder(DataType::List(Arc::new(item)))
+            .len(num_rows)
+            .add_buffer(Buffer::from_slice_ref(&offsets))
+            .add_child_data(values)
+            .null_bit_buffer(Some(validity.into_iter().collect()))
+            .build()?;
+        Ok(make_array(zipped))
+    };
+
+    create_udf(
+        "unnest_zip",
+        fields
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect(),
+        Arc::new(return_type),
+        Volatility::Immutable,
+        make_scalar_function(zip),
+    )
+}
+
+/// A random number in [0, 1) for each row of `df`, from `random()` or, given a `seed`,
+/// from a hash of the seed and the row's values
+fn sample_key(df: &DataFrame, seed: Option<u64>) -> Expr {
+    match seed {
+        None => random(),
+        Some(seed) => {
+            let fields = df.schema().fields();
+            let args = fields
+                .iter()
+                .map(|field| Expr::Column(field.qualified_column()))
+                .collect();
+            seeded_random_udf(fields, seed).call(args)
+        }
+    }
+}
+
+/// A function of all the columns `fields` of a row that returns a number in [0, 1)
+/// derived from hashing `seed` with the row's values as `ArrayFormatter` displays them
+fn seeded_random_udf(fields: &[DFField], seed: u64) -> ScalarUDF {
+    let hash_rows = move |args: &[ArrayRef]| -> datafusion_common::Result<ArrayRef> {
+        let options = FormatOptions::default();
+        let formatters = args
+            .iter()
+            .map(|array| ArrayFormatter::try_new(array.as_ref(), &options))
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_rows = args.first().map_or(0, |array| array.len());
+        let keys: Float64Array = (0..num_rows)
+            .map(|row| {
+                let mut hasher = DefaultHasher::new();
+                seed.hash(&mut hasher);
+                for (array, formatter) in args.iter().zip(&formatters) {
+                    array.is_null(row).hash(&mut hasher);
+                    if array.is_valid(row) {
+                        formatter.value(row).to_string().hash(&mut hasher);
+                    }
+                }
+                // the top 53 bits, as many as a f64 represents exactly
+                Some((hasher.finish() >> 11) as f64 / (1u64 << 53) as f64)
+            })
+            .collect();
+        Ok(Arc::new(keys))
+    };
+
+    create_udf(
+        "seeded_random",
+        fields
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect(),
+        Arc::new(DataType::Float64),
+        Volatility::Immutable,
+        make_scalar_function(hash_rows),
+    )
+}
+
+/// A function of a float column that tells whether each value is NaN, null for nulls
+fn is_nan_udf() -> ScalarUDF {
+    let is_nan = |args: &[ArrayRef]| -> datafusion_common::Result<ArrayRef> {
+        let values: BooleanArray = as_float64_array(&args[0])?
+            .iter()
+            .map(|value| value.map(f64::is_nan))
+            .collect();
+        Ok(Arc::new(values))
+    };
+
+    create_udf(
+        "is_nan",
+        vec![DataType::Float64],
+        Arc::new(DataType::Boolean),
+        Volatility::Immutable,
+        make_scalar_function(is_nan),
+    )
+}
+
+/// The fields of the `subset` columns of `df`, or all of its fields
+fn subset_fields<'a>(df: &'a DataFrame, subset: Option<Vec<&str>>) -> PyResult<Vec<&'a DFField>> {
+    let schema = df.schema();
+    match subset {
+        Some(subset) => subset
+            .into_iter()
+            .map(|name| schema.field_with_unqualified_name(name))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| DataFusionError::from(e).into()),
+        None => Ok(schema.fields().iter().collect()),
+    }
+}
+
+/// A reference to the column of `df` called `name`, which is first looked up as a
+/// qualified name such as `t.a`, and then as a column name containing a dot. Raises a
+/// `KeyError` with DataFusion's schema error if there is no such column.
+fn column_expr(df: &DataFrame, name: &str) -> PyResult<Expr> {
+    let schema = df.schema();
+    let index = schema
+        .index_of_column(&Column::from_qualified_name(name))
+        .or_else(|err| {
+            schema
+                .index_of_column(&Column::from_name(name))
+                .map_err(|_| err)
+        })
+        .map_err(|err| PyKeyError::new_err(err.to_string()))?;
+    Ok(Expr::Column(schema.field(index).qualified_column()))
+}
+
+/// The Parquet compression codecs that `write_parquet` accepts
+const PARQUET_COMPRESSIONS: [&str; 7] = [
+    "uncompressed",
+    "snappy",
+    "gzip",
+    "brotli",
+    "lz4",
+    "lz4_raw",
+    "zstd",
+];
+
+/// Parses a Parquet compression codec name, and its level for the codecs that have one
+fn parquet_compression(name: &str, level: Option<i32>) -> PyResult<Compression> {
+    let invalid_level = |e: ParquetError| PyValueError::new_err(e.to_string());
+    let unsigned_level = |level: i32| {
+        u32::try_from(level).map_err(|_| {
+            PyValueError::new_err(format!("Invalid compression level {level} for {name}"))
+        })
+    };
+    let compression = match (name.to_lowercase().as_str(), level) {
+        ("uncompressed", None) => Compression::UNCOMPRESSED,
+        ("snappy", None) => Compression::SNAPPY,
+        ("lz4", None) => Compression::LZ4,
+        ("lz4_raw", None) => Compression::LZ4_RAW,
+        ("gzip", level) => Compression::GZIP(match level {
+            Some(level) => GzipLevel::try_new(unsigned_level(level)?).map_err(invalid_level)?,
+            None => GzipLevel::default(),
+        }),
+        ("brotli", level) => Compression::BROTLI(match level {
+            Some(level) => BrotliLevel::try_new(unsigned_level(level)?).map_err(invalid_level)?,
+            None => BrotliLevel::default(),
+        }),
+        ("zstd", level) => Compression::ZSTD(match level {
+            Some(level) => ZstdLevel::try_new(level).map_err(invalid_level)?,
+            None => ZstdLevel::default(),
+        }),
+        (codec, Some(_)) if PARQUET_COMPRESSIONS.contains(&codec) => {
+            return Err(PyValueError::new_err(format!(
+                "Compression {codec} does not take a compression level"
+            )))
+        }
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported compression '{name}', expected one of {}",
+                PARQUET_COMPRESSIONS.join(", ")
+            )))
+        }
+    };
+    Ok(compression)
+}
+
+/// The statistics computed by `describe`, in the order of its rows
+const DESCRIBE_STATISTICS: [&str; 8] = [
+    "count",
+    "null_count",
+    "mean",
+    "std",
+    "min",
+    "max",
+    "median",
+    "distinct",
+];
+
+/// The aggregate computing `statistic` for a column, or `None` if its type does not
+/// support the statistic. Medians and distinct counts are approximate.
+fn describe_aggregate(statistic: &str, column: Expr, data_type: &DataType) -> Option<Expr> {
+    let numeric = data_type.is_numeric();
+    let decimal = matches!(
+        data_type,
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _)
+    );
+    let string = matches!(data_type, DataType::Utf8 | DataType::LargeUtf8);
+    let ordered = numeric
+        || string
+        || matches!(
+            data_type,
+            DataType::Boolean
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Time32(_)
+                | DataType::Time64(_)
+                | DataType::Timestamp(_, _)
+        );
+    match statistic {
+        "count" => Some(count(column)),
+        "null_count" => Some(sum(cast_expr(column.is_null(), DataType::Int64))),
+        "mean" if numeric => Some(avg(column)),
+        "std" if numeric && !decimal => Some(stddev(column)),
+        "median" if numeric && !decimal => Some(approx_median(column)),
+        "min" if ordered => Some(min(column)),
+        "max" if ordered => Some(max(column)),
+        "distinct" if string => Some(approx_distinct(column)),
+        _ => None,
+    }
+}
+
+/// The unqualified names of the columns of `df`
+fn field_names(df: &DataFrame) -> Vec<String> {
+    df.schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect()
+}
+
+/// The name of a transform step for error messages, its qualified name when it has one
+fn callable_name(func: &PyAny) -> String {
+    ["__qualname__", "__name__"]
+        .iter()
+        .find_map(|attr| func.getattr(*attr).and_then(|name| name.extract()).ok())
+        .unwrap_or_else(|| func.to_string())
+}
+
+/// A key passed to `DataFrame.select` or `DataFrame.aggregate`,
+/// either an expression or a column name
+#[derive(FromPyObject)]
+#[allow(clippy::large_enum_variant)]
+enum ExprOrColumn {
+    Expr(PyExpr),
+    Column(String),
+}
+
+impl From<ExprOrColumn> for Expr {
+    fn from(key: ExprOrColumn) -> Expr {
+        match key {
+            ExprOrColumn::Expr(expr) => expr.expr,
+            ExprOrColumn::Column(name) => col(name.as_str()),
+        }
+    }
+}
+
+/// Checks that every requested field exists in `schema` and can be cast to the requested type
+fn validate_requested_schema(schema: &Schema, requested: &Schema) -> PyResult<()> {
+    for field in requested.fields() {
+        let source = schema
+            .field_with_name(field.name())
+            .map_err(DataFusionError::from)?;
+        if !can_cast_types(source.data_type(), field.data_type()) {
+            return Err(DataFusionError::Common(format!(
+                "Cannot cast column {} from {} to the requested type {}",
+                field.name(),
+                source.data_type(),
+                field.data_type()
+            ))
+            .into());
+        }
+    }
     Ok(())
 }
+
+/// `FFI_ArrowArrayStream` holds raw pointers and is therefore not `Send`, which `PyCapsule`
+/// requires. The stream is only ever handed to a single consumer through the capsule.
+#[repr(transparent)]
+struct ArrowArrayStreamCapsule(FFI_ArrowArrayStream);
+
+unsafe impl Send for ArrowArrayStreamCapsule {}
+
+/// Adapts a `SendableRecordBatchStream` to a blocking `RecordBatchReader`, driving the
+/// execution on the Tokio runtime as batches are requested.
+struct DataFrameStreamReader {
+    stream: SendableRecordBatchStream,
+    schema: SchemaRef,
+    runtime: Handle,
+}
+
+impl Iterator for DataFrameStreamReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.runtime.block_on(self.stream.next())?;
+        Some(
+            batch
+                .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+                .and_then(|batch| cast_batch(&batch, &self.schema)),
+        )
+    }
+}
+
+impl RecordBatchReader for DataFrameStreamReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Selects and casts the columns of `batch` to match `schema`
+fn cast_batch(batch: &RecordBatch, schema: &SchemaRef) -> Result<RecordBatch, ArrowError> {
+    if batch.schema() == *schema {
+        return Ok(batch.clone());
+    }
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let column = batch.column_by_name(field.name()).ok_or_else(|| {
+                ArrowError::SchemaError(format!("Column {} not found", field.name()))
+            })?;
+            cast(column, field.data_type())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+/// Maximum number of rows rendered by `DataFrame.__repr__` and `DataFrame._repr_html_`
+static MAX_DISPLAY_ROWS: AtomicUsize = AtomicUsize::new(10);
+/// Maximum number of columns rendered by `DataFrame.__repr__` and `DataFrame._repr_html_`
+static MAX_DISPLAY_COLUMNS: AtomicUsize = AtomicUsize::new(20);
+/// Rendered cells longer than this are abbreviated
+const MAX_CELL_WIDTH: usize = 32;
+
+/// Set the maximum number of rows and columns rendered when displaying a `DataFrame`
+#[pyfunction]
+#[pyo3(signature = (max_rows=None, max_columns=None))]
+pub(crate) fn set_display_options(max_rows: Option<usize>, max_columns: Option<usize>) {
+    if let Some(max_rows) = max_rows {
+        MAX_DISPLAY_ROWS.store(max_rows, Ordering::Relaxed);
+    }
+    if let Some(max_columns) = max_columns {
+        MAX_DISPLAY_COLUMNS.store(max_columns, Ordering::Relaxed);
+    }
+}
+
+/// Get the maximum number of rows and columns rendered when displaying a `DataFrame`
+#[pyfunction]
+pub(crate) fn get_display_options() -> HashMap<&'static str, usize> {
+    HashMap::from([
+        ("max_rows", MAX_DISPLAY_ROWS.load(Ordering::Relaxed)),
+        ("max_columns", MAX_DISPLAY_COLUMNS.load(Ordering::Relaxed)),
+    ])
+}
+
+/// The head of a `DataFrame` as rendered for display, along with whether rows or
+/// columns were left out.
+struct DisplayTable {
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    more_rows: bool,
+    more_columns: bool,
+}
+
+impl DisplayTable {
+    /// Collects the rows and columns to display, pushing a `LIMIT` into the plan so
+    /// that only one row past the display limit is ever produced.
+    fn collect(py: Python, df: DataFrame) -> PyResult<Self> {
+        let max_rows = MAX_DISPLAY_ROWS.load(Ordering::Relaxed);
+        let df = df.limit(0, Some(max_rows + 1))?;
+        let schema: Schema = df.schema().into();
+        let batches = wait_for_future(py, df.collect())?;
+        Ok(Self::try_new(schema, batches, max_rows)?)
+    }
+
+    /// Keeps the first `max_rows` rows of `batches` and the first columns up to the
+    /// configured maximum number of columns.
+    fn try_new(
+        schema: Schema,
+        batches: Vec<RecordBatch>,
+        max_rows: usize,
+    ) -> Result<Self, DataFusionError> {
+        let max_columns = MAX_DISPLAY_COLUMNS.load(Ordering::Relaxed);
+        let more_columns = schema.fields().len() > max_columns;
+        let projection: Vec<usize> = (0..schema.fields().len().min(max_columns)).collect();
+
+        let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        let mut remaining = max_rows;
+        let batches = batches
+            .iter()
+            .map(|batch| {
+                let batch = batch.slice(0, remaining.min(batch.num_rows()));
+                remaining -= batch.num_rows();
+                batch.project(&projection)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            schema: Arc::new(schema.project(&projection)?),
+            batches,
+            more_rows: num_rows > max_rows,
+            more_columns,
+        })
+    }
+
+    fn header(&self) -> Vec<String> {
+        let mut header: Vec<String> = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect();
+        if self.more_columns {
+            header.push("...".to_string());
+        }
+        header
+    }
+
+    /// Formats every displayed cell, abbreviating long values such as nested or binary data
+    fn rows(&self) -> PyResult<Vec<Vec<String>>> {
+        let options = FormatOptions::default();
+        let mut rows = vec![];
+        for batch in &self.batches {
+            let formatters = batch
+                .columns()
+                .iter()
+                .map(|column| ArrayFormatter::try_new(column.as_ref(), &options))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(DataFusionError::from)?;
+            for row in 0..batch.num_rows() {
+                let mut cells: Vec<String> = formatters
+                    .iter()
+                    .map(|formatter| abbreviate(formatter.value(row).to_string()))
+                    .collect();
+                if self.more_columns {
+                    cells.push("...".to_string());
+                }
+                rows.push(cells);
+            }
+        }
+        if self.more_rows {
+            rows.push(vec!["...".to_string(); self.header().len()]);
+        }
+        Ok(rows)
+    }
+
+    fn to_ascii(&self) -> PyResult<String> {
+        let header = self.header();
+        let rows = self.rows()?;
+        let widths: Vec<usize> = (0..header.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(header[i].chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let separator = widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .collect::<Vec<_>>()
+            .join("+");
+        let separator = format!("+{separator}+");
+        let format_row = |row: &[String]| {
+            let cells = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!(" {cell:<width$} "))
+                .collect::<Vec<_>>()
+                .join("|");
+            format!("|{cells}|")
+        };
+
+        let mut lines = vec![separator.clone(), format_row(&header), separator.clone()];
+        lines.extend(rows.iter().map(|row| format_row(row)));
+        lines.push(separator);
+        Ok(lines.join("\n"))
+    }
+
+    fn to_html(&self) -> PyResult<String> {
+        let mut html = String::from("<table border='1'>\n<tr>");
+        for name in self.header() {
+            html.push_str(&format!("<th>{}</th>", escape_html(&name)));
+        }
+        html.push_str("</tr>\n");
+        for row in self.rows()? {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", escape_html(&cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        Ok(html)
+    }
+}
+
+fn abbreviate(value: String) -> String {
+    if value.chars().count() <= MAX_CELL_WIDTH {
+        return value;
+    }
+    let prefix: String = value.chars().take(MAX_CELL_WIDTH - 3).collect();
+    format!("{prefix}...")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,260 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reads the transaction log of a Delta Lake table to find the Parquet files making
+//! up a snapshot, so that they can be registered as a listing table.
+
+use std::collections::BTreeSet;
+
+use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use datafusion_common::DataFusionError;
+use futures::TryStreamExt;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use serde_json::Value;
+
+type Result<T> = std::result::Result<T, DataFusionError>;
+
+/// The state of a Delta table at one version of its log
+#[derive(Debug)]
+pub struct DeltaSnapshot {
+    pub version: u64,
+    /// Paths of the active data files, relative to the table root unless absolute
+    pub files: Vec<String>,
+    /// The table schema, if every column has a primitive type
+    pub schema: Option<Schema>,
+}
+
+/// Which version of a Delta table to read
+#[derive(Debug, Clone, Copy)]
+pub enum DeltaVersion {
+    Latest,
+    Version(u64),
+    /// The last version committed at or before a time in milliseconds since the epoch
+    AsOfTimestamp(i64),
+}
+
+/// Replays the JSON commits in the `_delta_log` directory under `table_path` to find
+/// the data files of the requested version of the table.
+pub async fn load_snapshot(
+    store: &dyn ObjectStore,
+    table_path: &Path,
+    version: DeltaVersion,
+) -> Result<DeltaSnapshot> {
+    let log_path = table_path.child("_delta_log");
+    let mut commits: Vec<(u64, Path, i64)> = store
+        .list(Some(&log_path))
+        .await?
+        .try_filter_map(|meta| async move {
+            let version = meta
+                .location
+                .filename()
+                .and_then(|name| name.strip_suffix(".json"))
+                .filter(|stem| stem.len() == 20)
+                .and_then(|stem| stem.parse::<u64>().ok());
+            let last_modified = meta.last_modified.timestamp_millis();
+            Ok(version.map(|version| (version, meta.location, last_modified)))
+        })
+        .try_collect()
+        .await?;
+    commits.sort();
+
+    if commits.is_empty() {
+        return Err(DataFusionError::Plan(format!(
+            "No Delta transaction log found at {log_path}"
+        )));
+    }
+    for (expected, (version, _, _)) in commits.iter().enumerate() {
+        if *version != expected as u64 {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Delta table at {table_path} has no JSON commit for version {expected}, \
+                 it was probably removed by log cleanup after a checkpoint and reading \
+                 checkpoints is not supported"
+            )));
+        }
+    }
+    let latest = commits.len() as u64 - 1;
+    if let DeltaVersion::Version(requested) = version {
+        if requested > latest {
+            return Err(DataFusionError::Plan(format!(
+                "Delta table at {table_path} has no version {requested}, \
+                 the latest version is {latest}"
+            )));
+        }
+    }
+
+    let mut snapshot_version = None;
+    let mut files = BTreeSet::new();
+    let mut schema = None;
+    for (commit_version, location, last_modified) in &commits {
+        if matches!(version, DeltaVersion::Version(requested) if *commit_version > requested) {
+            break;
+        }
+        let bytes = store.get(location).await?.bytes().await?;
+        let actions = bytes
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .map(|line| {
+                serde_json::from_slice::<Value>(line).map_err(|e| {
+                    DataFusionError::Execution(format!(
+                        "Invalid action in Delta commit {commit_version} of {table_path}: {e}"
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if let DeltaVersion::AsOfTimestamp(as_of) = version {
+            // commits record when they were made, older writers leave the file's time
+            let timestamp = actions
+                .iter()
+                .find_map(|action| action.pointer("/commitInfo/timestamp"))
+                .and_then(Value::as_i64)
+                .unwrap_or(*last_modified);
+            if timestamp > as_of {
+                break;
+            }
+        }
+
+        for action in &actions {
+            if let Some(protocol) = action.get("protocol") {
+                check_protocol(protocol, table_path)?;
+            }
+            if action
+                .pointer("/add/deletionVector")
+                .map_or(false, |dv| !dv.is_null())
+            {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Delta table at {table_path} has deletion vectors, which are not supported"
+                )));
+            }
+            if let Some(path) = action.pointer("/add/path").and_then(Value::as_str) {
+                files.insert(decode_path(path)?);
+            } else if let Some(path) = action.pointer("/remove/path").and_then(Value::as_str) {
+                files.remove(&decode_path(path)?);
+            } else if let Some(metadata) = action.get("metaData") {
+                let partitioned = metadata
+                    .get("partitionColumns")
+                    .and_then(Value::as_array)
+                    .map_or(false, |columns| !columns.is_empty());
+                if partitioned {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Partitioned Delta tables are not supported, {table_path} is partitioned"
+                    )));
+                }
+                schema = metadata
+                    .get("schemaString")
+                    .and_then(Value::as_str)
+                    .and_then(|schema| serde_json::from_str::<Value>(schema).ok())
+                    .and_then(|schema| convert_schema(&schema));
+            }
+        }
+        snapshot_version = Some(*commit_version);
+    }
+
+    let version = snapshot_version.ok_or_else(|| {
+        DataFusionError::Plan(format!(
+            "Delta table at {table_path} has no version committed at or before the \
+             requested timestamp"
+        ))
+    })?;
+    Ok(DeltaSnapshot {
+        version,
+        files: files.into_iter().collect(),
+        schema,
+    })
+}
+
+/// Only tables readable by a reader of protocol version 1 are supported, newer versions
+/// add features such as column mapping that change how the data files must be read
+fn check_protocol(protocol: &Value, table_path: &Path) -> Result<()> {
+    let reader_version = protocol
+        .get("minReaderVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    if reader_version > 1 {
+        return Err(DataFusionError::NotImplemented(format!(
+            "Delta table at {table_path} requires reader version {reader_version}, \
+             only version 1 is supported"
+        )));
+    }
+    let features = protocol
+        .get("readerFeatures")
+        .and_then(Value::as_array)
+        .filter(|features| !features.is_empty());
+    if let Some(features) = features {
+        return Err(DataFusionError::NotImplemented(format!(
+            "Delta table at {table_path} requires the reader features {}, which are not \
+             supported",
+            Value::Array(features.clone())
+        )));
+    }
+    Ok(())
+}
+
+/// Paths in the log are percent-encoded URIs
+fn decode_path(path: &str) -> Result<String> {
+    if path.contains("://") {
+        return Ok(path.to_string());
+    }
+    Path::from_url_path(path)
+        .map(|path| path.to_string())
+        .map_err(|e| DataFusionError::Execution(format!("Invalid path {path} in Delta log: {e}")))
+}
+
+/// Converts a Delta schema to Arrow, returning `None` if it has nested or unknown types
+/// so that the schema is inferred from the data files instead.
+fn convert_schema(schema: &Value) -> Option<Schema> {
+    let fields = schema
+        .get("fields")?
+        .as_array()?
+        .iter()
+        .map(|field| {
+            let name = field.get("name")?.as_str()?;
+            let nullable = field
+                .get("nullable")
+                .and_then(Value::as_bool)
+                .unwrap_or(true);
+            let data_type = convert_type(field.get("type")?.as_str()?)?;
+            Some(Field::new(name, data_type, nullable))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Schema::new(fields))
+}
+
+fn convert_type(delta_type: &str) -> Option<DataType> {
+    Some(match delta_type {
+        "string" => DataType::Utf8,
+        "long" => DataType::Int64,
+        "integer" => DataType::Int32,
+        "short" => DataType::Int16,
+        "byte" => DataType::Int8,
+        "float" => DataType::Float32,
+        "double" => DataType::Float64,
+        "boolean" => DataType::Boolean,
+        "binary" => DataType::Binary,
+        "date" => DataType::Date32,
+        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        "timestamp_ntz" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        _ => {
+            let (precision, scale) = delta_type
+                .strip_prefix("decimal(")?
+                .strip_suffix(')')?
+                .split_once(',')?;
+            DataType::Decimal128(precision.trim().parse().ok()?, scale.trim().parse().ok()?)
+        }
+    })
+}
@@ -15,25 +15,31 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use pyo3::{basic::CompareOp, prelude::*};
+use pyo3::{
+    basic::CompareOp,
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+};
+use std::collections::hash_map::DefaultHasher;
 use std::convert::{From, Into};
+use std::hash::{Hash, Hasher};
 
 use datafusion::arrow::datatypes::DataType;
-use datafusion::arrow::pyarrow::PyArrowType;
+use datafusion::arrow::pyarrow::{PyArrowConvert, PyArrowType};
 use datafusion::scalar::ScalarValue;
-use datafusion_common::DFField;
+use datafusion_common::{Column, DFField, OwnedTableReference};
 use datafusion_expr::{
-    col,
     expr::{
         AggregateFunction, AggregateUDF, InList, InSubquery, ScalarFunction, ScalarUDF, Sort,
         WindowFunction,
     },
     lit,
     utils::exprlist_to_fields,
-    Between, BinaryExpr, Case, Cast, Expr, GetIndexedField, Like, LogicalPlan, Operator, TryCast,
+    window_function, Between, BinaryExpr, Case, Cast, Expr, GetIndexedField, Like, LogicalPlan,
+    Operator, TryCast, WindowFrame,
 };
 
-use crate::common::data_type::{DataTypeMap, RexType};
+use crate::common::data_type::{DataTypeArg, DataTypeMap, PyScalarValue, RexType};
 use crate::errors::{py_runtime_err, py_type_err, DataFusionError};
 use crate::expr::aggregate_expr::PyAggregateFunction;
 use crate::expr::binary_expr::PyBinaryExpr;
@@ -48,6 +54,8 @@ use self::bool_expr::{
 };
 use self::like::{PyILike, PyLike, PySimilarTo};
 use self::scalar_variable::PyScalarVariable;
+use self::sort_expr::{PySortExpr, SortKey};
+use self::window::PyWindowFrame;
 
 pub mod aggregate;
 pub mod aggregate_expr;
@@ -86,10 +94,12 @@ pub mod scalar_subquery;
 pub mod scalar_variable;
 pub mod signature;
 pub mod sort;
+pub mod sort_expr;
 pub mod subquery;
 pub mod subquery_alias;
 pub mod table_scan;
 pub mod union;
+pub mod window;
 
 /// A PyExpr that can be used on a DataFrame
 #[pyclass(name = "Expr", module = "datafusion.expr", subclass)]
@@ -110,6 +120,23 @@ impl From<Expr> for PyExpr {
     }
 }
 
+/// The operand of an operator on an `Expr`: another expression, or a Python value
+/// that is made a literal as `lit` does
+#[derive(FromPyObject)]
+pub enum ExprOrLiteral {
+    Expr(PyExpr),
+    Literal(PyScalarValue),
+}
+
+impl From<ExprOrLiteral> for Expr {
+    fn from(operand: ExprOrLiteral) -> Expr {
+        match operand {
+            ExprOrLiteral::Expr(expr) => expr.expr,
+            ExprOrLiteral::Literal(value) => lit(value.0),
+        }
+    }
+}
+
 #[pymethods]
 impl PyExpr {
     /// Return the specific expression
@@ -135,6 +162,7 @@ impl PyExpr {
             Expr::AggregateFunction(expr) => {
                 Ok(PyAggregateFunction::from(expr.clone()).into_py(py))
             }
+            Expr::Sort(sort) => Ok(PySortExpr::from(sort.clone()).into_py(py)),
             other => Err(py_runtime_err(format!(
                 "Cannot convert this Expr to a Python object: {:?}",
                 other
@@ -153,49 +181,91 @@ impl PyExpr {
         Ok(self.expr.canonical_name())
     }
 
-    fn __richcmp__(&self, other: PyExpr, op: CompareOp) -> PyExpr {
+    fn __richcmp__(&self, other: ExprOrLiteral, op: CompareOp) -> PyExpr {
+        let other = Expr::from(other);
         let expr = match op {
-            CompareOp::Lt => self.expr.clone().lt(other.expr),
-            CompareOp::Le => self.expr.clone().lt_eq(other.expr),
-            CompareOp::Eq => self.expr.clone().eq(other.expr),
-            CompareOp::Ne => self.expr.clone().not_eq(other.expr),
-            CompareOp::Gt => self.expr.clone().gt(other.expr),
-            CompareOp::Ge => self.expr.clone().gt_eq(other.expr),
+            CompareOp::Lt => self.expr.clone().lt(other),
+            CompareOp::Le => self.expr.clone().lt_eq(other),
+            CompareOp::Eq => self.expr.clone().eq(other),
+            CompareOp::Ne => self.expr.clone().not_eq(other),
+            CompareOp::Gt => self.expr.clone().gt(other),
+            CompareOp::Ge => self.expr.clone().gt_eq(other),
         };
         expr.into()
     }
 
+    /// `==` builds an expression rather than comparing, so expressions hash by their
+    /// structure to stay usable in sets and as dict keys
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.expr.hash(&mut hasher);
+        hasher.finish()
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("Expr({})", self.expr))
     }
 
-    fn __add__(&self, rhs: PyExpr) -> PyResult<PyExpr> {
-        Ok((self.expr.clone() + rhs.expr).into())
+    fn __add__(&self, rhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((self.expr.clone() + Expr::from(rhs)).into())
+    }
+
+    fn __radd__(&self, lhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((Expr::from(lhs) + self.expr.clone()).into())
+    }
+
+    fn __sub__(&self, rhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((self.expr.clone() - Expr::from(rhs)).into())
+    }
+
+    fn __rsub__(&self, lhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((Expr::from(lhs) - self.expr.clone()).into())
+    }
+
+    fn __truediv__(&self, rhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((self.expr.clone() / Expr::from(rhs)).into())
     }
 
-    fn __sub__(&self, rhs: PyExpr) -> PyResult<PyExpr> {
-        Ok((self.expr.clone() - rhs.expr).into())
+    fn __rtruediv__(&self, lhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((Expr::from(lhs) / self.expr.clone()).into())
     }
 
-    fn __truediv__(&self, rhs: PyExpr) -> PyResult<PyExpr> {
-        Ok((self.expr.clone() / rhs.expr).into())
+    fn __mul__(&self, rhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((self.expr.clone() * Expr::from(rhs)).into())
     }
 
-    fn __mul__(&self, rhs: PyExpr) -> PyResult<PyExpr> {
-        Ok((self.expr.clone() * rhs.expr).into())
+    fn __rmul__(&self, lhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok((Expr::from(lhs) * self.expr.clone()).into())
     }
 
-    fn __mod__(&self, rhs: PyExpr) -> PyResult<PyExpr> {
-        let expr = self.expr.clone() % rhs.expr;
+    fn __mod__(&self, rhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        let expr = self.expr.clone() % Expr::from(rhs);
         Ok(expr.into())
     }
 
-    fn __and__(&self, rhs: PyExpr) -> PyResult<PyExpr> {
-        Ok(self.expr.clone().and(rhs.expr).into())
+    fn __rmod__(&self, lhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        let expr = Expr::from(lhs) % self.expr.clone();
+        Ok(expr.into())
+    }
+
+    fn __neg__(&self) -> PyResult<PyExpr> {
+        Ok(Expr::Negative(Box::new(self.expr.clone())).into())
+    }
+
+    fn __and__(&self, rhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok(self.expr.clone().and(rhs.into()).into())
+    }
+
+    fn __rand__(&self, lhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok(Expr::from(lhs).and(self.expr.clone()).into())
     }
 
-    fn __or__(&self, rhs: PyExpr) -> PyResult<PyExpr> {
-        Ok(self.expr.clone().or(rhs.expr).into())
+    fn __or__(&self, rhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok(self.expr.clone().or(rhs.into()).into())
+    }
+
+    fn __ror__(&self, lhs: ExprOrLiteral) -> PyResult<PyExpr> {
+        Ok(Expr::from(lhs).or(self.expr.clone()).into())
     }
 
     fn __invert__(&self) -> PyResult<PyExpr> {
@@ -203,22 +273,55 @@ impl PyExpr {
         Ok(expr.into())
     }
 
-    fn __getitem__(&self, key: &str) -> PyResult<PyExpr> {
+    /// `expr["name"]` is the field `name` of a struct, like `field`, and `expr[i]` the
+    /// element at zero-based position `i` of a list, which is null past its end
+    fn __getitem__(&self, key: &PyAny) -> PyResult<PyExpr> {
+        if let Ok(name) = key.extract::<&str>() {
+            return Ok(self.field(name));
+        }
+        let index = key.extract::<i64>().map_err(|_| {
+            PyTypeError::new_err(format!(
+                "Expression index must be a field name or a list position, not {}",
+                key.get_type()
+            ))
+        })?;
+        if index < 0 {
+            return Err(PyValueError::new_err(format!(
+                "Invalid list position {index}, negative positions are not supported"
+            )));
+        }
+        // DataFusion numbers list elements from 1, as SQL does
         Ok(Expr::GetIndexedField(GetIndexedField::new(
             Box::new(self.expr.clone()),
-            ScalarValue::Utf8(Some(key.to_string())),
+            ScalarValue::Int64(Some(index + 1)),
         ))
         .into())
     }
 
+    /// The field `name` of a struct. A field the struct does not have is an error when
+    /// the expression is planned.
+    pub fn field(&self, name: &str) -> PyExpr {
+        Expr::GetIndexedField(GetIndexedField::new(
+            Box::new(self.expr.clone()),
+            ScalarValue::Utf8(Some(name.to_string())),
+        ))
+        .into()
+    }
+
+    /// A literal of a `pyarrow.Scalar` or a plain Python value, whose type is inferred
+    /// as `pyarrow.scalar` does unless `data_type` is given
     #[staticmethod]
-    pub fn literal(value: ScalarValue) -> PyExpr {
-        lit(value).into()
+    #[pyo3(signature = (value, data_type=None))]
+    pub fn literal(value: &PyAny, data_type: Option<PyArrowType<DataType>>) -> PyResult<PyExpr> {
+        let value = PyScalarValue::from_python(value, data_type.map(|t| t.0).as_ref())?;
+        Ok(lit(value.0).into())
     }
 
+    /// A reference to a column, whose name may be qualified as in `t.a`; see
+    /// `parse_column`
     #[staticmethod]
-    pub fn column(value: &str) -> PyExpr {
-        col(value).into()
+    pub fn column(value: &str) -> PyResult<PyExpr> {
+        Ok(Expr::Column(parse_column(value)?).into())
     }
 
     /// assign a name to the PyExpr
@@ -226,23 +329,101 @@ impl PyExpr {
         self.expr.clone().alias(name).into()
     }
 
-    /// Create a sort PyExpr from an existing PyExpr.
-    #[pyo3(signature = (ascending=true, nulls_first=true))]
-    pub fn sort(&self, ascending: bool, nulls_first: bool) -> PyExpr {
-        self.expr.clone().sort(ascending, nulls_first).into()
+    /// Sort by this expression. Unless `nulls_first` is given, nulls sort last in
+    /// ascending order and first in descending order, as DataFusion does.
+    #[pyo3(signature = (ascending=true, nulls_first=None))]
+    pub fn sort(&self, ascending: bool, nulls_first: Option<bool>) -> PySortExpr {
+        PySortExpr::new(self.expr.clone(), ascending, nulls_first)
+    }
+
+    /// Sort by this expression in ascending order
+    #[pyo3(signature = (nulls_first=None))]
+    pub fn asc(&self, nulls_first: Option<bool>) -> PySortExpr {
+        self.sort(true, nulls_first)
+    }
+
+    /// Sort by this expression in descending order
+    #[pyo3(signature = (nulls_first=None))]
+    pub fn desc(&self, nulls_first: Option<bool>) -> PySortExpr {
+        self.sort(false, nulls_first)
+    }
+
+    /// Evaluate this aggregate (or window) function over a window, e.g.
+    /// `sum(x).over(partition_by=[g])` is `SUM(x) OVER (PARTITION BY g)`.
+    #[pyo3(signature = (partition_by=vec![], order_by=vec![], frame=None))]
+    pub fn over(
+        &self,
+        partition_by: Vec<PyExpr>,
+        order_by: Vec<SortKey>,
+        frame: Option<PyWindowFrame>,
+    ) -> PyResult<PyExpr> {
+        let (fun, args) = match &self.expr {
+            Expr::AggregateFunction(AggregateFunction {
+                fun,
+                args,
+                distinct: false,
+                filter: None,
+                order_by: None,
+            }) => (
+                window_function::WindowFunction::AggregateFunction(fun.clone()),
+                args.clone(),
+            ),
+            Expr::AggregateUDF(AggregateUDF {
+                fun,
+                args,
+                filter: None,
+                order_by: None,
+            }) => (
+                window_function::WindowFunction::AggregateUDF(fun.clone()),
+                args.clone(),
+            ),
+            Expr::WindowFunction(WindowFunction { fun, args, .. }) => (fun.clone(), args.clone()),
+            Expr::AggregateFunction(_) | Expr::AggregateUDF(_) => {
+                return Err(py_type_err(format!(
+                    "Cannot use DISTINCT, FILTER or ORDER BY aggregate {} as a window function",
+                    self.expr
+                )))
+            }
+            other => {
+                return Err(py_type_err(format!(
+                    "over() requires an aggregate or window function, got {other}"
+                )))
+            }
+        };
+        let partition_by: Vec<Expr> = partition_by.into_iter().map(|e| e.expr).collect();
+        let order_by: Vec<Expr> = order_by.into_iter().map(SortKey::into_sort_expr).collect();
+        let window_frame = frame
+            .map(WindowFrame::from)
+            .unwrap_or_else(|| WindowFrame::new(!order_by.is_empty()));
+        Ok(Expr::WindowFunction(WindowFunction::new(
+            fun,
+            args,
+            partition_by,
+            order_by,
+            window_frame,
+        ))
+        .into())
     }
 
     pub fn is_null(&self) -> PyExpr {
         self.expr.clone().is_null().into()
     }
 
-    pub fn cast(&self, to: PyArrowType<DataType>) -> PyExpr {
+    /// Cast to `to`, a `DataType`, a `pyarrow.DataType` or a type name such as
+    /// `"decimal(10, 2)"`. A value that cannot be converted fails the query; a cast
+    /// between types Arrow cannot convert at all fails when the query is planned.
+    pub fn cast(&self, to: DataTypeArg) -> PyExpr {
         // self.expr.cast_to() requires DFSchema to validate that the cast
         // is supported, omit that for now
         let expr = Expr::Cast(Cast::new(Box::new(self.expr.clone()), to.0));
         expr.into()
     }
 
+    /// Like `cast`, but a value that cannot be converted becomes null
+    pub fn try_cast(&self, to: DataTypeArg) -> PyExpr {
+        Expr::TryCast(TryCast::new(Box::new(self.expr.clone()), to.0)).into()
+    }
+
     /// A Rex (Row Expression) specifies a single row of data. That specification
     /// could include user defined functions or types. RexType identifies the row
     /// as one of the possible valid `RexTypes`.
@@ -294,45 +475,12 @@ impl PyExpr {
         Self::_types(&self.expr)
     }
 
-    /// Extracts the Expr value into a PyObject that can be shared with Python
+    /// Extracts the Expr value into a PyObject that can be shared with Python.
+    /// The value is converted through a `pyarrow.Scalar` so that it matches the
+    /// native Python values produced by `DataFrame.to_pydict` and `DataFrame.to_pylist`.
     pub fn python_value(&self, py: Python) -> PyResult<PyObject> {
         match &self.expr {
-            Expr::Literal(scalar_value) => Ok(match scalar_value {
-                ScalarValue::Null => todo!(),
-                ScalarValue::Boolean(v) => v.into_py(py),
-                ScalarValue::Float32(v) => v.into_py(py),
-                ScalarValue::Float64(v) => v.into_py(py),
-                ScalarValue::Decimal128(_, _, _) => todo!(),
-                ScalarValue::Int8(v) => v.into_py(py),
-                ScalarValue::Int16(v) => v.into_py(py),
-                ScalarValue::Int32(v) => v.into_py(py),
-                ScalarValue::Int64(v) => v.into_py(py),
-                ScalarValue::UInt8(v) => v.into_py(py),
-                ScalarValue::UInt16(v) => v.into_py(py),
-                ScalarValue::UInt32(v) => v.into_py(py),
-                ScalarValue::UInt64(v) => v.into_py(py),
-                ScalarValue::Utf8(v) => v.clone().into_py(py),
-                ScalarValue::LargeUtf8(v) => v.clone().into_py(py),
-                ScalarValue::Binary(v) => v.clone().into_py(py),
-                ScalarValue::FixedSizeBinary(_, _) => todo!(),
-                ScalarValue::LargeBinary(v) => v.clone().into_py(py),
-                ScalarValue::List(_, _) => todo!(),
-                ScalarValue::Date32(v) => v.into_py(py),
-                ScalarValue::Date64(v) => v.into_py(py),
-                ScalarValue::Time32Second(v) => v.into_py(py),
-                ScalarValue::Time32Millisecond(v) => v.into_py(py),
-                ScalarValue::Time64Microsecond(v) => v.into_py(py),
-                ScalarValue::Time64Nanosecond(v) => v.into_py(py),
-                ScalarValue::TimestampSecond(_, _) => todo!(),
-                ScalarValue::TimestampMillisecond(_, _) => todo!(),
-                ScalarValue::TimestampMicrosecond(_, _) => todo!(),
-                ScalarValue::TimestampNanosecond(_, _) => todo!(),
-                ScalarValue::IntervalYearMonth(v) => v.into_py(py),
-                ScalarValue::IntervalDayTime(v) => v.into_py(py),
-                ScalarValue::IntervalMonthDayNano(v) => v.into_py(py),
-                ScalarValue::Struct(_, _) => todo!(),
-                ScalarValue::Dictionary(_, _) => todo!(),
-            }),
+            Expr::Literal(scalar_value) => scalar_value.to_pyarrow(py)?.call_method0(py, "as_py"),
             _ => Err(py_type_err(format!(
                 "Non Expr::Literal encountered in types: {:?}",
                 &self.expr
@@ -582,6 +730,53 @@ impl PyExpr {
     }
 }
 
+/// Parses a column name that may be qualified by its relation, as in `t.a`, `s.t.a` or
+/// `c.s.t.a`. A part in double quotes may contain dots, as in `"a.b"`, with `""`
+/// standing for a quote in it. Names are not normalized, so `A` and `a` differ.
+pub(crate) fn parse_column(name: &str) -> PyResult<Column> {
+    let mut parts = vec![];
+    let mut part = String::new();
+    let mut quoted = false;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                part.push('"');
+            }
+            '"' => quoted = !quoted,
+            '.' if !quoted => parts.push(std::mem::take(&mut part)),
+            c => part.push(c),
+        }
+    }
+    if quoted {
+        return Err(PyValueError::new_err(format!(
+            "Unterminated quote in column name {name}"
+        )));
+    }
+    let column = part;
+    let relation = match parts.as_slice() {
+        [] => None,
+        [table] => Some(OwnedTableReference::bare(table.clone())),
+        [schema, table] => Some(OwnedTableReference::partial(schema.clone(), table.clone())),
+        [catalog, schema, table] => Some(OwnedTableReference::full(
+            catalog.clone(),
+            schema.clone(),
+            table.clone(),
+        )),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Invalid column name {name}, a column is qualified by at most a catalog, \
+                 schema and table; quote names that contain dots"
+            )))
+        }
+    };
+    Ok(Column {
+        relation,
+        name: column,
+    })
+}
+
 /// Initializes the `expr` module to match the pattern of `datafusion-expr` https://docs.rs/datafusion-expr/latest/datafusion_expr/
 pub(crate) fn init_module(m: &PyModule) -> PyResult<()> {
     m.add_class::<PyExpr>()?;
@@ -623,6 +818,7 @@ pub(crate) fn init_module(m: &PyModule) -> PyResult<()> {
     m.add_class::<limit::PyLimit>()?;
     m.add_class::<aggregate::PyAggregate>()?;
     m.add_class::<sort::PySort>()?;
+    m.add_class::<sort_expr::PySortExpr>()?;
     m.add_class::<analyze::PyAnalyze>()?;
     m.add_class::<empty_relation::PyEmptyRelation>()?;
     m.add_class::<join::PyJoin>()?;
@@ -641,5 +837,6 @@ pub(crate) fn init_module(m: &PyModule) -> PyResult<()> {
     m.add_class::<drop_table::PyDropTable>()?;
     m.add_class::<repartition::PyPartitioning>()?;
     m.add_class::<repartition::PyRepartition>()?;
+    m.add_class::<window::PyWindowFrame>()?;
     Ok(())
 }
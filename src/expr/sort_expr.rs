@@ -0,0 +1,134 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use datafusion_expr::{col, expr::Sort, Expr};
+use pyo3::exceptions::{PyAttributeError, PyTypeError};
+use pyo3::prelude::*;
+use std::fmt::{self, Display, Formatter};
+
+use crate::expr::PyExpr;
+
+/// An expression together with the direction to order rows by it, as built by
+/// `Expr.sort`, `Expr.asc` or `Expr.desc`. It is not an `Expr` itself, since it only
+/// means something where rows are ordered.
+#[pyclass(name = "SortExpr", module = "datafusion.expr", subclass)]
+#[derive(Clone)]
+pub struct PySortExpr {
+    sort: Sort,
+}
+
+impl PySortExpr {
+    /// Sorts by `expr`, with nulls last in ascending order and first in descending order
+    /// as DataFusion does unless `nulls_first` says otherwise
+    pub fn new(expr: Expr, ascending: bool, nulls_first: Option<bool>) -> Self {
+        Sort::new(Box::new(expr), ascending, nulls_first.unwrap_or(!ascending)).into()
+    }
+}
+
+impl From<Sort> for PySortExpr {
+    fn from(sort: Sort) -> PySortExpr {
+        PySortExpr { sort }
+    }
+}
+
+impl From<PySortExpr> for Expr {
+    fn from(sort: PySortExpr) -> Expr {
+        Expr::Sort(sort.sort)
+    }
+}
+
+impl Display for PySortExpr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} NULLS {}",
+            self.sort.expr,
+            if self.sort.asc { "ASC" } else { "DESC" },
+            if self.sort.nulls_first {
+                "FIRST"
+            } else {
+                "LAST"
+            }
+        )
+    }
+}
+
+#[pymethods]
+impl PySortExpr {
+    /// The expression whose values order the rows
+    fn expr(&self) -> PyResult<PyExpr> {
+        Ok((*self.sort.expr).clone().into())
+    }
+
+    fn ascending(&self) -> bool {
+        self.sort.asc
+    }
+
+    fn nulls_first(&self) -> bool {
+        self.sort.nulls_first
+    }
+
+    /// Expression methods are looked up here when they are missing from a `SortExpr`,
+    /// which is reported as a type error explaining the difference rather than as a
+    /// missing attribute
+    fn __getattr__(&self, py: Python, name: &str) -> PyResult<PyObject> {
+        if !name.starts_with('_') && py.get_type::<PyExpr>().hasattr(name)? {
+            return Err(PyTypeError::new_err(format!(
+                "SortExpr({self}) only orders rows and is not an Expr, so it has no \
+                 {name}(). Apply {name}() to the expression before sorting by it, or use \
+                 expr() to get the sorted expression back"
+            )));
+        }
+        Err(PyAttributeError::new_err(format!(
+            "'SortExpr' object has no attribute '{name}'"
+        )))
+    }
+
+    fn __repr__(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// A key to order rows by: a sort expression, or an expression or column name that
+/// sorts in ascending order with nulls last
+#[derive(FromPyObject)]
+#[allow(clippy::large_enum_variant)]
+pub enum SortKey {
+    Sort(PySortExpr),
+    Expr(PyExpr),
+    Column(String),
+}
+
+impl SortKey {
+    /// The key as a sort expression, an expression that already is one is kept as is
+    pub fn into_sort_expr(self) -> Expr {
+        match Expr::from(self) {
+            expr @ Expr::Sort(_) => expr,
+            expr => expr.sort(true, false),
+        }
+    }
+}
+
+impl From<SortKey> for Expr {
+    fn from(key: SortKey) -> Expr {
+        match key {
+            SortKey::Sort(sort) => sort.into(),
+            SortKey::Expr(expr) => expr.expr,
+            SortKey::Column(name) => col(name.as_str()),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use datafusion::scalar::ScalarValue;
+use datafusion_expr::{WindowFrame, WindowFrameBound, WindowFrameUnits};
+use pyo3::prelude::*;
+use std::fmt::{self, Display, Formatter};
+
+use crate::errors::DataFusionError;
+
+/// The frame of rows a window function is evaluated over, e.g.
+/// `ROWS BETWEEN 2 PRECEDING AND CURRENT ROW`
+#[pyclass(name = "WindowFrame", module = "datafusion.expr", subclass)]
+#[derive(Clone)]
+pub struct PyWindowFrame {
+    frame: WindowFrame,
+}
+
+impl From<PyWindowFrame> for WindowFrame {
+    fn from(frame: PyWindowFrame) -> WindowFrame {
+        frame.frame
+    }
+}
+
+impl From<WindowFrame> for PyWindowFrame {
+    fn from(frame: WindowFrame) -> PyWindowFrame {
+        PyWindowFrame { frame }
+    }
+}
+
+impl Display for PyWindowFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.frame)
+    }
+}
+
+/// A window frame bound given from Python: a number of rows, values or groups, or a
+/// pyarrow scalar such as an interval for `RANGE` frames over timestamps
+#[derive(FromPyObject)]
+pub enum PyWindowFrameBound {
+    Int(u64),
+    Scalar(ScalarValue),
+}
+
+impl PyWindowFrameBound {
+    fn into_scalar(self) -> ScalarValue {
+        match self {
+            PyWindowFrameBound::Int(n) => ScalarValue::UInt64(Some(n)),
+            PyWindowFrameBound::Scalar(value) => value,
+        }
+    }
+
+    fn is_current_row(&self) -> bool {
+        matches!(self, PyWindowFrameBound::Int(0))
+    }
+}
+
+#[pymethods]
+impl PyWindowFrame {
+    /// Create a frame with `units` one of "rows", "range" or "groups". `start_bound`
+    /// is how far the frame extends before the current row and `end_bound` how far it
+    /// extends after it; `None` means unbounded and `0` the current row.
+    #[new]
+    #[pyo3(signature = (units, start_bound=None, end_bound=None))]
+    pub fn new(
+        units: &str,
+        start_bound: Option<PyWindowFrameBound>,
+        end_bound: Option<PyWindowFrameBound>,
+    ) -> PyResult<Self> {
+        let units = match units.to_ascii_lowercase().as_str() {
+            "rows" => WindowFrameUnits::Rows,
+            "range" => WindowFrameUnits::Range,
+            "groups" => WindowFrameUnits::Groups,
+            other => {
+                return Err(DataFusionError::Common(format!(
+                "Unknown window frame units '{other}', expected one of 'rows', 'range' or 'groups'"
+            ))
+                .into())
+            }
+        };
+        let start_bound = match start_bound {
+            Some(bound) if bound.is_current_row() => WindowFrameBound::CurrentRow,
+            Some(bound) => WindowFrameBound::Preceding(bound.into_scalar()),
+            None => WindowFrameBound::Preceding(ScalarValue::UInt64(None)),
+        };
+        let end_bound = match end_bound {
+            Some(bound) if bound.is_current_row() => WindowFrameBound::CurrentRow,
+            Some(bound) => WindowFrameBound::Following(bound.into_scalar()),
+            None => WindowFrameBound::Following(ScalarValue::UInt64(None)),
+        };
+        Ok(WindowFrame {
+            units,
+            start_bound,
+            end_bound,
+        }
+        .into())
+    }
+
+    /// The frame units, one of "rows", "range" or "groups"
+    fn units(&self) -> String {
+        self.frame.units.to_string().to_lowercase()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("WindowFrame({})", self)
+    }
+}
@@ -17,17 +17,18 @@
 
 use pyo3::{prelude::*, wrap_pyfunction};
 
-use datafusion_common::Column;
 use datafusion_expr::{
     aggregate_function,
-    expr::{AggregateFunction, ScalarFunction, Sort, WindowFunction},
+    expr::{AggregateFunction, ScalarFunction, WindowFunction},
     lit,
     window_function::find_df_window_func,
     BuiltinScalarFunction, Expr, WindowFrame,
 };
 
 use crate::errors::DataFusionError;
-use crate::expr::PyExpr;
+use crate::expr::sort_expr::{PySortExpr, SortKey};
+use crate::expr::window::PyWindowFrame;
+use crate::expr::{parse_column, PyExpr};
 
 #[pyfunction]
 fn in_list(expr: PyExpr, value: Vec<PyExpr>, negated: bool) -> PyExpr {
@@ -69,16 +70,10 @@ fn concat_ws(sep: String, args: Vec<PyExpr>) -> PyResult<PyExpr> {
     Ok(datafusion_expr::concat_ws(lit(sep), args).into())
 }
 
-/// Creates a new Sort Expr
+/// Creates a new SortExpr, as `expr.sort(asc, nulls_first)` does
 #[pyfunction]
-fn order_by(expr: PyExpr, asc: Option<bool>, nulls_first: Option<bool>) -> PyResult<PyExpr> {
-    Ok(PyExpr {
-        expr: datafusion_expr::Expr::Sort(Sort {
-            expr: Box::new(expr.expr),
-            asc: asc.unwrap_or(true),
-            nulls_first: nulls_first.unwrap_or(true),
-        }),
-    })
+fn order_by(expr: PyExpr, asc: Option<bool>, nulls_first: Option<bool>) -> PySortExpr {
+    PySortExpr::new(expr.expr, asc.unwrap_or(true), nulls_first)
 }
 
 /// Creates a new Alias Expr
@@ -89,14 +84,11 @@ fn alias(expr: PyExpr, name: &str) -> PyResult<PyExpr> {
     })
 }
 
-/// Create a column reference Expr
+/// Create a column reference Expr, whose name may be qualified as in `t.a`
 #[pyfunction]
 fn col(name: &str) -> PyResult<PyExpr> {
     Ok(PyExpr {
-        expr: datafusion_expr::Expr::Column(Column {
-            relation: None,
-            name: name.to_string(),
-        }),
+        expr: datafusion_expr::Expr::Column(parse_column(name)?),
     })
 }
 
@@ -114,20 +106,27 @@ fn count_star() -> PyResult<PyExpr> {
     })
 }
 
-/// Creates a new Window function expression
+/// Creates a new Window function expression. `name` is a built-in window function
+/// such as `row_number` or `lag`, or an aggregate function such as `sum`. Without a
+/// `window_frame`, the frame runs from the start of the partition to the current row
+/// when there is an `order_by` and spans the whole partition otherwise.
 #[pyfunction]
+#[pyo3(signature = (name, args, partition_by=None, order_by=None, window_frame=None))]
 fn window(
     name: &str,
     args: Vec<PyExpr>,
     partition_by: Option<Vec<PyExpr>>,
-    order_by: Option<Vec<PyExpr>>,
+    order_by: Option<Vec<SortKey>>,
+    window_frame: Option<PyWindowFrame>,
 ) -> PyResult<PyExpr> {
     let fun = find_df_window_func(name);
     if fun.is_none() {
         return Err(DataFusionError::Common("window function not found".to_string()).into());
     }
     let fun = fun.unwrap();
-    let window_frame = WindowFrame::new(order_by.is_some());
+    let window_frame = window_frame
+        .map(WindowFrame::from)
+        .unwrap_or_else(|| WindowFrame::new(order_by.is_some()));
     Ok(PyExpr {
         expr: datafusion_expr::Expr::WindowFunction(WindowFunction {
             fun,
@@ -140,7 +139,7 @@ fn window(
             order_by: order_by
                 .unwrap_or_default()
                 .into_iter()
-                .map(|x| x.expr)
+                .map(SortKey::into_sort_expr)
                 .collect::<Vec<_>>(),
             window_frame,
         }),
@@ -173,14 +172,15 @@ macro_rules! aggregate_function {
     ($NAME: ident, $FUNC: ident, $DOC: expr) => {
         #[doc = $DOC]
         #[pyfunction]
-        #[pyo3(signature = (*args, distinct=false))]
-        fn $NAME(args: Vec<PyExpr>, distinct: bool) -> PyExpr {
+        #[pyo3(signature = (*args, distinct=false, order_by=None))]
+        fn $NAME(args: Vec<PyExpr>, distinct: bool, order_by: Option<Vec<SortKey>>) -> PyExpr {
             let expr = datafusion_expr::Expr::AggregateFunction(AggregateFunction {
                 fun: datafusion_expr::aggregate_function::AggregateFunction::$FUNC,
                 args: args.into_iter().map(|e| e.into()).collect(),
                 distinct,
                 filter: None,
-                order_by: None,
+                order_by: order_by
+                    .map(|keys| keys.into_iter().map(SortKey::into_sort_expr).collect()),
             });
             expr.into()
         }
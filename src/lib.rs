@@ -38,11 +38,14 @@ mod context;
 mod dataframe;
 mod dataset;
 mod dataset_exec;
+#[cfg(feature = "delta")]
+mod delta;
 pub mod errors;
 #[allow(clippy::borrow_deref_ref)]
 pub mod expr;
 #[allow(clippy::borrow_deref_ref)]
 mod functions;
+mod listing;
 pub mod physical_plan;
 mod pyarrow_filter_expression;
 mod record_batch;
@@ -54,6 +57,7 @@ mod udaf;
 #[allow(clippy::borrow_deref_ref)]
 mod udf;
 pub mod utils;
+mod writer;
 
 #[cfg(feature = "mimalloc")]
 #[global_allocator]
@@ -87,6 +91,8 @@ fn _internal(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<config::PyConfig>()?;
     m.add_class::<sql::logical::PyLogicalPlan>()?;
     m.add_class::<physical_plan::PyExecutionPlan>()?;
+    m.add_function(wrap_pyfunction!(dataframe::set_display_options, m)?)?;
+    m.add_function(wrap_pyfunction!(dataframe::get_display_options, m)?)?;
 
     // Register `common` as a submodule. Matching `datafusion-common` https://docs.rs/datafusion-common/latest/datafusion_common/
     let common = PyModule::new(py, "common")?;
@@ -0,0 +1,217 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A listing table that defers listing its files until it is first scanned
+
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use object_store::ObjectMeta;
+use tokio::sync::OnceCell;
+
+use datafusion::arrow::datatypes::{Field, Schema, SchemaRef};
+use datafusion::datasource::datasource::TableProviderFilterPushDown;
+use datafusion::datasource::listing::{ListingOptions, ListingTableUrl, PartitionedFile};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::Result as DFResult;
+use datafusion::execution::context::SessionState;
+use datafusion::physical_expr::create_physical_expr;
+use datafusion::physical_plan::file_format::FileScanConfig;
+use datafusion::physical_plan::{ExecutionPlan, Statistics};
+use datafusion_common::{DFSchema, ScalarValue};
+use datafusion_expr::Expr;
+use datafusion_optimizer::utils::conjunction;
+
+use crate::utils::list_all_files;
+
+/// The directory name Hive uses for a null partition value
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// A table over the files under `table_path`, like DataFusion's `ListingTable`, except
+/// that the files are listed when the table is first scanned rather than when it is
+/// created, and that listing is then reused by every later scan. Since inferring the
+/// schema would list the files, it has to be given up front.
+pub(crate) struct LazyListingTable {
+    table_path: ListingTableUrl,
+    options: ListingOptions,
+    file_schema: SchemaRef,
+    table_schema: SchemaRef,
+    files: OnceCell<Vec<PartitionedFile>>,
+}
+
+impl LazyListingTable {
+    /// `file_schema` is the schema of the files, without the partition columns of
+    /// `options`, which are appended to it as the table's schema
+    pub fn new(
+        table_path: ListingTableUrl,
+        options: ListingOptions,
+        file_schema: SchemaRef,
+    ) -> Self {
+        let fields = file_schema
+            .fields()
+            .iter()
+            .map(|field| field.as_ref().clone())
+            .chain(
+                options
+                    .table_partition_cols
+                    .iter()
+                    .map(|(name, data_type)| Field::new(name, data_type.clone(), false)),
+            )
+            .collect::<Vec<_>>();
+        Self {
+            table_path,
+            options,
+            file_schema,
+            table_schema: Arc::new(Schema::new(fields)),
+            files: OnceCell::new(),
+        }
+    }
+
+    /// The files of the table, listed on the first call
+    async fn files(&self, state: &SessionState) -> DFResult<&Vec<PartitionedFile>> {
+        self.files
+            .get_or_try_init(|| async {
+                let store = state.runtime_env().object_store(&self.table_path)?;
+                let files = list_all_files(
+                    &self.table_path,
+                    store.as_ref(),
+                    &self.options.file_extension,
+                )
+                .await?;
+                let mut files = files
+                    .into_iter()
+                    .filter_map(|meta| self.partitioned_file(meta).transpose())
+                    .collect::<DFResult<Vec<_>>>()?;
+                files.sort_by(|a, b| a.object_meta.location.cmp(&b.object_meta.location));
+                Ok(files)
+            })
+            .await
+    }
+
+    /// The file with the values of the partition columns parsed from its directories,
+    /// such as `year=2024/`, or `None` for a file outside of the partition directories,
+    /// which `ListingTable` ignores as well
+    fn partitioned_file(&self, object_meta: ObjectMeta) -> DFResult<Option<PartitionedFile>> {
+        let partition_cols = &self.options.table_partition_cols;
+        let mut dirs: Vec<String> =
+            match object_meta.location.prefix_match(self.table_path.prefix()) {
+                Some(parts) => parts.map(|part| part.as_ref().to_string()).collect(),
+                None => return Ok(None),
+            };
+        // the last part is the file name
+        dirs.pop();
+
+        let mut partition_values = Vec::with_capacity(partition_cols.len());
+        for ((name, data_type), dir) in partition_cols.iter().zip(dirs.iter()) {
+            match dir.split_once('=') {
+                Some((key, value)) if key == name => {
+                    partition_values.push(if value == HIVE_DEFAULT_PARTITION {
+                        ScalarValue::try_from(data_type)?
+                    } else {
+                        ScalarValue::try_from_string(value.to_string(), data_type)?
+                    })
+                }
+                _ => return Ok(None),
+            }
+        }
+        if partition_values.len() < partition_cols.len() {
+            return Ok(None);
+        }
+        Ok(Some(PartitionedFile {
+            object_meta,
+            partition_values,
+            range: None,
+            extensions: None,
+        }))
+    }
+}
+
+#[async_trait]
+impl TableProvider for LazyListingTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table_schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let files = self.files(state).await?;
+
+        let partitions = self.options.target_partitions.clamp(1, files.len().max(1));
+        let mut file_groups = vec![vec![]; partitions];
+        for (i, file) in files.iter().enumerate() {
+            file_groups[i % partitions].push(file.clone());
+        }
+        file_groups.retain(|group| !group.is_empty());
+
+        // the filters only refer to file columns, see `supports_filter_pushdown`, and
+        // are only used to prune what is read
+        let predicate = match conjunction(filters.to_vec()) {
+            Some(expr) => {
+                let table_df_schema = DFSchema::try_from(self.table_schema.as_ref().clone())?;
+                Some(create_physical_expr(
+                    &expr,
+                    &table_df_schema,
+                    &self.table_schema,
+                    state.execution_props(),
+                )?)
+            }
+            None => None,
+        };
+
+        let config = FileScanConfig {
+            object_store_url: self.table_path.object_store(),
+            file_schema: self.file_schema.clone(),
+            file_groups,
+            statistics: Statistics::default(),
+            projection: projection.cloned(),
+            limit,
+            table_partition_cols: self.options.table_partition_cols.clone(),
+            output_ordering: vec![],
+            infinite_source: false,
+        };
+        self.options
+            .format
+            .create_physical_plan(state, config, predicate.as_ref())
+            .await
+    }
+
+    fn supports_filter_pushdown(&self, filter: &Expr) -> DFResult<TableProviderFilterPushDown> {
+        let file_columns_only = filter
+            .to_columns()?
+            .iter()
+            .all(|column| self.file_schema.field_with_name(&column.name).is_ok());
+        Ok(if file_columns_only {
+            TableProviderFilterPushDown::Inexact
+        } else {
+            TableProviderFilterPushDown::Unsupported
+        })
+    }
+}
@@ -15,12 +15,17 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use crate::utils::wait_for_future;
+use crate::errors::DataFusionError;
+use crate::utils::{timeout_err, wait_for_future, wait_for_future_until};
 use datafusion::arrow::pyarrow::PyArrowConvert;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use futures::StreamExt;
-use pyo3::{pyclass, pymethods, PyObject, PyResult, Python};
+use pyo3::{pyclass, pymethods, PyObject, PyRef, PyResult, Python};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{Mutex, Notify};
 
 #[pyclass(name = "RecordBatch", module = "datafusion", subclass)]
 pub struct PyRecordBatch {
@@ -42,23 +47,113 @@ impl From<RecordBatch> for PyRecordBatch {
 
 #[pyclass(name = "RecordBatchStream", module = "datafusion", subclass)]
 pub struct PyRecordBatchStream {
-    stream: SendableRecordBatchStream,
+    // shared with the future driving `next`, so that `cancel` can be called from
+    // another thread while a batch is being computed
+    stream: Arc<Mutex<Option<SendableRecordBatchStream>>>,
+    cancelled: Arc<AtomicBool>,
+    cancel: Arc<Notify>,
+    deadline: Option<(Instant, f64)>,
+    // set once the deadline has passed, after which every `next` raises `TimeoutError`
+    timed_out: AtomicBool,
 }
 
 impl PyRecordBatchStream {
     pub fn new(stream: SendableRecordBatchStream) -> Self {
-        Self { stream }
+        Self {
+            stream: Arc::new(Mutex::new(Some(stream))),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            cancel: Arc::new(Notify::new()),
+            deadline: None,
+            timed_out: AtomicBool::new(false),
+        }
+    }
+
+    /// Cancel the stream, raising a `TimeoutError` from `next` and every later call,
+    /// once `deadline` has passed. `timeout` is the number of seconds it was set to, for error messages.
+    pub fn with_deadline(mut self, deadline: Instant, timeout: f64) -> Self {
+        self.deadline = Some((deadline, timeout));
+        self
+    }
+
+    /// Drops the underlying stream, which stops the query and frees its buffers,
+    /// unless a call to `next` holds it, in which case that call drops it
+    fn drop_stream(&self) {
+        if let Ok(mut stream) = self.stream.try_lock() {
+            *stream = None;
+        }
     }
 }
 
 #[pymethods]
 impl PyRecordBatchStream {
-    fn next(&mut self, py: Python) -> PyResult<Option<PyRecordBatch>> {
-        let result = self.stream.next();
-        match wait_for_future(py, result) {
+    fn next(&self, py: Python) -> PyResult<Option<PyRecordBatch>> {
+        if let Some((_, timeout)) = self.deadline {
+            if self.timed_out.load(Ordering::SeqCst) {
+                return Err(timeout_err(timeout));
+            }
+        }
+        let stream = self.stream.clone();
+        let cancelled = self.cancelled.clone();
+        let cancel = self.cancel.clone();
+        let next = async move {
+            let mut stream = stream.lock().await;
+            // registered before checking the flag so that a concurrent cancel is seen
+            let notified = cancel.notified();
+            if cancelled.load(Ordering::SeqCst) {
+                *stream = None;
+                return Err(cancelled_err());
+            }
+            let batch = match stream.as_mut() {
+                Some(batches) => tokio::select! {
+                    batch = batches.next() => Some(batch),
+                    _ = notified => None,
+                },
+                None => return Ok(None),
+            };
+            match batch {
+                Some(batch) => Ok(batch),
+                None => {
+                    *stream = None;
+                    Err(cancelled_err())
+                }
+            }
+        };
+
+        let result = match self.deadline {
+            Some((deadline, timeout)) => match wait_for_future_until(py, next, deadline) {
+                Some(result) => result,
+                None => {
+                    self.timed_out.store(true, Ordering::SeqCst);
+                    self.drop_stream();
+                    return Err(timeout_err(timeout));
+                }
+            },
+            None => wait_for_future(py, next),
+        };
+        match result? {
             None => Ok(None),
             Some(Ok(b)) => Ok(Some(b.into())),
             Some(Err(e)) => Err(e.into()),
         }
     }
+
+    /// Stop the query. A `next` call in progress on another thread, and any later
+    /// one, raises an error.
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.cancel.notify_waiters();
+        self.drop_stream();
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python) -> PyResult<Option<PyRecordBatch>> {
+        self.next(py)
+    }
+}
+
+fn cancelled_err() -> DataFusionError {
+    DataFusionError::Common("The query was cancelled".to_string())
 }
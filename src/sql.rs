@@ -17,3 +17,4 @@
 
 pub mod exceptions;
 pub mod logical;
+pub mod predicate;
@@ -0,0 +1,76 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::DataType;
+use datafusion::execution::context::TaskContext;
+use datafusion::execution::registry::FunctionRegistry;
+use datafusion_common::config::ConfigOptions;
+use datafusion_common::{DFSchema, DataFusionError, Result, TableReference};
+use datafusion_expr::{AggregateUDF, Expr, ScalarUDF, TableSource};
+use datafusion_sql::planner::{ContextProvider, PlannerContext, SqlToRel};
+use datafusion_sql::sqlparser::dialect::GenericDialect;
+use datafusion_sql::sqlparser::parser::Parser;
+use datafusion_sql::sqlparser::tokenizer::Token;
+
+/// Plans a SQL expression, such as the predicate `x > 10 AND y IS NOT NULL`, against
+/// the columns of `schema`. It may call the functions registered with the session of
+/// `task_ctx`, but may not refer to tables, so subqueries are rejected.
+pub fn sql_to_expr(task_ctx: &TaskContext, schema: &DFSchema, sql: &str) -> Result<Expr> {
+    let dialect = GenericDialect {};
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+    let sql_expr = parser.parse_expr()?;
+    let next = parser.peek_token().token;
+    if next != Token::EOF {
+        return Err(DataFusionError::Plan(format!(
+            "Unexpected {next} after the end of the expression"
+        )));
+    }
+
+    let provider = ExprContextProvider { task_ctx };
+    SqlToRel::new(&provider).sql_to_expr(sql_expr, schema, &mut PlannerContext::new())
+}
+
+/// Resolves the functions of a standalone SQL expression through a `TaskContext`
+struct ExprContextProvider<'a> {
+    task_ctx: &'a TaskContext,
+}
+
+impl ContextProvider for ExprContextProvider<'_> {
+    fn get_table_provider(&self, name: TableReference) -> Result<Arc<dyn TableSource>> {
+        Err(DataFusionError::Plan(format!(
+            "Cannot refer to table {name} in an expression"
+        )))
+    }
+
+    fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
+        self.task_ctx.udf(name).ok()
+    }
+
+    fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
+        self.task_ctx.udaf(name).ok()
+    }
+
+    fn get_variable_type(&self, _variable_names: &[String]) -> Option<DataType> {
+        None
+    }
+
+    fn options(&self) -> &ConfigOptions {
+        self.task_ctx.session_config().options()
+    }
+}
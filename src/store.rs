@@ -17,6 +17,7 @@
 
 use std::sync::Arc;
 
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
 use object_store::aws::{AmazonS3, AmazonS3Builder};
@@ -67,8 +68,12 @@ pub struct PyMicrosoftAzureContext {
 
 #[pymethods]
 impl PyMicrosoftAzureContext {
+    /// Credentials not given are read from the `AZURE_*` environment variables. At most
+    /// one kind of credential may be given: an access key, a bearer token, a client
+    /// secret with its client and tenant ids, or a SAS token, either as a query string
+    /// in `sas_token` or as its `sas_query_pairs`.
     #[allow(clippy::too_many_arguments)]
-    #[pyo3(signature = (container_name, account=None, access_key=None, bearer_token=None, client_id=None, client_secret=None, tenant_id=None, sas_query_pairs=None, use_emulator=None, allow_http=None))]
+    #[pyo3(signature = (container_name, account=None, access_key=None, bearer_token=None, client_id=None, client_secret=None, tenant_id=None, sas_query_pairs=None, use_emulator=None, allow_http=None, sas_token=None))]
     #[new]
     fn new(
         container_name: String,
@@ -81,7 +86,35 @@ impl PyMicrosoftAzureContext {
         sas_query_pairs: Option<Vec<(String, String)>>,
         use_emulator: Option<bool>,
         allow_http: Option<bool>,
-    ) -> Self {
+        sas_token: Option<String>,
+    ) -> PyResult<Self> {
+        let credentials = [
+            access_key.is_some(),
+            bearer_token.is_some(),
+            client_id.is_some() || client_secret.is_some() || tenant_id.is_some(),
+            sas_query_pairs.is_some(),
+            sas_token.is_some(),
+        ];
+        if credentials.iter().filter(|given| **given).count() > 1 {
+            return Err(PyValueError::new_err(
+                "Pass only one of access_key, bearer_token, client_id/client_secret/tenant_id, \
+                 sas_query_pairs or sas_token",
+            ));
+        }
+        let sas_query_pairs = match sas_token {
+            Some(token) => {
+                let pairs: Vec<(String, String)> =
+                    url::form_urlencoded::parse(token.trim_start_matches('?').as_bytes())
+                        .into_owned()
+                        .collect();
+                if pairs.is_empty() {
+                    return Err(PyValueError::new_err("Invalid SAS token, it is empty"));
+                }
+                Some(pairs)
+            }
+            None => sas_query_pairs,
+        };
+
         let mut builder = MicrosoftAzureBuilder::from_env().with_container_name(&container_name);
 
         if let Some(account) = account {
@@ -103,7 +136,9 @@ impl PyMicrosoftAzureContext {
             }
             (None, None, None) => {}
             _ => {
-                panic!("client_id, client_secret, tenat_id must be all set or all None");
+                return Err(PyValueError::new_err(
+                    "client_id, client_secret and tenant_id must be all set or all None",
+                ));
             }
         }
 
@@ -119,14 +154,11 @@ impl PyMicrosoftAzureContext {
             builder = builder.with_allow_http(allow_http);
         }
 
-        Self {
-            inner: Arc::new(
-                builder
-                    .build()
-                    .expect("Could not create Azure Storage context"), //TODO: change these to PyErr
-            ),
+        let store = builder.build().map_err(|e| build_err("Azure Storage", e))?;
+        Ok(Self {
+            inner: Arc::new(store),
             container_name,
-        }
+        })
     }
 }
 
@@ -139,24 +171,41 @@ pub struct PyGoogleCloudContext {
 
 #[pymethods]
 impl PyGoogleCloudContext {
-    #[allow(clippy::too_many_arguments)]
-    #[pyo3(signature = (bucket_name, service_account_path=None))]
+    /// Authenticates with the service account whose JSON key is in the file at
+    /// `service_account_path` or given as `service_account_key`. Without either the
+    /// `GOOGLE_*` environment variables are used, and then the credentials of the
+    /// instance metadata service.
+    #[pyo3(signature = (bucket_name, service_account_path=None, service_account_key=None))]
     #[new]
-    fn new(bucket_name: String, service_account_path: Option<String>) -> Self {
-        let mut builder = GoogleCloudStorageBuilder::new().with_bucket_name(&bucket_name);
+    fn new(
+        bucket_name: String,
+        service_account_path: Option<String>,
+        service_account_key: Option<String>,
+    ) -> PyResult<Self> {
+        let mut builder = GoogleCloudStorageBuilder::from_env().with_bucket_name(&bucket_name);
 
-        if let Some(credential_path) = service_account_path {
-            builder = builder.with_service_account_path(credential_path);
+        match (service_account_path, service_account_key) {
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "Pass service_account_path or service_account_key, not both",
+                ))
+            }
+            (Some(credential_path), None) => {
+                builder = builder.with_service_account_path(credential_path);
+            }
+            (None, Some(key)) => {
+                builder = builder.with_service_account_key(key);
+            }
+            (None, None) => {}
         }
 
-        Self {
-            inner: Arc::new(
-                builder
-                    .build()
-                    .expect("Could not create Google Cloud Storage"),
-            ),
+        let store = builder
+            .build()
+            .map_err(|e| build_err("Google Cloud Storage", e))?;
+        Ok(Self {
+            inner: Arc::new(store),
             bucket_name,
-        }
+        })
     }
 }
 
@@ -219,6 +268,10 @@ impl PyAmazonS3Context {
     }
 }
 
+fn build_err(store: &str, e: object_store::Error) -> PyErr {
+    PyValueError::new_err(format!("Could not create {store} object store: {e}"))
+}
+
 pub(crate) fn init_module(m: &PyModule) -> PyResult<()> {
     m.add_class::<PyAmazonS3Context>()?;
     m.add_class::<PyMicrosoftAzureContext>()?;
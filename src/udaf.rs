@@ -24,7 +24,10 @@ use datafusion::arrow::datatypes::DataType;
 use datafusion::arrow::pyarrow::{PyArrowConvert, PyArrowType};
 use datafusion::common::ScalarValue;
 use datafusion::error::{DataFusionError, Result};
-use datafusion_expr::{create_udaf, Accumulator, AccumulatorFunctionImplementation, AggregateUDF};
+use datafusion_expr::{
+    Accumulator, AccumulatorFunctionImplementation, AggregateUDF, ReturnTypeFunction, Signature,
+    StateTypeFunction,
+};
 
 use crate::expr::PyExpr;
 use crate::utils::parse_volatility;
@@ -72,18 +75,18 @@ impl Accumulator for RustAccumulator {
 
     fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
         Python::with_gil(|py| {
-            let state = &states[0];
-
-            // 1. cast states to Pyarrow array
-            let state = state
-                .into_data()
-                .to_pyarrow(py)
+            // 1. cast states to Pyarrow arrays, one per state value
+            let states = states
+                .iter()
+                .map(|state| state.into_data().to_pyarrow(py))
+                .collect::<PyResult<Vec<_>>>()
                 .map_err(|e| DataFusionError::Execution(format!("{e}")))?;
+            let states = PyTuple::new(py, states);
 
             // 2. call merge
             self.accum
                 .as_ref(py)
-                .call_method1("merge", (state,))
+                .call_method1("merge", states)
                 .map_err(|e| DataFusionError::Execution(format!("{e}")))?;
 
             Ok(())
@@ -106,6 +109,14 @@ pub fn to_rust_accumulator(accum: PyObject) -> AccumulatorFunctionImplementation
     })
 }
 
+/// The type of the single argument of an aggregate UDF, or a list with the type of
+/// each argument
+#[derive(FromPyObject)]
+enum InputTypes {
+    Single(PyArrowType<DataType>),
+    List(PyArrowType<Vec<DataType>>),
+}
+
 /// Represents an AggregateUDF
 #[pyclass(name = "AggregateUDF", module = "datafusion", subclass)]
 #[derive(Debug, Clone)]
@@ -115,22 +126,32 @@ pub struct PyAggregateUDF {
 
 #[pymethods]
 impl PyAggregateUDF {
+    /// The accumulator's `update` is called with one array per input type, and its
+    /// `merge` with one array per type in `state_type`
     #[new(name, accumulator, input_type, return_type, state_type, volatility)]
     fn new(
         name: &str,
         accumulator: PyObject,
-        input_type: PyArrowType<DataType>,
+        input_type: InputTypes,
         return_type: PyArrowType<DataType>,
         state_type: PyArrowType<Vec<DataType>>,
         volatility: &str,
     ) -> PyResult<Self> {
-        let function = create_udaf(
+        let input_types = match input_type {
+            InputTypes::Single(input_type) => vec![input_type.0],
+            InputTypes::List(input_types) => input_types.0,
+        };
+        let signature = Signature::exact(input_types, parse_volatility(volatility)?);
+        let return_type = Arc::new(return_type.0);
+        let return_type: ReturnTypeFunction = Arc::new(move |_| Ok(return_type.clone()));
+        let state_type = Arc::new(state_type.0);
+        let state_type: StateTypeFunction = Arc::new(move |_| Ok(state_type.clone()));
+        let function = AggregateUDF::new(
             name,
-            input_type.0,
-            Arc::new(return_type.0),
-            parse_volatility(volatility)?,
-            to_rust_accumulator(accumulator),
-            Arc::new(state_type.0),
+            &signature,
+            &return_type,
+            &to_rust_accumulator(accumulator),
+            &state_type,
         );
         Ok(Self { function })
     }
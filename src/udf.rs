@@ -15,19 +15,35 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use std::sync::Arc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
 
-use pyo3::{prelude::*, types::PyTuple};
+use parking_lot::{const_mutex, Mutex};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyTuple};
 
-use datafusion::arrow::array::{make_array, Array, ArrayData, ArrayRef};
+use datafusion::arrow::array::{
+    make_array, new_null_array, Array, ArrayData, ArrayRef, UInt32Array,
+};
+use datafusion::arrow::compute::{cast, take};
 use datafusion::arrow::datatypes::DataType;
 use datafusion::arrow::pyarrow::{PyArrowConvert, PyArrowType};
+use datafusion::arrow::row::{RowConverter, SortField};
 use datafusion::error::DataFusionError;
 use datafusion::physical_plan::functions::make_scalar_function;
 use datafusion::physical_plan::udf::ScalarUDF;
+use datafusion_common::config::ConfigOptions;
+use datafusion_common::tree_node::{Transformed, TreeNode};
+use datafusion_common::ScalarValue;
 use datafusion_expr::create_udf;
+use datafusion_expr::expr::{self, Exists, InSubquery};
 use datafusion_expr::function::ScalarFunctionImplementation;
+use datafusion_expr::utils::from_plan;
+use datafusion_expr::{ColumnarValue, Expr, LogicalPlan, Subquery};
+use datafusion_optimizer::analyzer::AnalyzerRule;
 
+use crate::common::data_type::DataTypeMap;
+use crate::errors::DataFusionError as PyDataFusionError;
 use crate::expr::PyExpr;
 use crate::utils::parse_volatility;
 
@@ -59,6 +75,336 @@ fn to_rust_function(func: PyObject) -> ScalarFunctionImplementation {
     )
 }
 
+/// Create a DataFusion's UDF implementation from a python function that is
+/// called once per row with Python values, such as `int` or `str`, with `None`
+/// for nulls. The returned values are converted to an array of `return_type`, or
+/// of the type pyarrow infers for them if it is not given. This is much slower
+/// than calling a function once per batch with arrays.
+fn to_rust_row_function(
+    func: PyObject,
+    return_type: Option<DataType>,
+) -> ScalarFunctionImplementation {
+    make_scalar_function(
+        move |args: &[ArrayRef]| -> Result<ArrayRef, DataFusionError> {
+            Python::with_gil(|py| {
+                let py_err = |e: PyErr| DataFusionError::Execution(format!("{e:?}"));
+                let num_rows = args.first().map_or(0, |arg| arg.len());
+                let columns = args
+                    .iter()
+                    .map(|arg| {
+                        arg.into_data()
+                            .to_pyarrow(py)?
+                            .call_method0(py, "to_pylist")?
+                            .extract::<Vec<PyObject>>(py)
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+                    .map_err(py_err)?;
+
+                let mut results = Vec::with_capacity(num_rows);
+                for row in 0..num_rows {
+                    let row_args = PyTuple::new(py, columns.iter().map(|column| &column[row]));
+                    results.push(func.as_ref(py).call1(row_args).map_err(py_err)?);
+                }
+
+                let array = py
+                    .import("pyarrow")
+                    .and_then(|pa| {
+                        let return_type = match &return_type {
+                            Some(return_type) => return_type.to_pyarrow(py)?,
+                            None => py.None(),
+                        };
+                        pa.call_method1("array", (results, return_type))
+                    })
+                    .map_err(py_err)?;
+                let array_data = ArrayData::from_pyarrow(array).map_err(py_err)?;
+                Ok(make_array(array_data))
+            })
+        },
+    )
+}
+
+/// Wraps `function` to raise an error when it returns a type other than the declared
+/// `return_type`, which would otherwise surface as a confusing error downstream
+fn check_return_type(
+    name: String,
+    function: ScalarFunctionImplementation,
+    return_type: DataType,
+) -> ScalarFunctionImplementation {
+    Arc::new(move |args: &[ColumnarValue]| {
+        let result = function(args)?;
+        if result.data_type() != return_type {
+            return Err(DataFusionError::Execution(format!(
+                "UDF {name} returned {} but its return type is {return_type}",
+                result.data_type()
+            )));
+        }
+        Ok(result)
+    })
+}
+
+/// Infers the return type of a UDF by calling it once on a row of sample values of
+/// `input_types`, which are zero or empty values where possible and nulls otherwise
+fn infer_return_type(
+    name: &str,
+    function: &ScalarFunctionImplementation,
+    input_types: &[DataType],
+) -> Result<DataType, DataFusionError> {
+    let args = input_types
+        .iter()
+        .map(|data_type| {
+            let sample = DataTypeMap::map_from_arrow_type(data_type)
+                .and_then(|map| map.default_value())
+                .ok()
+                .and_then(|value| cast(&value.0.to_array_of_size(1), data_type).ok())
+                .unwrap_or_else(|| new_null_array(data_type, 1));
+            ColumnarValue::Array(sample)
+        })
+        .collect::<Vec<_>>();
+    match function(&args)?.data_type() {
+        DataType::Null => Err(DataFusionError::Plan(format!(
+            "Cannot infer the return type of UDF {name} from its output for sample \
+             arguments, which has type Null; pass return_type"
+        ))),
+        data_type => Ok(data_type),
+    }
+}
+
+/// The results of a UDF for the rows of arguments it has seen, keyed by the row format
+/// encoding of the arguments, holding at most `capacity` entries
+#[derive(Debug)]
+struct ResultCache {
+    results: HashMap<Vec<u8>, ScalarValue>,
+    capacity: usize,
+}
+
+/// Wraps `function` to call it only for rows of arguments it has not seen before, and
+/// to take the results of the others from `cache`. Rows that repeat within a batch are
+/// also computed once. When the cache is full it is cleared, so memory stays bounded.
+fn cached_function(
+    function: ScalarFunctionImplementation,
+    input_types: Vec<DataType>,
+    cache: Arc<Mutex<ResultCache>>,
+) -> ScalarFunctionImplementation {
+    Arc::new(move |args: &[ColumnarValue]| {
+        // only arguments that are all literals have no array, those are evaluated once
+        let num_rows = match args.iter().find_map(|arg| match arg {
+            ColumnarValue::Array(array) => Some(array.len()),
+            ColumnarValue::Scalar(_) => None,
+        }) {
+            Some(num_rows) if num_rows > 0 => num_rows,
+            _ => return function(args),
+        };
+        let arrays: Vec<ArrayRef> = args
+            .iter()
+            .map(|arg| arg.clone().into_array(num_rows))
+            .collect();
+        let mut converter = RowConverter::new(
+            input_types
+                .iter()
+                .map(|data_type| SortField::new(data_type.clone()))
+                .collect(),
+        )?;
+        let rows = converter.convert_columns(&arrays)?;
+
+        // the rows to compute, each once, and the results of the others
+        let mut results: HashMap<Vec<u8>, ScalarValue> = HashMap::new();
+        let mut missing: Vec<u32> = vec![];
+        {
+            let cache = cache.lock();
+            for (i, row) in rows.iter().enumerate() {
+                let key = row.as_ref();
+                if results.contains_key(key) {
+                    continue;
+                }
+                match cache.results.get(key) {
+                    Some(value) => {
+                        results.insert(key.to_vec(), value.clone());
+                    }
+                    None => {
+                        missing.push(i as u32);
+                        results.insert(key.to_vec(), ScalarValue::Null);
+                    }
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let indices = UInt32Array::from(missing.clone());
+            let missing_args = arrays
+                .iter()
+                .map(|array| Ok(ColumnarValue::Array(take(array.as_ref(), &indices, None)?)))
+                .collect::<Result<Vec<_>, DataFusionError>>()?;
+            let computed = function(&missing_args)?.into_array(missing.len());
+            let mut cache = cache.lock();
+            if cache.results.len() + missing.len() > cache.capacity {
+                cache.results.clear();
+            }
+            for (j, i) in missing.iter().enumerate() {
+                let key = rows.row(*i as usize).as_ref().to_vec();
+                let value = ScalarValue::try_from_array(&computed, j)?;
+                if cache.results.len() < cache.capacity {
+                    cache.results.insert(key.clone(), value.clone());
+                }
+                results.insert(key, value);
+            }
+        }
+
+        let values = rows.iter().map(|row| results[row.as_ref()].clone());
+        Ok(ColumnarValue::Array(ScalarValue::iter_to_array(values)?))
+    })
+}
+
+/// What a UDF created with a `cache_size` is made of, to wrap it with a new cache
+#[derive(Clone)]
+struct CachedUdf {
+    implementation: ScalarFunctionImplementation,
+    input_types: Vec<DataType>,
+    capacity: usize,
+}
+
+type WeakFunction =
+    Weak<dyn Fn(&[ColumnarValue]) -> Result<ColumnarValue, DataFusionError> + Send + Sync>;
+
+/// The UDFs created with a `cache_size`, by their function, which is only held weakly
+/// so that an entry is dropped with the last expression calling its UDF
+static CACHED_UDFS: Mutex<Vec<(WeakFunction, CachedUdf)>> = const_mutex(Vec::new());
+
+fn register_cached_udf(function: &ScalarFunctionImplementation, cached: CachedUdf) {
+    let mut cached_udfs = CACHED_UDFS.lock();
+    cached_udfs.retain(|(function, _)| function.strong_count() > 0);
+    cached_udfs.push((Arc::downgrade(function), cached));
+}
+
+/// The parts of the UDF whose function is `function` if it was created with a
+/// `cache_size`. Functions that are alive have distinct addresses.
+fn find_cached_udf(function: &ScalarFunctionImplementation) -> Option<CachedUdf> {
+    let address = Arc::as_ptr(function) as *const ();
+    CACHED_UDFS
+        .lock()
+        .iter()
+        .find(|(cached, _)| cached.strong_count() > 0 && cached.as_ptr() as *const () == address)
+        .map(|(_, cached)| cached.clone())
+}
+
+/// Analyzer rule that replaces every UDF created with a `cache_size` by one with a new
+/// empty cache, shared by all its calls in the plan. The analyzer runs whenever a query
+/// is planned, so results are reused within a query execution but never across them.
+pub(crate) struct UdfCacheScope;
+
+impl AnalyzerRule for UdfCacheScope {
+    fn analyze(
+        &self,
+        plan: LogicalPlan,
+        _config: &ConfigOptions,
+    ) -> Result<LogicalPlan, DataFusionError> {
+        if CACHED_UDFS.lock().is_empty() {
+            return Ok(plan);
+        }
+        let scoped = RefCell::new(HashMap::new());
+        scope_plan(plan, &scoped)
+    }
+
+    fn name(&self) -> &str {
+        "udf_cache_scope"
+    }
+}
+
+/// The UDFs given a cache for the plan being analyzed, by the address of their function
+type ScopedUdfs = RefCell<HashMap<*const (), Arc<ScalarUDF>>>;
+
+/// `plan` with the cached UDFs it calls, also in its inputs and subqueries, replaced by
+/// the ones in `scoped`. `LogicalPlan::transform_up` cannot be used as the replacements
+/// compare equal to the UDFs they replace, which it takes as nothing having changed.
+fn scope_plan(plan: LogicalPlan, scoped: &ScopedUdfs) -> Result<LogicalPlan, DataFusionError> {
+    let changed = RefCell::new(false);
+    let inputs = plan
+        .inputs()
+        .into_iter()
+        .map(|input| scope_plan(input.clone(), scoped))
+        .collect::<Result<Vec<_>, _>>()?;
+    let exprs = plan
+        .expressions()
+        .into_iter()
+        .map(|expr| scope_expr(expr, scoped, &changed))
+        .collect::<Result<Vec<_>, _>>()?;
+    if changed.into_inner() {
+        from_plan(&plan, &exprs, &inputs)
+    } else if !inputs.is_empty() {
+        plan.with_new_inputs(&inputs)
+    } else {
+        Ok(plan)
+    }
+}
+
+fn scope_expr(
+    expr: Expr,
+    scoped: &ScopedUdfs,
+    changed: &RefCell<bool>,
+) -> Result<Expr, DataFusionError> {
+    let scope_subquery = |subquery: Subquery| -> Result<Subquery, DataFusionError> {
+        *changed.borrow_mut() = true;
+        Ok(Subquery {
+            subquery: Arc::new(scope_plan(subquery.subquery.as_ref().clone(), scoped)?),
+            outer_ref_columns: subquery.outer_ref_columns,
+        })
+    };
+    expr.transform_up(&|expr| {
+        let expr = match expr {
+            Expr::ScalarUDF(expr::ScalarUDF { fun, args }) => match scoped_udf(&fun, scoped) {
+                Some(fun) => {
+                    *changed.borrow_mut() = true;
+                    Expr::ScalarUDF(expr::ScalarUDF { fun, args })
+                }
+                None => {
+                    return Ok(Transformed::No(Expr::ScalarUDF(expr::ScalarUDF {
+                        fun,
+                        args,
+                    })))
+                }
+            },
+            Expr::ScalarSubquery(subquery) => Expr::ScalarSubquery(scope_subquery(subquery)?),
+            Expr::Exists(Exists { subquery, negated }) => Expr::Exists(Exists {
+                subquery: scope_subquery(subquery)?,
+                negated,
+            }),
+            Expr::InSubquery(InSubquery {
+                expr,
+                subquery,
+                negated,
+            }) => Expr::InSubquery(InSubquery {
+                expr,
+                subquery: scope_subquery(subquery)?,
+                negated,
+            }),
+            expr => return Ok(Transformed::No(expr)),
+        };
+        Ok(Transformed::Yes(expr))
+    })
+}
+
+/// The UDF with a cache for the plan being analyzed that replaces `fun`, if it was
+/// created with a `cache_size`
+fn scoped_udf(fun: &Arc<ScalarUDF>, scoped: &ScopedUdfs) -> Option<Arc<ScalarUDF>> {
+    let address = Arc::as_ptr(&fun.fun) as *const ();
+    if let Some(scoped_fun) = scoped.borrow().get(&address) {
+        return Some(scoped_fun.clone());
+    }
+    let cached = find_cached_udf(&fun.fun)?;
+    let cache = Arc::new(Mutex::new(ResultCache {
+        results: HashMap::new(),
+        capacity: cached.capacity,
+    }));
+    let function = cached_function(cached.implementation, cached.input_types, cache);
+    let scoped_fun = Arc::new(ScalarUDF::new(
+        &fun.name,
+        &fun.signature,
+        &fun.return_type,
+        &function,
+    ));
+    scoped.borrow_mut().insert(address, scoped_fun.clone());
+    Some(scoped_fun)
+}
+
 /// Represents a PyScalarUDF
 #[pyclass(name = "ScalarUDF", module = "datafusion", subclass)]
 #[derive(Debug, Clone)]
@@ -68,20 +414,61 @@ pub struct PyScalarUDF {
 
 #[pymethods]
 impl PyScalarUDF {
-    #[new(name, func, input_types, return_type, volatility)]
+    /// With `cache_size`, the results for up to that many distinct rows of arguments are
+    /// remembered and reused instead of calling `func` again for them, within each query
+    #[new]
+    #[pyo3(signature = (name, func, input_types, return_type, volatility, scalar=false, cache_size=None))]
     fn new(
         name: &str,
         func: PyObject,
         input_types: PyArrowType<Vec<DataType>>,
-        return_type: PyArrowType<DataType>,
+        return_type: Option<PyArrowType<DataType>>,
         volatility: &str,
+        scalar: bool,
+        cache_size: Option<usize>,
     ) -> PyResult<Self> {
+        let volatility = parse_volatility(volatility)?;
+        let return_type = return_type.map(|return_type| return_type.0);
+        let implementation = if scalar {
+            to_rust_row_function(func, return_type.clone())
+        } else {
+            to_rust_function(func)
+        };
+        let return_type = match return_type {
+            Some(return_type) => return_type,
+            None => infer_return_type(name, &implementation, &input_types.0)
+                .map_err(PyDataFusionError::from)?,
+        };
+        let implementation =
+            check_return_type(name.to_string(), implementation, return_type.clone());
+        let function = match cache_size {
+            Some(capacity) => {
+                let sort_fields = input_types.0.iter().cloned().map(SortField::new).collect();
+                if let Err(e) = RowConverter::new(sort_fields) {
+                    return Err(PyValueError::new_err(format!(
+                        "Cannot cache the results of UDF {name}: {e}"
+                    )));
+                }
+                // calls are uncached unless `UdfCacheScope` gives them a cache for the
+                // query, so the function must be distinct from the implementation it finds
+                let uncached = implementation.clone();
+                let function: ScalarFunctionImplementation = Arc::new(move |args| uncached(args));
+                let cached = CachedUdf {
+                    implementation,
+                    input_types: input_types.0.clone(),
+                    capacity,
+                };
+                register_cached_udf(&function, cached);
+                function
+            }
+            None => implementation,
+        };
         let function = create_udf(
             name,
             input_types.0,
-            Arc::new(return_type.0),
-            parse_volatility(volatility)?,
-            to_rust_function(func),
+            Arc::new(return_type),
+            volatility,
+            function,
         );
         Ok(Self { function })
     }
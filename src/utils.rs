@@ -17,9 +17,14 @@
 
 use crate::errors::DataFusionError;
 use crate::TokioRuntime;
+use datafusion::datasource::listing::ListingTableUrl;
 use datafusion_expr::Volatility;
+use futures::TryStreamExt;
+use object_store::{ObjectMeta, ObjectStore};
+use pyo3::exceptions::{PyTimeoutError, PyValueError};
 use pyo3::prelude::*;
 use std::future::Future;
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 
 /// Utility to get the Tokio Runtime from Python
@@ -28,6 +33,32 @@ pub(crate) fn get_tokio_runtime(py: Python) -> PyRef<TokioRuntime> {
     datafusion.getattr("runtime").unwrap().extract().unwrap()
 }
 
+/// The files of `table_path` in `store` whose names end with `file_extension`, like
+/// DataFusion's `ListingTableUrl::list_all_files` which is not public
+pub(crate) async fn list_all_files(
+    table_path: &ListingTableUrl,
+    store: &dyn ObjectStore,
+    file_extension: &str,
+) -> datafusion_common::Result<Vec<ObjectMeta>> {
+    // a path ending with a slash is a directory to list, anything else a single file
+    let files = match table_path.as_str().ends_with('/') {
+        true => {
+            store
+                .list(Some(table_path.prefix()))
+                .await?
+                .try_collect()
+                .await?
+        }
+        false => vec![store.head(table_path.prefix()).await?],
+    };
+    Ok(files
+        .into_iter()
+        .filter(|meta| {
+            meta.location.as_ref().ends_with(file_extension) && table_path.contains(&meta.location)
+        })
+        .collect())
+}
+
 /// Utility to collect rust futures with GIL released
 pub fn wait_for_future<F: Future>(py: Python, f: F) -> F::Output
 where
@@ -38,6 +69,59 @@ where
     py.allow_threads(|| runtime.block_on(f))
 }
 
+/// Like `wait_for_future`, but raises a Python `TimeoutError` if `f` has not completed
+/// after `timeout` seconds. On expiry `f` is dropped, cancelling the work it started.
+pub(crate) fn wait_for_future_with_timeout<F: Future>(
+    py: Python,
+    f: F,
+    timeout: Option<f64>,
+) -> PyResult<F::Output>
+where
+    F: Send,
+    F::Output: Send,
+{
+    match timeout {
+        Some(timeout) => {
+            let deadline = deadline_after(timeout)?;
+            wait_for_future_until(py, f, deadline).ok_or_else(|| timeout_err(timeout))
+        }
+        None => Ok(wait_for_future(py, f)),
+    }
+}
+
+/// Collects `f` with the GIL released, or returns `None` and drops `f` if it has not
+/// completed by `deadline`
+pub(crate) fn wait_for_future_until<F: Future>(
+    py: Python,
+    f: F,
+    deadline: Instant,
+) -> Option<F::Output>
+where
+    F: Send,
+    F::Output: Send,
+{
+    let runtime: &Runtime = &get_tokio_runtime(py).0;
+    py.allow_threads(|| {
+        runtime.block_on(async { tokio::time::timeout_at(deadline.into(), f).await })
+    })
+    .ok()
+}
+
+/// The instant `timeout` seconds, as given from Python, from now
+pub(crate) fn deadline_after(timeout: f64) -> PyResult<Instant> {
+    if timeout.is_finite() && timeout > 0.0 {
+        Ok(Instant::now() + Duration::from_secs_f64(timeout))
+    } else {
+        Err(PyValueError::new_err(format!(
+            "timeout must be a positive number of seconds, got {timeout}"
+        )))
+    }
+}
+
+pub(crate) fn timeout_err(timeout: f64) -> PyErr {
+    PyTimeoutError::new_err(format!("Query did not complete within {timeout} seconds"))
+}
+
 pub(crate) fn parse_volatility(value: &str) -> Result<Volatility, DataFusionError> {
     Ok(match value {
         "immutable" => Volatility::Immutable,
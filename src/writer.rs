@@ -0,0 +1,586 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Writes the output of a `DataFrame` to files in the object store registered for their
+//! path, encoding and uploading it one record batch at a time.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use async_compression::tokio::write::{BzEncoder, GzipEncoder, XzEncoder, ZstdEncoder};
+use datafusion::arrow::array::{Array, UInt32Array};
+use datafusion::arrow::compute::take;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::json::LineDelimitedWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
+use datafusion::dataframe::DataFrame;
+use datafusion::datasource::listing::ListingTableUrl;
+use datafusion::execution::context::TaskContext;
+use datafusion::parquet::arrow::ArrowWriter;
+use datafusion::parquet::file::properties::WriterProperties;
+use datafusion::physical_plan::{execute_stream_partitioned, SendableRecordBatchStream};
+use datafusion_common::parsers::CompressionTypeVariant;
+use datafusion_common::{DataFusionError, Result};
+use futures::future::try_join_all;
+use futures::StreamExt;
+use object_store::path::Path;
+use object_store::{MultipartId, ObjectStore};
+use parking_lot::Mutex;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// The bytes an encoder has written that are not uploaded yet
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Encodes the record batches of one file into the `SharedBuffer` it was created with
+pub(crate) trait BatchEncoder: Send {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()>;
+
+    /// Writes whatever ends the file, such as the Parquet footer
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// A file format that record batches can be written in
+pub(crate) trait FileFormatWriter: Send + Sync {
+    /// The extension of the files written to a directory, e.g. `parquet`
+    fn extension(&self) -> String;
+
+    /// The compression applied to the whole encoded file
+    fn compression(&self) -> CompressionTypeVariant {
+        CompressionTypeVariant::UNCOMPRESSED
+    }
+
+    fn create_encoder(
+        &self,
+        buffer: SharedBuffer,
+        schema: SchemaRef,
+    ) -> Result<Box<dyn BatchEncoder>>;
+}
+
+/// Executes `df` and writes its output to `path`, a local path or the URL of a
+/// registered object store. With `single_file` the partitions are written one after
+/// the other to the file `path`, otherwise each to a `part-{i}` file in the directory
+/// `path`. With `partition_by` the rows are further split into Hive-style directories,
+/// such as `year=2023/month=07`, by the values of those columns, which are left out of
+/// the files.
+pub(crate) async fn write_files(
+    df: DataFrame,
+    path: &str,
+    single_file: bool,
+    partition_by: &[String],
+    format: &dyn FileFormatWriter,
+) -> Result<()> {
+    if single_file && !partition_by.is_empty() {
+        return Err(DataFusionError::Plan(
+            "Cannot write a single file partitioned by columns".to_string(),
+        ));
+    }
+    let url = ListingTableUrl::parse(path)?;
+    let (state, plan) = df.into_parts();
+    let task_ctx = Arc::new(TaskContext::from(&state));
+    let store = task_ctx.runtime_env().object_store(&url)?;
+    let plan = state.create_physical_plan(&plan).await?;
+    let schema = plan.schema();
+    let partitioning = HivePartitioning::try_new(&schema, partition_by)?;
+    let streams = execute_stream_partitioned(plan, task_ctx)?;
+
+    if single_file {
+        return write_file(store.as_ref(), url.prefix(), schema, streams, format).await;
+    }
+    try_join_all(streams.into_iter().enumerate().map(|(i, stream)| {
+        let (store, schema) = (store.clone(), schema.clone());
+        let (root, partitioning) = (url.prefix(), partitioning.as_ref());
+        async move {
+            match partitioning {
+                Some(partitioning) => {
+                    write_partitioned(store.as_ref(), root, i, stream, partitioning, format).await
+                }
+                None => {
+                    let location = root.child(format!("part-{i}.{}", format.extension()));
+                    write_file(store.as_ref(), &location, schema, vec![stream], format).await
+                }
+            }
+        }
+    }))
+    .await?;
+    Ok(())
+}
+
+/// Writes the batches of `streams`, in order, to one file at `location`
+async fn write_file(
+    store: &dyn ObjectStore,
+    location: &Path,
+    schema: SchemaRef,
+    streams: Vec<SendableRecordBatchStream>,
+    format: &dyn FileFormatWriter,
+) -> Result<()> {
+    let mut file = FileUpload::start(store, location.clone(), schema, format).await?;
+    let result = async {
+        for mut stream in streams {
+            while let Some(batch) = stream.next().await.transpose()? {
+                file.write(&batch).await?;
+            }
+        }
+        Ok::<_, DataFusionError>(())
+    }
+    .await;
+    match result {
+        Ok(()) => file.finish().await,
+        Err(e) => {
+            file.abort().await?;
+            Err(e)
+        }
+    }
+}
+
+/// Writes the batches of `stream`, the `part`-th partition of the output, to a file in
+/// the Hive-style directory under `root` of each partition value its rows have
+async fn write_partitioned(
+    store: &dyn ObjectStore,
+    root: &Path,
+    part: usize,
+    mut stream: SendableRecordBatchStream,
+    partitioning: &HivePartitioning,
+    format: &dyn FileFormatWriter,
+) -> Result<()> {
+    let mut files: HashMap<String, FileUpload> = HashMap::new();
+    let result = async {
+        while let Some(batch) = stream.next().await.transpose()? {
+            for (directory, batch) in partitioning.split(&batch)? {
+                let file = match files.entry(directory) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let location = entry
+                            .key()
+                            .split('/')
+                            .fold(root.clone(), |path, segment| path.child(segment))
+                            .child(format!("part-{part}.{}", format.extension()));
+                        let schema = partitioning.file_schema.clone();
+                        entry.insert(FileUpload::start(store, location, schema, format).await?)
+                    }
+                };
+                file.write(&batch).await?;
+            }
+        }
+        Ok::<_, DataFusionError>(())
+    }
+    .await;
+    match result {
+        Ok(()) => try_join_all(files.into_values().map(FileUpload::finish))
+            .await
+            .map(|_| ()),
+        Err(e) => {
+            try_join_all(files.into_values().map(FileUpload::abort)).await?;
+            Err(e)
+        }
+    }
+}
+
+/// A file being encoded and uploaded, with what has been encoded uploaded after every
+/// batch. It must be finished, or aborted if writing fails.
+struct FileUpload<'a> {
+    store: &'a dyn ObjectStore,
+    location: Path,
+    upload_id: MultipartId,
+    upload: Box<dyn AsyncWrite + Send + Unpin>,
+    buffer: SharedBuffer,
+    encoder: Box<dyn BatchEncoder>,
+}
+
+impl<'a> FileUpload<'a> {
+    async fn start(
+        store: &'a dyn ObjectStore,
+        location: Path,
+        schema: SchemaRef,
+        format: &dyn FileFormatWriter,
+    ) -> Result<FileUpload<'a>> {
+        let buffer = SharedBuffer::default();
+        let encoder = format.create_encoder(buffer.clone(), schema)?;
+        let (upload_id, upload) = store.put_multipart(&location).await?;
+        let upload = compressed_upload(upload, format.compression());
+        Ok(FileUpload {
+            store,
+            location,
+            upload_id,
+            upload,
+            buffer,
+            encoder,
+        })
+    }
+
+    async fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.encoder.write(batch)?;
+        self.upload.write_all(&self.buffer.take()).await?;
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<()> {
+        self.encoder.finish()?;
+        self.upload.write_all(&self.buffer.take()).await?;
+        self.upload.shutdown().await?;
+        Ok(())
+    }
+
+    async fn abort(self) -> Result<()> {
+        self.store
+            .abort_multipart(&self.location, &self.upload_id)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Wraps `upload` so that the bytes written to it are compressed with `compression`
+fn compressed_upload(
+    upload: Box<dyn AsyncWrite + Send + Unpin>,
+    compression: CompressionTypeVariant,
+) -> Box<dyn AsyncWrite + Send + Unpin> {
+    match compression {
+        CompressionTypeVariant::GZIP => Box::new(GzipEncoder::new(upload)),
+        CompressionTypeVariant::BZIP2 => Box::new(BzEncoder::new(upload)),
+        CompressionTypeVariant::XZ => Box::new(XzEncoder::new(upload)),
+        CompressionTypeVariant::ZSTD => Box::new(ZstdEncoder::new(upload)),
+        CompressionTypeVariant::UNCOMPRESSED => upload,
+    }
+}
+
+/// The directory name of a null or empty partition value, as Hive names it
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Splits batches by the values of the partition columns
+struct HivePartitioning {
+    /// The index and name of each partition column
+    columns: Vec<(usize, String)>,
+    /// The indices of the columns written to the files
+    file_columns: Vec<usize>,
+    file_schema: SchemaRef,
+}
+
+impl HivePartitioning {
+    fn try_new(schema: &SchemaRef, partition_by: &[String]) -> Result<Option<Self>> {
+        if partition_by.is_empty() {
+            return Ok(None);
+        }
+        let columns = partition_by
+            .iter()
+            .map(|name| Ok((schema.index_of(name)?, name.clone())))
+            .collect::<Result<Vec<_>>>()?;
+        let file_columns: Vec<usize> = (0..schema.fields().len())
+            .filter(|i| !columns.iter().any(|(column, _)| column == i))
+            .collect();
+        if file_columns.is_empty() {
+            return Err(DataFusionError::Plan(
+                "Cannot partition by every column, no column would be left to write".to_string(),
+            ));
+        }
+        let file_schema = Arc::new(schema.project(&file_columns)?);
+        Ok(Some(HivePartitioning {
+            columns,
+            file_columns,
+            file_schema,
+        }))
+    }
+
+    /// Groups the rows of `batch` by their partition directory, e.g. `year=2023`,
+    /// without the partition columns
+    fn split(&self, batch: &RecordBatch) -> Result<Vec<(String, RecordBatch)>> {
+        let options = FormatOptions::default();
+        let formatters = self
+            .columns
+            .iter()
+            .map(|(i, _)| ArrayFormatter::try_new(batch.column(*i).as_ref(), &options))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut groups: Vec<(String, Vec<u32>)> = vec![];
+        let mut group_of_directory: HashMap<String, usize> = HashMap::new();
+        for row in 0..batch.num_rows() {
+            let mut segments = vec![];
+            for ((i, name), formatter) in self.columns.iter().zip(&formatters) {
+                let value = match batch.column(*i).is_null(row) {
+                    true => String::new(),
+                    false => formatter.value(row).try_to_string()?,
+                };
+                let value = match value.is_empty() {
+                    true => HIVE_DEFAULT_PARTITION.to_string(),
+                    false => escape_partition_value(&value),
+                };
+                segments.push(format!("{}={value}", escape_partition_value(name)));
+            }
+            let directory = segments.join("/");
+            let group = *group_of_directory
+                .entry(directory)
+                .or_insert_with_key(|directory| {
+                    groups.push((directory.clone(), vec![]));
+                    groups.len() - 1
+                });
+            groups[group].1.push(row as u32);
+        }
+
+        groups
+            .into_iter()
+            .map(|(directory, rows)| {
+                let indices = UInt32Array::from(rows);
+                let columns = self
+                    .file_columns
+                    .iter()
+                    .map(|i| take(batch.column(*i).as_ref(), &indices, None))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                Ok((
+                    directory,
+                    RecordBatch::try_new(self.file_schema.clone(), columns)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Percent-encodes the characters Hive escapes in partition directory names, including
+/// path separators
+fn escape_partition_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_control() || "\"#%'*/:=?\\{[]^".contains(c) {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                escaped.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Writes Parquet files with `properties`. A row group is also ended once the batches
+/// buffered for it take `max_row_group_bytes` of memory.
+pub(crate) struct ParquetWriter {
+    pub properties: WriterProperties,
+    pub max_row_group_bytes: Option<usize>,
+}
+
+impl FileFormatWriter for ParquetWriter {
+    fn extension(&self) -> String {
+        "parquet".to_string()
+    }
+
+    fn create_encoder(
+        &self,
+        buffer: SharedBuffer,
+        schema: SchemaRef,
+    ) -> Result<Box<dyn BatchEncoder>> {
+        let writer = ArrowWriter::try_new(buffer, schema, Some(self.properties.clone()))?;
+        Ok(Box::new(ParquetEncoder {
+            writer,
+            max_row_group_bytes: self.max_row_group_bytes,
+            buffered_bytes: 0,
+        }))
+    }
+}
+
+struct ParquetEncoder {
+    writer: ArrowWriter<SharedBuffer>,
+    max_row_group_bytes: Option<usize>,
+    buffered_bytes: usize,
+}
+
+impl BatchEncoder for ParquetEncoder {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.writer.write(batch)?;
+        if let Some(max_row_group_bytes) = self.max_row_group_bytes {
+            self.buffered_bytes += batch.get_array_memory_size();
+            if self.buffered_bytes >= max_row_group_bytes {
+                self.writer.flush()?;
+                self.buffered_bytes = 0;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+/// Writes CSV files, formatting values, including nulls, as `options` specifies.
+/// Fields are quoted with `quote` when they contain the delimiter, the quote or a line
+/// break.
+#[derive(Debug, Clone)]
+pub(crate) struct CsvWriter {
+    pub with_header: bool,
+    pub delimiter: char,
+    pub quote: char,
+    pub null_value: String,
+    pub date_format: Option<String>,
+    pub timestamp_format: Option<String>,
+}
+
+impl CsvWriter {
+    fn push_field(&self, line: &mut String, value: &str) {
+        let needs_quotes = value
+            .chars()
+            .any(|c| c == self.delimiter || c == self.quote || c == '\n' || c == '\r');
+        if !needs_quotes {
+            line.push_str(value);
+            return;
+        }
+        line.push(self.quote);
+        for c in value.chars() {
+            if c == self.quote {
+                line.push(c);
+            }
+            line.push(c);
+        }
+        line.push(self.quote);
+    }
+
+    fn push_record<'a>(&self, out: &mut String, values: impl IntoIterator<Item = &'a str>) {
+        for (i, value) in values.into_iter().enumerate() {
+            if i > 0 {
+                out.push(self.delimiter);
+            }
+            self.push_field(out, value);
+        }
+        out.push('\n');
+    }
+}
+
+impl FileFormatWriter for CsvWriter {
+    fn extension(&self) -> String {
+        "csv".to_string()
+    }
+
+    fn create_encoder(
+        &self,
+        mut buffer: SharedBuffer,
+        schema: SchemaRef,
+    ) -> Result<Box<dyn BatchEncoder>> {
+        if self.with_header {
+            let mut header = String::new();
+            self.push_record(
+                &mut header,
+                schema.fields().iter().map(|field| field.name().as_str()),
+            );
+            buffer.write_all(header.as_bytes())?;
+        }
+        Ok(Box::new(CsvEncoder {
+            buffer,
+            writer: self.clone(),
+        }))
+    }
+}
+
+struct CsvEncoder {
+    buffer: SharedBuffer,
+    writer: CsvWriter,
+}
+
+impl BatchEncoder for CsvEncoder {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let writer = &self.writer;
+        let timestamp_format = writer.timestamp_format.as_deref();
+        let options = FormatOptions::default()
+            .with_null(&writer.null_value)
+            .with_date_format(writer.date_format.as_deref())
+            .with_datetime_format(timestamp_format)
+            .with_timestamp_format(timestamp_format)
+            .with_timestamp_tz_format(timestamp_format);
+        let formatters = batch
+            .columns()
+            .iter()
+            .map(|column| ArrayFormatter::try_new(column.as_ref(), &options))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut out = String::new();
+        for row in 0..batch.num_rows() {
+            let values = formatters
+                .iter()
+                .map(|formatter| formatter.value(row).try_to_string())
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            writer.push_record(&mut out, values.iter().map(String::as_str));
+        }
+        self.buffer.write_all(out.as_bytes())?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes newline-delimited JSON files, compressed as a whole with `compression`
+pub(crate) struct JsonWriter {
+    pub compression: CompressionTypeVariant,
+}
+
+impl FileFormatWriter for JsonWriter {
+    fn extension(&self) -> String {
+        match self.compression {
+            CompressionTypeVariant::GZIP => "json.gz",
+            CompressionTypeVariant::BZIP2 => "json.bz2",
+            CompressionTypeVariant::XZ => "json.xz",
+            CompressionTypeVariant::ZSTD => "json.zst",
+            CompressionTypeVariant::UNCOMPRESSED => "json",
+        }
+        .to_string()
+    }
+
+    fn compression(&self) -> CompressionTypeVariant {
+        self.compression
+    }
+
+    fn create_encoder(
+        &self,
+        buffer: SharedBuffer,
+        _schema: SchemaRef,
+    ) -> Result<Box<dyn BatchEncoder>> {
+        Ok(Box::new(JsonEncoder(LineDelimitedWriter::new(buffer))))
+    }
+}
+
+struct JsonEncoder(LineDelimitedWriter<SharedBuffer>);
+
+impl BatchEncoder for JsonEncoder {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.0.write(batch)?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.0.finish()?;
+        Ok(())
+    }
+}